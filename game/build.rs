@@ -0,0 +1,372 @@
+//! Превращает модели Wavefront OBJ из `assets/` в статические `Mesh`,
+//! а текстовые уровни `assets/*.map` — в двоичные карты NGTM.
+//!
+//! Для каждого `assets/<имя>.obj` в `$OUT_DIR/meshes.rs` появляется
+//! `pub static <ИМЯ>: Mesh<'static>`, который подключает `src/mesh.rs`.
+//! Поддерживаются `v`, `vt`, `vn`, `f` (многоугольники режутся веером),
+//! `usemtl` и `mtllib` (из материалов берётся только цвет `Kd`).
+//!
+//! Для каждого `assets/<имя>.map` появляется `$OUT_DIR/<имя>.ngtm`, а в
+//! `$OUT_DIR/levels.rs` — `pub static <ИМЯ>: &[u8]` с его содержимым для
+//! `src/tilemap.rs`. Формат текста описан в `assets/demo.map`.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Индексы вершин в `Mesh` — u16
+const MAX_VERTICES: usize = u16::MAX as usize;
+/// Номера граней у рёбер тоже u16, а `u16::MAX` занят под «второй грани нет»
+const MAX_TRIANGLES: usize = u16::MAX as usize;
+/// Грань без материала
+const DEFAULT_COLOR: u32 = 0xFFFF_FFFF;
+/// Слоёв в карте не больше (tilemap::MAX_LAYERS)
+const MAX_LAYERS: usize = 4;
+
+struct Model {
+    /// Уникальные сочетания (позиция, uv, нормаль)
+    vertices: Vec<([f32; 3], [f32; 2], [f32; 3])>,
+    /// Номер исходной позиции у каждой вершины: по нему ищутся общие рёбра
+    position_of: Vec<usize>,
+    triangles: Vec<[u16; 3]>,
+    colors: Vec<u32>,
+}
+
+fn main() {
+    let assets = Path::new("assets");
+    println!("cargo:rerun-if-changed=assets");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+
+    let mut out = String::from("// Сгенерировано build.rs из assets/*.obj, не править\n");
+    for path in &files(assets, "obj") {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source = fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let model = parse_obj(path, &source).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        emit(&mut out, &static_name(path), &model);
+    }
+    fs::write(out_dir.join("meshes.rs"), out).expect("не удалось записать meshes.rs");
+
+    let mut out = String::from("// Сгенерировано build.rs из assets/*.map, не править\n");
+    for path in &files(assets, "map") {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source = fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let level = encode_level(&source).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let file = format!("{}.ngtm", path.file_stem().unwrap().to_string_lossy());
+        fs::write(out_dir.join(&file), level).unwrap_or_else(|e| panic!("{}: {}", file, e));
+        writeln!(
+            out,
+            "\npub static {}: &[u8] = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{}\"));",
+            static_name(path),
+            file
+        )
+        .unwrap();
+    }
+    fs::write(out_dir.join("levels.rs"), out).expect("не удалось записать levels.rs");
+}
+
+/// Файлы каталога с расширением `extension` в алфавитном порядке
+fn files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .expect("нет каталога assets")
+        .map(|e| e.expect("ошибка чтения assets").path())
+        .filter(|p| p.extension().is_some_and(|e| e == extension))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Имя статической переменной по имени файла: `demo-1.map` — `DEMO_1`
+fn static_name(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().to_uppercase().replace(['-', '.', ' '], "_")
+}
+
+/// Номер элемента по индексу OBJ: с 1, отрицательные считаются от конца
+fn resolve(index: &str, len: usize, line: usize) -> Result<usize, String> {
+    let i: i64 = index.parse().map_err(|_| format!("строка {}: плохой индекс {:?}", line, index))?;
+    let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("строка {}: индекс {} вне списка из {}", line, i, len));
+    }
+    Ok(resolved as usize)
+}
+
+fn floats<const N: usize>(parts: &[&str], line: usize) -> Result<[f32; N], String> {
+    let mut v = [0.0; N];
+    for (i, slot) in v.iter_mut().enumerate() {
+        let s = parts.get(i).ok_or_else(|| format!("строка {}: мало чисел", line))?;
+        *slot = s.parse().map_err(|_| format!("строка {}: не число {:?}", line, s))?;
+    }
+    Ok(v)
+}
+
+fn parse_obj(path: &Path, source: &str) -> Result<Model, String> {
+    let (mut positions, mut uvs, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+    let mut materials = HashMap::new();
+    let mut color = DEFAULT_COLOR;
+    let mut model = Model { vertices: Vec::new(), position_of: Vec::new(), triangles: Vec::new(), colors: Vec::new() };
+    let mut known = HashMap::new();
+
+    for (n, text) in source.lines().enumerate() {
+        let line = n + 1;
+        let parts: Vec<&str> = text.split('#').next().unwrap().split_whitespace().collect();
+        let Some((&keyword, args)) = parts.split_first() else {
+            continue;
+        };
+        match keyword {
+            "v" => positions.push(floats::<3>(args, line)?),
+            "vt" => uvs.push(floats::<2>(args, line)?),
+            "vn" => normals.push(floats::<3>(args, line)?),
+            "mtllib" => {
+                let mtl = path.with_file_name(args.join(" "));
+                println!("cargo:rerun-if-changed={}", mtl.display());
+                let text = fs::read_to_string(&mtl).map_err(|e| format!("{}: {}", mtl.display(), e))?;
+                parse_mtl(&text, &mut materials)?;
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                color = *materials.get(&name).ok_or_else(|| format!("строка {}: нет материала {}", line, name))?;
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(format!("строка {}: у грани меньше трёх вершин", line));
+                }
+                let mut corners = Vec::new();
+                for corner in args {
+                    let mut refs = corner.split('/');
+                    let p = resolve(refs.next().unwrap(), positions.len(), line)?;
+                    let t = match refs.next() {
+                        Some("") | None => None,
+                        Some(s) => Some(resolve(s, uvs.len(), line)?),
+                    };
+                    let nm = match refs.next() {
+                        Some("") | None => None,
+                        Some(s) => Some(resolve(s, normals.len(), line)?),
+                    };
+                    corners.push((p, t, nm));
+                }
+                // Без нормалей в файле берём нормаль плоскости грани
+                let flat = face_normal(positions[corners[0].0], positions[corners[1].0], positions[corners[2].0]);
+                let mut index = Vec::new();
+                for &(p, t, nm) in &corners {
+                    let key = (p, t, nm.ok_or(flat.map(f32::to_bits)));
+                    let next = model.vertices.len();
+                    let i = *known.entry(key).or_insert(next);
+                    if i == next {
+                        if next >= MAX_VERTICES {
+                            return Err(format!("больше {} вершин", MAX_VERTICES));
+                        }
+                        let uv = t.map_or([0.0, 0.0], |t| uvs[t]);
+                        model.vertices.push((positions[p], uv, nm.map_or(flat, |nm| normals[nm])));
+                        model.position_of.push(p);
+                    }
+                    index.push(i as u16);
+                }
+                for k in 1..index.len() - 1 {
+                    if model.triangles.len() >= MAX_TRIANGLES {
+                        return Err(format!("больше {} треугольников", MAX_TRIANGLES));
+                    }
+                    model.triangles.push([index[0], index[k], index[k + 1]]);
+                    model.colors.push(color);
+                }
+            }
+            // Группы, объекты и сглаживание на геометрию не влияют
+            _ => {}
+        }
+    }
+    if model.triangles.is_empty() {
+        return Err("нет ни одной грани".into());
+    }
+    Ok(model)
+}
+
+fn parse_mtl(text: &str, materials: &mut HashMap<String, u32>) -> Result<(), String> {
+    let mut current = None;
+    for (n, line) in text.lines().enumerate() {
+        let parts: Vec<&str> = line.split('#').next().unwrap().split_whitespace().collect();
+        match parts.split_first() {
+            Some((&"newmtl", name)) => {
+                let name = name.join(" ");
+                materials.insert(name.clone(), DEFAULT_COLOR);
+                current = Some(name);
+            }
+            Some((&"Kd", rgb)) => {
+                let [r, g, b] = floats::<3>(rgb, n + 1)?;
+                let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u32;
+                if let Some(name) = &current {
+                    materials.insert(name.clone(), 0xFF00_0000 | byte(r) << 16 | byte(g) << 8 | byte(b));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let (u, v) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+    // Обход по часовой стрелке снаружи при y вниз: (b - a) x (c - a) смотрит внутрь,
+    // поэтому берётся (c - a) x (b - a)
+    let n = [u[2] * v[1] - u[1] * v[2], u[0] * v[2] - u[2] * v[0], u[1] * v[0] - u[0] * v[1]];
+    let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if len > 0.0 { n.map(|x| x / len) } else { n }
+}
+
+/// Рёбра, которые стоит обводить: граница модели и изломы между гранями.
+/// Возвращает ([вершина, вершина], [грань, грань или u16::MAX]).
+fn feature_edges(model: &Model) -> Vec<([u16; 2], [u16; 2])> {
+    let mut shared: HashMap<(usize, usize), ([u16; 2], Vec<usize>)> = HashMap::new();
+    let mut order = Vec::new();
+    for (t, tri) in model.triangles.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            let (pa, pb) = (model.position_of[a as usize], model.position_of[b as usize]);
+            let key = (pa.min(pb), pa.max(pb));
+            let entry = shared.entry(key).or_insert_with(|| {
+                order.push(key);
+                ([a, b], Vec::new())
+            });
+            entry.1.push(t);
+        }
+    }
+    let normal = |t: usize| {
+        let [a, b, c] = model.triangles[t].map(|i| model.vertices[i as usize].0);
+        face_normal(a, b, c)
+    };
+    let mut edges = Vec::new();
+    for key in order {
+        let (vertices, faces) = &shared[&key];
+        let crease = match faces.as_slice() {
+            [a, b] => {
+                let (na, nb) = (normal(*a), normal(*b));
+                na[0] * nb[0] + na[1] * nb[1] + na[2] * nb[2] < 0.999
+            }
+            _ => true,
+        };
+        if crease {
+            let second = faces.get(1).map_or(u16::MAX, |&f| f as u16);
+            edges.push((*vertices, [faces[0] as u16, second]));
+        }
+    }
+    edges
+}
+
+fn emit(out: &mut String, name: &str, model: &Model) {
+    writeln!(out, "\npub static {}: Mesh<'static> = Mesh {{", name).unwrap();
+    out.push_str("    vertices: &[\n");
+    for (p, uv, n) in &model.vertices {
+        writeln!(
+            out,
+            "        Vertex {{ position: Vec3::new({:?}, {:?}, {:?}), normal: Vec3::new({:?}, {:?}, {:?}), uv: Vec2::new({:?}, {:?}) }},",
+            p[0], p[1], p[2], n[0], n[1], n[2], uv[0], uv[1]
+        )
+        .unwrap();
+    }
+    out.push_str("    ],\n    triangles: &[\n");
+    for t in &model.triangles {
+        writeln!(out, "        {:?},", t).unwrap();
+    }
+    out.push_str("    ],\n    colors: &[\n");
+    for c in &model.colors {
+        writeln!(out, "        {:#010X},", c).unwrap();
+    }
+    out.push_str("    ],\n    edges: &[\n");
+    for (vertices, faces) in feature_edges(model) {
+        writeln!(out, "        Edge {{ vertices: {:?}, faces: {:?} }},", vertices, faces).unwrap();
+    }
+    out.push_str("    ],\n};\n");
+}
+
+/// Текстовый уровень в двоичную карту NGTM (см. `src/tilemap.rs`):
+/// заголовок, флаги тайлов и слои, сжатые парами (длина серии, тайл)
+fn encode_level(source: &str) -> Result<Vec<u8>, String> {
+    let mut tile_size = None;
+    let mut legend = HashMap::from([('.', 0u8), (' ', 0u8)]);
+    let mut flags = Vec::new();
+    let mut layers: Vec<Vec<Vec<u8>>> = Vec::new();
+    for (n, text) in source.lines().enumerate() {
+        let line = n + 1;
+        if text.trim() == "layer" {
+            layers.push(Vec::new());
+            continue;
+        }
+        if let Some(layer) = layers.last_mut() {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let row = text
+                .chars()
+                .map(|c| legend.get(&c).copied().ok_or_else(|| format!("строка {}: неизвестный тайл {:?}", line, c)))
+                .collect::<Result<Vec<u8>, String>>()?;
+            layer.push(row);
+            continue;
+        }
+        let parts: Vec<&str> = text.split('#').next().unwrap().split_whitespace().collect();
+        match parts.as_slice() {
+            [] => {}
+            ["tile", w, h] => {
+                let size = |s: &str| s.parse::<u8>().ok().filter(|&v| v > 0);
+                let (w, h) = size(w).zip(size(h)).ok_or_else(|| format!("строка {}: плохой размер тайла", line))?;
+                tile_size = Some((w, h));
+            }
+            // Символ `#` сам выглядит как комментарий, поэтому легенду разбираем по исходной строке
+            ["legend", ..] => {
+                let words: Vec<&str> = text.split_whitespace().collect();
+                let (c, index, names) = match words.as_slice() {
+                    [_, c, index, names @ ..] if c.chars().count() == 1 => (c.chars().next().unwrap(), index, names),
+                    _ => return Err(format!("строка {}: ожидается legend <символ> <номер> [флаги]", line)),
+                };
+                let index: u8 = index
+                    .parse()
+                    .ok()
+                    .filter(|&i| i > 0 && i < 255)
+                    .ok_or_else(|| format!("строка {}: номер тайла 1..=254", line))?;
+                let mut bits = 0u8;
+                for name in names {
+                    bits |= match *name {
+                        "solid" => 1,
+                        "platform" => 2,
+                        "hazard" => 4,
+                        _ => return Err(format!("строка {}: неизвестный флаг {:?}", line, name)),
+                    };
+                }
+                if flags.len() <= index as usize {
+                    flags.resize(index as usize + 1, 0);
+                }
+                flags[index as usize] |= bits;
+                legend.insert(c, index);
+            }
+            _ => return Err(format!("строка {}: непонятная строка {:?}", line, text)),
+        }
+    }
+    let (tile_w, tile_h) = tile_size.ok_or("нет строки tile")?;
+    if layers.is_empty() || layers.len() > MAX_LAYERS {
+        return Err(format!("слоёв должно быть от 1 до {}", MAX_LAYERS));
+    }
+    let height = layers[0].len();
+    let width = layers[0].first().map_or(0, Vec::len);
+    for layer in &layers {
+        if layer.len() != height || layer.iter().any(|row| row.len() != width) {
+            return Err("слои и ряды должны быть одного размера".into());
+        }
+    }
+    if width == 0 || width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(format!("размер карты {}x{} не помещается в формат", width, height));
+    }
+
+    let mut out = b"NGTM".to_vec();
+    out.extend_from_slice(&[1, tile_w, tile_h, layers.len() as u8]);
+    out.extend_from_slice(&(width as u16).to_le_bytes());
+    out.extend_from_slice(&(height as u16).to_le_bytes());
+    out.push(flags.len() as u8);
+    out.extend_from_slice(&flags);
+    for layer in &layers {
+        let tiles: Vec<u8> = layer.iter().flatten().copied().collect();
+        for run in tiles.chunk_by(|a, b| a == b) {
+            for part in run.chunks(255) {
+                out.extend_from_slice(&[part.len() as u8, part[0]]);
+            }
+        }
+    }
+    Ok(out)
+}
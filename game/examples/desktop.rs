@@ -0,0 +1,57 @@
+//! Запуск игры на хосте без mykvm и vmm.
+//!
+//! Каждый кадр пишется в framebuffer_dump.ppm, который показывает viewer:
+//!
+//!     cd mykvm && cargo run --manifest-path ../game/Cargo.toml --features std --example desktop -- 800x600
+//!     cd mykvm/viewer && cargo run
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+use game::cube::CubeGame;
+use game::hosted::HostedGame;
+use game::input::Key;
+
+const OUTPUT: &str = "framebuffer_dump.ppm";
+const SCRATCH_SIZE: usize = 16 * 1024 * 1024;
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+fn main() {
+    // Аргументы: [ШИРИНАxВЫСОТА] [число кадров, 0 — бесконечно]
+    let mut args = std::env::args().skip(1);
+    let (width, height) = match args.next() {
+        Some(size) => match size.split_once('x').map(|(w, h)| (w.parse(), h.parse())) {
+            Some((Ok(w), Ok(h))) if w > 0 && h > 0 => (w, h),
+            _ => {
+                eprintln!("[desktop] ожидается ШИРИНАxВЫСОТА, получено {}", size);
+                return;
+            }
+        },
+        None => (640, 480),
+    };
+    let frames: u64 = args.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+
+    let mut game = HostedGame::<CubeGame>::new(width, height, SCRATCH_SIZE);
+    // Клавиатуры здесь нет: сразу начинаем игру из меню
+    game.tap(Key::ENTER);
+    let mut last = Instant::now();
+    let mut frame = 0u64;
+    while frames == 0 || frame < frames {
+        let now = Instant::now();
+        game.update((now - last).as_secs_f32());
+        last = now;
+        game.render();
+
+        // Пишем во временный файл и переименовываем, чтобы viewer не прочитал половину кадра
+        let tmp = format!("{}.tmp", OUTPUT);
+        let mut data = Vec::new();
+        if let Err(e) = game.surface().write_ppm(&mut data).and_then(|_| fs::write(&tmp, &data)).and_then(|_| fs::rename(&tmp, OUTPUT)) {
+            eprintln!("[desktop] не удалось сохранить кадр: {}", e);
+            return;
+        }
+        frame += 1;
+        if let Some(rest) = FRAME_TIME.checked_sub(now.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Замер стоимости рисования на хосте: очистка, линии, заливка треугольников и кадр куба.
+//!
+//!     cargo run --release --features std --example fillrate -- 640x480
+//!
+//! Печатает среднее время одной операции в микросекундах.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use game::cube::CubeGame;
+use game::hosted::{HostedGame, Surface};
+use game::input::Key;
+
+const SCRATCH_SIZE: usize = 16 * 1024 * 1024;
+
+/// Среднее время `f` в микросекундах за `runs` повторов
+fn measure(name: &str, runs: u32, mut f: impl FnMut(u32)) {
+    let start = Instant::now();
+    for i in 0..runs {
+        f(i);
+    }
+    let us = start.elapsed().as_secs_f64() * 1e6 / runs as f64;
+    println!("{:<28}{:>10.1} мкс", name, us);
+}
+
+fn main() {
+    let (width, height) = std::env::args()
+        .nth(1)
+        .and_then(|s| {
+            let (w, h) = s.split_once('x')?;
+            Some((w.parse().ok()?, h.parse().ok()?))
+        })
+        .unwrap_or((640, 480));
+    let (w, h) = (width as i32, height as i32);
+    let mut surface = Surface::new(width, height);
+    let mut fb = surface.framebuffer();
+
+    measure("clear", 1000, |i| fb.clear(black_box(0xFF000000 | i)));
+    measure("clear_depth", 1000, |_| fb.clear_depth());
+    // Половина линий выходит далеко за экран
+    measure("1000 lines", 100, |i| {
+        for k in 0..1000 {
+            let k = (k + i as i32) % 1000;
+            fb.draw_line(k * 7 % w - w / 2, k * 13 % h - h / 2, w * 3 / 2 - k % w, h * 3 / 2 - k * 3 % h, 0xFFFFFFFF);
+        }
+    });
+    measure("100 triangles", 100, |i| {
+        fb.clear_depth();
+        for k in 0..100 {
+            let (x, y) = ((k * 37 + i as i32) % w, (k * 53) % h);
+            fb.fill_triangle((x - 80, y - 60, 0.5), (x + 80, y, 0.5), (x - 40, y + 60, 0.5), 0xFF406080);
+        }
+    });
+
+    let mut game = HostedGame::<CubeGame>::new(width, height, SCRATCH_SIZE);
+    game.tap(Key::ENTER);
+    measure("cube frame", 200, |_| {
+        game.update(1.0 / 60.0);
+        game.render();
+    });
+}
@@ -0,0 +1,284 @@
+//! Интерфейс между ядром и игровыми модулями.
+//!
+//! Игра реализует трейт [`Game`] и экспортирует его макросом [`export_game!`].
+//! Макрос кладёт в секцию `.game_modules` таблицу [`GameModule`] с версией ABI
+//! и указателями на `extern "C"` функции, так что в ядро можно слинковать
+//! несколько игр и выбрать одну при загрузке.
+//!
+//! Глобального состояния у модуля нет: `init` размещает [`Runtime`] в памяти,
+//! которую дало ядро, и возвращает указатель на него, а ядро передаёт этот
+//! указатель во все остальные функции.
+
+use crate::arena::Arena;
+use crate::audio::{AudioInfo, AudioOut};
+use crate::framebuffer::{Display, Framebuffer, FramebufferInfo};
+use crate::input::InputState;
+use crate::timestep::FixedTimestep;
+
+/// Версия ABI. Увеличивается при любом несовместимом изменении `GameModule`
+/// или сигнатур его функций.
+pub const GAME_ABI_VERSION: u32 = 5;
+
+/// Игра, которую запускает ядро
+pub trait Game: Sized {
+    /// Длина шага симуляции, секунды
+    const TICK: f32 = 1.0 / 60.0;
+    /// Сколько шагов можно сделать за кадр, догоняя отставание
+    const MAX_STEPS: u32 = 5;
+
+    /// Создать игру. Из `arena` можно взять память под собственные буферы.
+    fn init(screen: &Framebuffer, arena: &mut Arena) -> Self;
+    /// Шаг логики длиной `delta` (всегда `TICK`); `input` — ввод с прошлого шага
+    fn update(&mut self, input: &InputState, delta: f32);
+    /// Нарисовать кадр в задний буфер; на экран он попадёт после `present`.
+    ///
+    /// `alpha` (0.0 ..= 1.0) — доля шага, прошедшая после последнего `update`:
+    /// по ней можно интерполировать положение между двумя шагами.
+    fn render(&mut self, fb: &mut Framebuffer<'_>, alpha: f32);
+    /// Записать следующие `out.len()` отсчётов звука (моно, `audio::SAMPLE_RATE`).
+    /// Вызывается после шагов `update` кадра; по умолчанию тишина.
+    fn mix(&mut self, out: &mut [i16]) {
+        out.fill(0);
+    }
+}
+
+/// Состояние запущенной игры; для ядра — непрозрачный указатель
+#[repr(C)]
+pub struct GameState {
+    _private: [u8; 0],
+}
+
+/// Таблица функций игрового модуля, совместимая с C
+#[repr(C)]
+pub struct GameModule {
+    /// Должна совпадать с `GAME_ABI_VERSION` ядра
+    pub abi_version: u32,
+    /// Имя игры в UTF-8, без завершающего нуля
+    pub name_ptr: *const u8,
+    pub name_len: usize,
+    /// Вызывается один раз при старте, см. [`Runtime::new`]. Возвращает состояние игры,
+    /// размещённое в `scratch`, или нулевой указатель, если памяти не хватило.
+    /// Остальные функции принимают только этот указатель.
+    pub init: unsafe extern "C" fn(
+        fb: &FramebufferInfo,
+        audio: &AudioInfo,
+        scratch: *mut u8,
+        scratch_len: usize,
+    ) -> *mut GameState,
+    /// Передаёт игре событие ввода (слово из устройства VMM, см. `input::Event::decode`);
+    /// ядро вызывает её перед `update` для каждого события кадра
+    pub input: unsafe extern "C" fn(state: *mut GameState, event: u32),
+    /// Вызывается каждый кадр с реальным временем, прошедшим с прошлого кадра (секунды);
+    /// делает столько шагов `Game::update`, сколько помещается в это время,
+    /// и дописывает в кольцо звука отсчёты за это время
+    pub update: unsafe extern "C" fn(state: *mut GameState, elapsed: f32),
+    /// Сколько отсчётов звука записано в кольцо с начала работы; ядро передаёт
+    /// это число VMM, чтобы тот забрал новые отсчёты
+    pub audio: unsafe extern "C" fn(state: *mut GameState) -> u32,
+    /// Вызывается каждый кадр для рисования в задний буфер
+    pub render: unsafe extern "C" fn(state: *mut GameState),
+    /// Копирует готовый кадр в видимый буфер; после этого ядро сообщает VMM,
+    /// что кадр можно забирать
+    pub present: unsafe extern "C" fn(state: *mut GameState),
+}
+
+// Таблица неизменяема, а имя указывает на статическую строку
+unsafe impl Sync for GameModule {}
+
+impl GameModule {
+    pub fn name(&self) -> &str {
+        unsafe {
+            let bytes = core::slice::from_raw_parts(self.name_ptr, self.name_len);
+            core::str::from_utf8(bytes).unwrap_or("?")
+        }
+    }
+
+    /// Совпадает ли версия ABI модуля с версией ядра
+    pub fn is_compatible(&self) -> bool {
+        self.abi_version == GAME_ABI_VERSION
+    }
+}
+
+/// Запущенная игра вместе с задним буфером и видимым экраном
+pub struct Runtime<G: Game> {
+    pub game: G,
+    /// Задний буфер в памяти ядра, в него рисует игра
+    pub screen: Framebuffer<'static>,
+    pub display: Display,
+    pub input: InputState,
+    pub timestep: FixedTimestep,
+    /// Кольцо звука хоста; `None`, если звука нет
+    pub audio: Option<AudioOut>,
+}
+
+impl<G: Game> Runtime<G> {
+    /// Взять из `arena` задний буфер и создать игру; `None`, если на буферы не хватило памяти.
+    ///
+    /// # Safety
+    /// `fb` и `audio` должны описывать доступную для записи память, которая живёт всё время работы.
+    pub unsafe fn new(fb: &FramebufferInfo, audio: &AudioInfo, arena: &mut Arena) -> Option<Self> {
+        let (width, height) = (fb.width as usize, fb.height as usize);
+        let back = arena.alloc_slice(width * height, 0u32)?;
+        let depth = arena.alloc_slice(width * height, f32::INFINITY)?;
+        let screen = Framebuffer::with_format(back, width, height, fb.format, depth);
+        let game = G::init(&screen, arena);
+        Some(Runtime {
+            game,
+            screen,
+            display: Display::new(fb),
+            input: InputState::new(),
+            timestep: FixedTimestep::new(G::TICK, G::MAX_STEPS),
+            audio: AudioOut::new(audio),
+        })
+    }
+
+    /// Учесть слово из устройства ввода
+    pub fn input(&mut self, event: u32) {
+        self.input.push_raw(event);
+    }
+
+    /// Прогнать игру на `elapsed` секунд реального времени фиксированными шагами
+    /// и досчитать звук за это время
+    pub fn update(&mut self, elapsed: f32) {
+        {
+            crate::profile_scope!("update");
+            self.timestep.run(&mut self.game, &mut self.input, elapsed);
+        }
+        if let Some(audio) = &mut self.audio {
+            crate::profile_scope!("mix");
+            audio.feed(elapsed, |out| self.game.mix(out));
+        }
+    }
+
+    /// Сколько отсчётов звука записано с начала работы
+    pub fn audio_position(&self) -> u32 {
+        self.audio.as_ref().map_or(0, AudioOut::position)
+    }
+
+    pub fn render(&mut self) {
+        crate::profile_scope!("render");
+        self.game.render(&mut self.screen, self.timestep.alpha());
+    }
+
+    pub fn present(&mut self) {
+        crate::profile_scope!("present");
+        self.display.present(&self.screen);
+    }
+}
+
+/// Разместить [`Runtime`] в `scratch`; нулевой указатель, если памяти не хватило.
+/// Это тело `init` из [`export_game!`].
+///
+/// # Safety
+/// Те же требования, что у [`Runtime::new`]; `scratch` — доступная для записи память
+/// размером `scratch_len`, которая живёт всё время работы.
+pub unsafe fn init<G: Game + 'static>(fb: &FramebufferInfo, audio: &AudioInfo, scratch: *mut u8, scratch_len: usize) -> *mut GameState {
+    let mut arena = Arena::new(scratch, scratch_len);
+    let Some(runtime) = Runtime::<G>::new(fb, audio, &mut arena) else {
+        return core::ptr::null_mut();
+    };
+    match arena.alloc(runtime) {
+        Some(rt) => (rt as *mut Runtime<G>).cast(),
+        None => core::ptr::null_mut(),
+    }
+}
+
+/// Экспортировать игру для ядра: `export_game!(MyGame, "name");`
+///
+/// Создаёт `extern "C"` обёртки над [`Runtime`] и таблицу [`GameModule`]
+/// в секции `.game_modules`.
+#[macro_export]
+macro_rules! export_game {
+    ($ty:ty, $name:literal) => {
+        const _: () = {
+            use $crate::abi::{GameModule, GameState, Runtime, GAME_ABI_VERSION};
+            use $crate::audio::AudioInfo;
+            use $crate::framebuffer::FramebufferInfo;
+
+            type State = Runtime<$ty>;
+
+            unsafe extern "C" fn init(
+                fb: &FramebufferInfo,
+                audio: &AudioInfo,
+                scratch: *mut u8,
+                scratch_len: usize,
+            ) -> *mut GameState {
+                $crate::abi::init::<$ty>(fb, audio, scratch, scratch_len)
+            }
+
+            // Указатель пришёл из `init`, а ядро не вызывает функции модуля одновременно
+            unsafe fn state<'a>(state: *mut GameState) -> Option<&'a mut State> {
+                state.cast::<State>().as_mut()
+            }
+
+            unsafe extern "C" fn input(rt: *mut GameState, event: u32) {
+                if let Some(rt) = state(rt) {
+                    rt.input(event);
+                }
+            }
+
+            unsafe extern "C" fn update(rt: *mut GameState, elapsed: f32) {
+                if let Some(rt) = state(rt) {
+                    rt.update(elapsed);
+                }
+            }
+
+            unsafe extern "C" fn audio(rt: *mut GameState) -> u32 {
+                state(rt).map_or(0, |rt| rt.audio_position())
+            }
+
+            unsafe extern "C" fn render(rt: *mut GameState) {
+                if let Some(rt) = state(rt) {
+                    rt.render();
+                }
+            }
+
+            unsafe extern "C" fn present(rt: *mut GameState) {
+                if let Some(rt) = state(rt) {
+                    rt.present();
+                }
+            }
+
+            #[used]
+            #[link_section = ".game_modules"]
+            static MODULE: GameModule = GameModule {
+                abi_version: GAME_ABI_VERSION,
+                name_ptr: $name.as_ptr(),
+                name_len: $name.len(),
+                init,
+                input,
+                update,
+                audio,
+                render,
+                present,
+            };
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::CubeGame;
+    use crate::framebuffer::PixelFormat;
+    use std::vec;
+
+    #[test]
+    fn init_without_memory_returns_null() {
+        let mut screen = vec![0u32; 64 * 64];
+        let fb = FramebufferInfo {
+            base: screen.as_mut_ptr() as u64,
+            width: 64,
+            height: 64,
+            stride: 64 * 4,
+            format: PixelFormat::Xrgb8888,
+        };
+        // Задний буфер и буфер глубины 64x64 занимают 32 КБ
+        let mut small = vec![0u8; 16 * 1024];
+        let state = unsafe { init::<CubeGame>(&fb, &AudioInfo::NONE, small.as_mut_ptr(), small.len()) };
+        assert!(state.is_null());
+        let mut large = vec![0u8; 1 << 20];
+        let state = unsafe { init::<CubeGame>(&fb, &AudioInfo::NONE, large.as_mut_ptr(), large.len()) };
+        assert!(!state.is_null());
+    }
+}
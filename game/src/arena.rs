@@ -0,0 +1,60 @@
+//! Простой линейный распределитель поверх памяти, которую отдаёт ядро
+
+use core::mem::{align_of, size_of};
+
+/// Раздаёт куски области памяти, ничего не освобождая.
+///
+/// Выделенные срезы живут до конца работы игры.
+pub struct Arena {
+    next: usize,
+    end: usize,
+}
+
+impl Arena {
+    /// # Safety
+    /// Область `[base, base + len)` должна быть доступна для записи, ни с чем
+    /// не пересекаться и жить всё время работы игры.
+    pub unsafe fn new(base: *mut u8, len: usize) -> Self {
+        Arena { next: base as usize, end: base as usize + len }
+    }
+
+    /// Сколько байт ещё свободно
+    pub fn remaining(&self) -> usize {
+        self.end - self.next
+    }
+
+    /// Выделить срез из `len` элементов, заполненный `value`.
+    ///
+    /// Возвращает `None`, если места не хватило.
+    pub fn alloc_slice<T: Copy>(&mut self, len: usize, value: T) -> Option<&'static mut [T]> {
+        let ptr = self.reserve(len.checked_mul(size_of::<T>())?, align_of::<T>())? as *mut T;
+        unsafe {
+            for i in 0..len {
+                ptr.add(i).write(value);
+            }
+            Some(core::slice::from_raw_parts_mut(ptr, len))
+        }
+    }
+
+    /// Переместить `value` в арену.
+    ///
+    /// Возвращает `None`, если места не хватило. `Drop` у значения не вызывается.
+    pub fn alloc<T>(&mut self, value: T) -> Option<&'static mut T> {
+        let ptr = self.reserve(size_of::<T>(), align_of::<T>())? as *mut T;
+        unsafe {
+            ptr.write(value);
+            Some(&mut *ptr)
+        }
+    }
+
+    /// Отрезать `size` байт с выравниванием `align`; адрес начала
+    fn reserve(&mut self, size: usize, align: usize) -> Option<usize> {
+        let start = self.next.checked_add(align - 1)? & !(align - 1);
+        let end = start.checked_add(size)?;
+        if end > self.end {
+            return None;
+        }
+        self.next = end;
+        Some(start)
+    }
+}
@@ -0,0 +1,412 @@
+//! Звук: микшер голосов и кольцевой буфер в памяти гостя, из которого VMM
+//! забирает поток.
+//!
+//! Звук моно, 16 бит со знаком, [`SAMPLE_RATE`] отсчётов в секунду. Игра
+//! запускает голоса через [`Mixer`], а [`AudioOut`] каждый кадр дописывает
+//! в кольцо столько отсчётов, сколько прошло реального времени.
+
+use core::sync::atomic::{fence, Ordering};
+
+use crate::collections::{Handle, Pool};
+
+/// Частота дискретизации, Гц
+pub const SAMPLE_RATE: u32 = 22050;
+/// Сколько голосов звучит одновременно
+pub const MAX_VOICES: usize = 8;
+
+/// Форма волны голоса
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    /// Меандр: половину периода +1, половину −1
+    Square,
+    /// Пила от −1 до +1
+    Saw,
+    /// Шум: новое случайное значение на каждом периоде, частота задаёт «высоту» шума
+    Noise,
+    /// Записанный звук с частотой `SAMPLE_RATE`; играет один раз
+    Sample(&'static [i16]),
+}
+
+/// Огибающая громкости: нарастание, спад до уровня `sustain`, затухание после отпускания.
+/// Времена в секундах, `sustain` — доля 0.0..=1.0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adsr {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Adsr {
+    pub const fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Adsr { attack, decay, sustain, release }
+    }
+
+    /// Громкость через `time` секунд после начала, пока нота держится
+    fn level(&self, time: f32) -> f32 {
+        if time < self.attack {
+            time / self.attack
+        } else if time < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (time - self.attack) / self.decay
+        } else {
+            self.sustain
+        }
+    }
+}
+
+/// Что сыграть
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sound {
+    pub waveform: Waveform,
+    /// Частота тона, Гц; у сэмпла — во сколько раз ускорить воспроизведение
+    pub frequency: f32,
+    /// Громкость 0.0..=1.0
+    pub volume: f32,
+    pub envelope: Adsr,
+    /// Сколько секунд держать ноту до затухания; `None` — пока не вызван [`Mixer::stop`]
+    pub length: Option<f32>,
+}
+
+struct Voice {
+    sound: Sound,
+    /// Фаза генератора: полный период — 2^32
+    phase: u32,
+    /// Позиция в сэмпле, Q16.16; в u64, чтобы длинный сэмпл не оборвался через 2^16 отсчётов
+    position: u64,
+    /// Приращение фазы (у сэмпла — позиции) за отсчёт
+    step: u32,
+    /// Текущее значение шума
+    noise: f32,
+    time: f32,
+    /// Когда отпустили ноту и какой была громкость в этот момент
+    released: Option<(f32, f32)>,
+    /// Голос отзвучал и будет освобождён после блока
+    done: bool,
+}
+
+impl Voice {
+    fn new(sound: Sound) -> Self {
+        let mut voice = Voice { sound, phase: 0, position: 0, step: 0, noise: 0.0, time: 0.0, released: None, done: false };
+        voice.set_frequency(sound.frequency);
+        voice
+    }
+
+    fn set_frequency(&mut self, frequency: f32) {
+        self.sound.frequency = frequency;
+        // В f64: точности f32 не хватает, и частота поплыла бы
+        let frequency = frequency as f64;
+        let step = match self.sound.waveform {
+            Waveform::Sample(_) => frequency * 65536.0,
+            _ => frequency / SAMPLE_RATE as f64 * 4_294_967_296.0,
+        };
+        self.step = (step + 0.5).clamp(0.0, u32::MAX as f64) as u32;
+    }
+
+    fn release(&mut self) {
+        if self.released.is_none() {
+            self.released = Some((self.time, self.sound.envelope.level(self.time)));
+        }
+    }
+
+    /// Следующий отсчёт −1.0..=1.0; `None`, когда голос отзвучал
+    fn next(&mut self, seed: &mut u32) -> Option<f32> {
+        if self.released.is_none() && self.sound.length.is_some_and(|length| self.time >= length) {
+            self.release();
+        }
+        let envelope = &self.sound.envelope;
+        let level = match self.released {
+            Some((at, from)) => {
+                let k = if envelope.release > 0.0 { 1.0 - (self.time - at) / envelope.release } else { 0.0 };
+                if k <= 0.0 {
+                    return None;
+                }
+                from * k
+            }
+            None => envelope.level(self.time),
+        };
+        let (phase, wrapped) = self.phase.overflowing_add(self.step);
+        let wave = match self.sound.waveform {
+            Waveform::Square => {
+                if self.phase < 1 << 31 { 1.0 } else { -1.0 }
+            }
+            Waveform::Saw => self.phase as f32 / 2_147_483_648.0 - 1.0,
+            Waveform::Noise => {
+                if wrapped || self.time == 0.0 {
+                    // xorshift32
+                    *seed ^= *seed << 13;
+                    *seed ^= *seed >> 17;
+                    *seed ^= *seed << 5;
+                    self.noise = *seed as f32 / 2_147_483_648.0 - 1.0;
+                }
+                self.noise
+            }
+            Waveform::Sample(data) => {
+                let index = (self.position >> 16) as usize;
+                if index >= data.len() {
+                    return None;
+                }
+                self.position += self.step as u64;
+                data[index] as f32 / 32768.0
+            }
+        };
+        self.phase = phase;
+        self.time += 1.0 / SAMPLE_RATE as f32;
+        Some(wave * level * self.sound.volume)
+    }
+}
+
+/// Смешивает до [`MAX_VOICES`] голосов в один поток
+pub struct Mixer {
+    voices: Pool<Voice, MAX_VOICES>,
+    /// Общая громкость 0.0..=1.0
+    pub volume: f32,
+    /// Состояние генератора шума
+    seed: u32,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Mixer { voices: Pool::new(), volume: 0.5, seed: 0x1234_5678 }
+    }
+
+    /// Начать звук; `None`, если все голоса заняты
+    pub fn play(&mut self, sound: Sound) -> Option<Handle> {
+        self.voices.insert(Voice::new(sound)).ok()
+    }
+
+    /// Отпустить ноту: голос затухает по огибающей и освобождается
+    pub fn stop(&mut self, voice: Handle) {
+        if let Some(v) = self.voices.get_mut(voice) {
+            v.release();
+        }
+    }
+
+    /// Отпустить все ноты
+    pub fn stop_all(&mut self) {
+        for (_, v) in self.voices.iter_mut() {
+            v.release();
+        }
+    }
+
+    /// Сменить частоту звучащего голоса
+    pub fn set_frequency(&mut self, voice: Handle, frequency: f32) {
+        if let Some(v) = self.voices.get_mut(voice) {
+            v.set_frequency(frequency);
+        }
+    }
+
+    pub fn is_playing(&self, voice: Handle) -> bool {
+        self.voices.contains(voice)
+    }
+
+    /// Сколько голосов звучит
+    pub fn voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Записать в `out` следующие `out.len()` отсчётов
+    pub fn mix(&mut self, out: &mut [i16]) {
+        let scale = self.volume.clamp(0.0, 1.0) * 32767.0;
+        for sample in out.iter_mut() {
+            let mut sum = 0.0;
+            for (_, voice) in self.voices.iter_mut() {
+                if !voice.done {
+                    match voice.next(&mut self.seed) {
+                        Some(value) => sum += value,
+                        None => voice.done = true,
+                    }
+                }
+            }
+            *sample = (sum * scale).clamp(-32768.0, 32767.0) as i16;
+        }
+        let mut done = [None; MAX_VOICES];
+        for (slot, (handle, voice)) in done.iter_mut().zip(self.voices.iter()) {
+            if voice.done {
+                *slot = Some(handle);
+            }
+        }
+        for handle in done.into_iter().flatten() {
+            self.voices.remove(handle);
+        }
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Кольцевой буфер звука, который выделяет хост. Передаётся в `game::init`.
+///
+/// В памяти по адресу `base` лежат два счётчика u32 — сколько отсчётов с начала
+/// работы записал гость и сколько прочитал хост (оба по модулю 2^32), — а за
+/// ними `frames` отсчётов i16. Раскладка совпадает с vmm/src/audio.rs.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AudioInfo {
+    /// Физический адрес буфера; 0 — звука нет
+    pub base: u64,
+    pub frames: u32,
+    /// Частота дискретизации хоста; звук идёт, только если она равна `SAMPLE_RATE`
+    pub rate: u32,
+}
+
+impl AudioInfo {
+    /// Звука нет
+    pub const NONE: AudioInfo = AudioInfo { base: 0, frames: 0, rate: 0 };
+}
+
+/// Запись в кольцевой буфер хоста
+pub struct AudioOut {
+    write: *mut u32,
+    read: *const u32,
+    samples: *mut i16,
+    frames: u32,
+    /// Сколько отсчётов положено записать, но ещё не записано
+    due: f32,
+}
+
+impl AudioOut {
+    /// `None`, если хост не дал буфер или ждёт другую частоту.
+    ///
+    /// # Safety
+    /// Если `info.base` не 0, он должен указывать на доступную для записи память
+    /// размером 8 + 2 * `info.frames` байт, которая живёт всё время работы.
+    pub unsafe fn new(info: &AudioInfo) -> Option<Self> {
+        if info.base == 0 || info.frames == 0 || info.rate != SAMPLE_RATE {
+            return None;
+        }
+        let base = info.base as *mut u32;
+        Some(AudioOut {
+            write: base,
+            read: base.add(1),
+            samples: base.add(2).cast(),
+            frames: info.frames,
+            due: 0.0,
+        })
+    }
+
+    /// Сколько отсчётов записано с начала работы (по модулю 2^32)
+    pub fn position(&self) -> u32 {
+        unsafe { self.write.read_volatile() }
+    }
+
+    /// Дописать звук за `elapsed` секунд: `mix` заполняет свободные участки кольца.
+    /// Если хост не успевает забирать отсчёты, лишние отбрасываются.
+    pub fn feed(&mut self, elapsed: f32, mut mix: impl FnMut(&mut [i16])) {
+        self.due = (self.due + elapsed * SAMPLE_RATE as f32).min(self.frames as f32);
+        let write = self.position();
+        let read = unsafe { self.read.read_volatile() };
+        let free = self.frames - write.wrapping_sub(read).min(self.frames);
+        let count = (self.due as u32).min(free);
+        self.due -= count as f32;
+        let start = write % self.frames;
+        let first = count.min(self.frames - start);
+        unsafe {
+            mix(core::slice::from_raw_parts_mut(self.samples.add(start as usize), first as usize));
+            mix(core::slice::from_raw_parts_mut(self.samples, (count - first) as usize));
+            // Хост должен увидеть отсчёты раньше нового счётчика
+            fence(Ordering::Release);
+            self.write.write_volatile(write.wrapping_add(count));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEEP: Sound = Sound {
+        waveform: Waveform::Square,
+        frequency: 441.0,
+        volume: 1.0,
+        envelope: Adsr::new(0.0, 0.0, 1.0, 0.01),
+        length: Some(0.1),
+    };
+
+    #[test]
+    fn square_voice_plays_and_frees_itself() {
+        let mut mixer = Mixer::new();
+        mixer.volume = 1.0;
+        let voice = mixer.play(BEEP).unwrap();
+        let mut out = [0i16; 100];
+        mixer.mix(&mut out);
+        // 441 Гц — 50 отсчётов на период: 25 вверх, 25 вниз
+        assert!(out[..25].iter().all(|&s| s == 32767));
+        assert!(out[25..50].iter().all(|&s| s == -32767));
+        assert_eq!(out[50], 32767);
+        // 0.1 с ноты (2205 отсчётов) и 0.01 с затухания (220)
+        let mut rest = [0i16; 2500];
+        mixer.mix(&mut rest);
+        assert!(!mixer.is_playing(voice));
+        assert_eq!(mixer.voices(), 0);
+        assert!(rest[2350..].iter().all(|&s| s == 0));
+        assert!(rest[2200] != 0 && rest[2200].abs() < 32767);
+    }
+
+    #[test]
+    fn envelope_and_samples() {
+        static CLICK: [i16; 4] = [1000, 2000, 3000, 4000];
+        let mut mixer = Mixer::new();
+        mixer.volume = 1.0;
+        let pad = Sound { envelope: Adsr::new(0.01, 0.01, 0.5, 0.1), length: None, ..BEEP };
+        let voice = mixer.play(pad).unwrap();
+        let mut out = [0i16; 882];
+        mixer.mix(&mut out);
+        // Нарастание за 220 отсчётов, спад к половине ещё за 220
+        assert!(out[110].abs() < 20000 && out[110].abs() > 12000);
+        assert!((out[800].abs() - 16383).abs() < 10);
+        mixer.stop(voice);
+        let mut tail = [0i16; 2300];
+        mixer.mix(&mut tail);
+        assert_eq!(mixer.voices(), 0);
+        // Сэмпл играет один раз с исходной скоростью
+        mixer.play(Sound { waveform: Waveform::Sample(&CLICK), frequency: 1.0, length: None, ..BEEP });
+        let mut out = [0i16; 6];
+        mixer.mix(&mut out);
+        assert_eq!(out, [999, 1999, 2999, 3999, 0, 0]);
+        assert_eq!(mixer.voices(), 0);
+    }
+
+    #[test]
+    fn long_sample_plays_to_the_end() {
+        // Дольше 2^16 отсчётов (около 3 с): позиция в Q16.16 не помещается в u32
+        static LONG: [i16; 70_000] = [1000; 70_000];
+        let mut mixer = Mixer::new();
+        mixer.volume = 1.0;
+        mixer.play(Sound { waveform: Waveform::Sample(&LONG), frequency: 1.0, length: None, ..BEEP });
+        let mut out = std::vec![0i16; 70_002];
+        mixer.mix(&mut out);
+        assert!(out[..70_000].iter().all(|&s| s == 999));
+        assert_eq!(&out[70_000..], &[0, 0]);
+        assert_eq!(mixer.voices(), 0);
+    }
+
+    #[test]
+    fn ring_buffer_wraps_and_waits_for_host() {
+        // Два счётчика и 8 отсчётов
+        let mut memory = [0u32; 2 + 4];
+        let info = AudioInfo { base: memory.as_mut_ptr() as u64, frames: 8, rate: SAMPLE_RATE };
+        let mut out = unsafe { AudioOut::new(&info) }.unwrap();
+        let mut next = 0i16;
+        let mut counter = |buf: &mut [i16]| {
+            for s in buf {
+                next += 1;
+                *s = next;
+            }
+        };
+        // 6 отсчётов по времени, затем ещё 6, но места только на 2
+        out.feed(6.0 / SAMPLE_RATE as f32, &mut counter);
+        out.feed(6.0 / SAMPLE_RATE as f32, &mut counter);
+        assert_eq!(out.position(), 8);
+        // Хост прочитал 5 — следующая запись заворачивает через конец кольца
+        memory[1] = 5;
+        let mut out = unsafe { AudioOut::new(&info) }.unwrap();
+        unsafe { out.write.write_volatile(8) };
+        out.feed(4.0 / SAMPLE_RATE as f32, &mut counter);
+        assert_eq!(memory[0], 12);
+        let samples: [i16; 8] = unsafe { core::mem::transmute([memory[2], memory[3], memory[4], memory[5]]) };
+        assert_eq!(samples, [9, 10, 11, 12, 5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Перспективная камера и отсечение по пирамиде видимости
+
+use crate::math::{Mat4, Quat, Vec3};
+
+/// Максимум вершин после отсечения треугольника шестью плоскостями
+pub const MAX_CLIPPED: usize = 9;
+
+/// Перспективная камера.
+///
+/// Пространство камеры: x вправо, y вниз, z вперёд (от зрителя).
+pub struct Camera {
+    pub position: Vec3,
+    /// Поворот из пространства камеры в мир
+    pub orientation: Quat,
+    /// Вертикальный угол обзора, радианы
+    pub fov_y: f32,
+    /// Отношение ширины экрана к высоте
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    pub fn new(position: Vec3, fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Camera { position, orientation: Quat::IDENTITY, fov_y, aspect, near, far }
+    }
+
+    /// Матрица вида: мир -> пространство камеры
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::from_quat(self.orientation.conjugate()) * Mat4::translation(-self.position)
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        Mat4::perspective(self.fov_y, self.aspect, self.near, self.far)
+    }
+
+    /// Перевести точку мира в пространство камеры
+    pub fn to_view(&self, p: Vec3) -> Vec3 {
+        self.orientation.conjugate().rotate(p - self.position)
+    }
+
+    /// Спроецировать точку пространства камеры на экран размером `width` x `height`.
+    ///
+    /// Глубина возвращается в диапазоне 0.0 (near) ..= 1.0 (far) и линейна
+    /// в экранном пространстве, поэтому её можно интерполировать при заливке.
+    /// Точка должна лежать внутри пирамиды видимости.
+    pub fn project(&self, v: Vec3, width: usize, height: usize) -> (i32, i32, f32) {
+        let clip = self.projection_matrix() * v.extend(1.0);
+        let inv_w = 1.0 / clip.w;
+        let sx = (clip.x * inv_w + 1.0) * 0.5 * width as f32;
+        let sy = (clip.y * inv_w + 1.0) * 0.5 * height as f32;
+        (sx as i32, sy as i32, clip.z * inv_w)
+    }
+
+    /// Плоскости пирамиды видимости в пространстве камеры: dot(n, p) + d >= 0 внутри
+    fn planes(&self) -> [(Vec3, f32); 6] {
+        let proj = self.projection_matrix();
+        let f = proj.m[1][1];
+        let fx = proj.m[0][0];
+        [
+            (Vec3::Z, -self.near),            // ближняя
+            (-Vec3::Z, self.far),             // дальняя
+            (Vec3::new(fx, 0.0, 1.0), 0.0),   // левая
+            (Vec3::new(-fx, 0.0, 1.0), 0.0),  // правая
+            (Vec3::new(0.0, f, 1.0), 0.0),    // верхняя
+            (Vec3::new(0.0, -f, 1.0), 0.0),   // нижняя
+        ]
+    }
+
+    /// Отсечь отрезок (в пространстве камеры) пирамидой видимости.
+    ///
+    /// Возвращает `None`, если отрезок целиком снаружи.
+    pub fn clip_line(&self, a: Vec3, b: Vec3) -> Option<(Vec3, Vec3)> {
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        for (n, d) in self.planes() {
+            let da = n.dot(a) + d;
+            let db = n.dot(b) + d;
+            if da < 0.0 && db < 0.0 {
+                return None;
+            }
+            if da < 0.0 {
+                t0 = t0.max(da / (da - db));
+            } else if db < 0.0 {
+                t1 = t1.min(da / (da - db));
+            }
+            if t0 > t1 {
+                return None;
+            }
+        }
+        Some((a.lerp(b, t0), a.lerp(b, t1)))
+    }
+
+    /// Отсечь треугольник (в пространстве камеры) пирамидой видимости.
+    ///
+    /// Результат — выпуклый многоугольник в `out`, возвращается число вершин
+    /// (0, если всё снаружи). Порядок обхода сохраняется. Каждая плоскость
+    /// добавляет не больше одной вершины, так что `MAX_CLIPPED` хватает всегда.
+    pub fn clip_triangle(&self, tri: &[Vec3; 3], out: &mut [Vec3; MAX_CLIPPED]) -> usize {
+        let mut buf = [Vec3::ZERO; MAX_CLIPPED];
+        let mut len = tri.len();
+        out[..len].copy_from_slice(tri);
+        for (n, d) in self.planes() {
+            if len == 0 {
+                break;
+            }
+            let mut count = 0;
+            for i in 0..len {
+                let cur = out[i];
+                let next = out[(i + 1) % len];
+                let (dc, dn) = (n.dot(cur) + d, n.dot(next) + d);
+                if dc >= 0.0 {
+                    buf[count] = cur;
+                    count += 1;
+                }
+                if (dc >= 0.0) != (dn >= 0.0) {
+                    buf[count] = cur.lerp(next, dc / (dc - dn));
+                    count += 1;
+                }
+            }
+            out[..count].copy_from_slice(&buf[..count]);
+            len = count;
+        }
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Угол 90° и квадратный экран: боковые плоскости — x = ±z и y = ±z
+    fn camera() -> Camera {
+        Camera::new(Vec3::ZERO, core::f32::consts::FRAC_PI_2, 1.0, 1.0, 100.0)
+    }
+
+    fn assert_inside(camera: &Camera, points: &[Vec3]) {
+        for p in points {
+            for (n, d) in camera.planes() {
+                assert!(n.dot(*p) + d >= -1e-3, "{:?} снаружи", p);
+            }
+        }
+    }
+
+    fn near(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn lines_are_cut_by_near_and_side_planes() {
+        let camera = camera();
+        // Целиком за ближней плоскостью и целиком правее пирамиды
+        assert_eq!(camera.clip_line(Vec3::new(0.0, 0.0, 0.2), Vec3::new(0.0, 0.0, 0.8)), None);
+        assert_eq!(camera.clip_line(Vec3::new(6.0, 0.0, 5.0), Vec3::new(10.0, 0.0, 5.0)), None);
+        // Пересекает ближнюю плоскость: начало сдвигается на z = near
+        let (a, b) = camera.clip_line(Vec3::new(0.0, 0.0, 0.5), Vec3::new(0.0, 0.0, 2.0)).unwrap();
+        assert!(near(a, Vec3::new(0.0, 0.0, 1.0)) && near(b, Vec3::new(0.0, 0.0, 2.0)), "{:?} {:?}", a, b);
+        // Пересекает правую плоскость x = z
+        let (a, b) = camera.clip_line(Vec3::new(0.0, 0.0, 5.0), Vec3::new(10.0, 0.0, 5.0)).unwrap();
+        assert!(near(a, Vec3::new(0.0, 0.0, 5.0)) && near(b, Vec3::new(5.0, 0.0, 5.0)), "{:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn triangles_are_cut_by_near_and_side_planes() {
+        let camera = camera();
+        let mut out = [Vec3::ZERO; MAX_CLIPPED];
+        // Целиком за ближней плоскостью и целиком правее пирамиды
+        let behind = [Vec3::new(0.0, 0.0, 0.5), Vec3::new(0.2, 0.0, 0.5), Vec3::new(0.0, 0.2, 0.8)];
+        assert_eq!(camera.clip_triangle(&behind, &mut out), 0);
+        let right = [Vec3::new(6.0, 0.0, 5.0), Vec3::new(10.0, 0.0, 5.0), Vec3::new(8.0, 1.0, 5.0)];
+        assert_eq!(camera.clip_triangle(&right, &mut out), 0);
+        // Вершина перед ближней плоскостью отрезается: треугольник становится четырёхугольником
+        let poke = [Vec3::new(0.0, 0.0, 0.5), Vec3::new(0.5, 0.0, 3.0), Vec3::new(-0.5, 0.0, 3.0)];
+        let n = camera.clip_triangle(&poke, &mut out);
+        assert_eq!(n, 4);
+        assert_inside(&camera, &out[..n]);
+        assert_eq!(out[..n].iter().filter(|p| (p.z - 1.0).abs() < 1e-4).count(), 2);
+        // То же с правой плоскостью x = z
+        let wide = [Vec3::new(0.0, 0.0, 5.0), Vec3::new(10.0, 0.0, 5.0), Vec3::new(0.0, 1.0, 5.0)];
+        let n = camera.clip_triangle(&wide, &mut out);
+        assert_eq!(n, 4);
+        assert_inside(&camera, &out[..n]);
+        assert!(out[..n].iter().any(|p| near(*p, Vec3::new(5.0, 0.0, 5.0))));
+    }
+
+    #[test]
+    fn huge_triangle_fits_in_max_clipped() {
+        let camera = camera();
+        let mut out = [Vec3::ZERO; MAX_CLIPPED];
+        // Каждая из шести плоскостей добавляет по вершине: 3 + 6 = MAX_CLIPPED
+        let huge = [Vec3::new(72.73, 85.99, 104.912), Vec3::new(65.44, -25.4, 38.702), Vec3::new(-11.82, 3.04, -5.608)];
+        let n = camera.clip_triangle(&huge, &mut out);
+        assert_eq!(n, MAX_CLIPPED);
+        assert_inside(&camera, &out[..n]);
+        // Вершины не совпадают: ни одна не задвоилась
+        for i in 0..n {
+            assert!(!near(out[i], out[(i + 1) % n]), "{:?}", &out[..n]);
+        }
+    }
+}
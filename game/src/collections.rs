@@ -0,0 +1,439 @@
+//! Контейнеры фиксированной ёмкости без кучи: вектор, пул с дескрипторами, кольцевой буфер
+//!
+//! Ёмкость задаётся параметром типа, память лежит внутри самого контейнера,
+//! так что его можно держать в состоянии игры или взять из `Arena`.
+
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+
+/// Вектор не больше чем на `N` элементов
+pub struct FixedVec<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> FixedVec<T, N> {
+    pub const fn new() -> Self {
+        FixedVec { items: [const { MaybeUninit::uninit() }; N], len: 0 }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Добавить в конец; если места нет, элемент возвращается обратно
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.items[self.len].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // Элемент за новой длиной больше не считается живым
+        Some(unsafe { self.items[self.len].assume_init_read() })
+    }
+
+    /// Убрать элемент, поставив на его место последний; порядок не сохраняется
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "индекс {} за пределами длины {}", index, self.len);
+        let last = self.len - 1;
+        self.as_mut_slice().swap(index, last);
+        self.pop().unwrap()
+    }
+
+    /// Убрать элемент со сдвигом хвоста
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "индекс {} за пределами длины {}", index, self.len);
+        self.as_mut_slice()[index..].rotate_left(1);
+        self.pop().unwrap()
+    }
+
+    /// Оставить только элементы, для которых `keep` вернул `true`; порядок сохраняется
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if keep(&self.as_slice()[i]) {
+                self.as_mut_slice().swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            drop(self.pop());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // Первые `len` элементов инициализированы
+        unsafe { core::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for FixedVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Deref for FixedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for FixedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for FixedVec<T, N> {
+    /// Лишние элементы, не поместившиеся в `N`, отбрасываются
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = FixedVec::new();
+        for value in iter.into_iter().take(N) {
+            let _ = vec.push(value);
+        }
+        vec
+    }
+}
+
+/// Ссылка на элемент [`Pool`]. После удаления элемента старый дескриптор
+/// перестаёт работать, даже если ячейку занял новый элемент.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+impl Handle {
+    /// Номер ячейки пула, 0 .. N
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+struct Slot<T> {
+    /// Растёт при каждом удалении из ячейки
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Пул на `N` элементов: вставка и удаление за O(1), адресация по [`Handle`]
+pub struct Pool<T, const N: usize> {
+    slots: [Slot<T>; N],
+    /// Стек свободных ячеек
+    free: FixedVec<u32, N>,
+    len: usize,
+}
+
+impl<T, const N: usize> Pool<T, N> {
+    pub fn new() -> Self {
+        assert!(N <= u32::MAX as usize, "слишком большой пул");
+        let mut free = FixedVec::new();
+        // Ячейки раздаются с начала
+        for i in (0..N as u32).rev() {
+            let _ = free.push(i);
+        }
+        Pool { slots: [const { Slot { generation: 0, value: None } }; N], free, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Положить элемент в свободную ячейку; если пул полон, элемент возвращается обратно
+    pub fn insert(&mut self, value: T) -> Result<Handle, T> {
+        let Some(index) = self.free.pop() else {
+            return Err(value);
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.value = Some(value);
+        self.len += 1;
+        Ok(Handle { index, generation: slot.generation })
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        let _ = self.free.push(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.value.as_mut()
+    }
+
+    /// Все элементы в порядке ячеек
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &T)> {
+        self.slots.iter().enumerate().filter_map(|(i, slot)| {
+            let handle = Handle { index: i as u32, generation: slot.generation };
+            slot.value.as_ref().map(|v| (handle, v))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(i, slot)| {
+            let handle = Handle { index: i as u32, generation: slot.generation };
+            slot.value.as_mut().map(|v| (handle, v))
+        })
+    }
+
+    /// Удалить все элементы; выданные дескрипторы становятся недействительными
+    pub fn clear(&mut self) {
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                let _ = self.free.push(i as u32);
+            }
+        }
+        self.len = 0;
+    }
+}
+
+impl<T, const N: usize> Default for Pool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Очередь на `N` элементов: добавление в конец, выборка из начала
+pub struct RingBuffer<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    /// Индекс первого элемента
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    pub const fn new() -> Self {
+        RingBuffer { items: [const { MaybeUninit::uninit() }; N], head: 0, len: 0 }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Добавить в конец; если места нет, элемент возвращается обратно
+    pub fn push_back(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.items[(self.head + self.len) % N].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Добавить в конец, вытеснив самый старый элемент, если места нет
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        let oldest = if self.is_full() { self.pop_front() } else { None };
+        let _ = self.push_back(value);
+        oldest
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.items[self.head].assume_init_read() };
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Элемент по счёту от начала очереди
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(unsafe { self.items[(self.head + index) % N].assume_init_ref() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Элементы от старого к новому
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len).map(move |i| self.get(i).unwrap())
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for RingBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    /// Считает свои удаления, чтобы проверить, что контейнеры не теряют и не удваивают drop
+    struct Counted<'a>(&'a Cell<u32>);
+
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn fixed_vec_push_remove_retain() {
+        let mut v = FixedVec::<i32, 4>::new();
+        for i in 1..=4 {
+            v.push(i).unwrap();
+        }
+        assert_eq!(v.push(5), Err(5));
+        assert_eq!(v.swap_remove(0), 1);
+        assert_eq!(&v[..], &[4, 2, 3]);
+        assert_eq!(v.remove(0), 4);
+        assert_eq!(&v[..], &[2, 3]);
+        v.push(6).unwrap();
+        v.retain(|&x| x % 2 == 0);
+        assert_eq!(&v[..], &[2, 6]);
+        v.iter_mut().for_each(|x| *x *= 10);
+        assert_eq!((v.pop(), v.len()), (Some(60), 1));
+
+        let drops = Cell::new(0);
+        {
+            let mut v = FixedVec::<Counted, 3>::new();
+            for _ in 0..3 {
+                let _ = v.push(Counted(&drops));
+            }
+            v.truncate(1);
+            assert_eq!(drops.get(), 2);
+        }
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn pool_handles_go_stale() {
+        let mut pool = Pool::<&str, 2>::new();
+        let a = pool.insert("a").unwrap();
+        let b = pool.insert("b").unwrap();
+        assert_eq!(pool.insert("c"), Err("c"));
+        assert_eq!(pool.remove(a), Some("a"));
+        assert_eq!(pool.remove(a), None);
+        // Новый элемент занимает ту же ячейку, но старый дескриптор к нему не ведёт
+        let c = pool.insert("c").unwrap();
+        assert_eq!(c.index(), a.index());
+        assert_eq!((pool.get(a), pool.get(c)), (None, Some(&"c")));
+        *pool.get_mut(b).unwrap() = "B";
+        let items: FixedVec<&str, 2> = pool.iter().map(|(_, v)| *v).collect();
+        assert_eq!(&items[..], &["c", "B"]);
+        pool.clear();
+        assert!(pool.is_empty() && !pool.contains(b));
+    }
+
+    #[test]
+    fn ring_buffer_wraps_around() {
+        let mut ring = RingBuffer::<u8, 3>::new();
+        for i in 0..3 {
+            ring.push_back(i).unwrap();
+        }
+        assert_eq!(ring.push_back(3), Err(3));
+        assert_eq!(ring.pop_front(), Some(0));
+        ring.push_back(3).unwrap();
+        assert_eq!(ring.push_overwrite(4), Some(1));
+        assert_eq!((ring.front(), ring.back()), (Some(&2), Some(&4)));
+        let items: FixedVec<u8, 3> = ring.iter().copied().collect();
+        assert_eq!(&items[..], &[2, 3, 4]);
+
+        let drops = Cell::new(0);
+        {
+            let mut ring = RingBuffer::<Counted, 2>::new();
+            for _ in 0..3 {
+                ring.push_overwrite(Counted(&drops));
+            }
+            assert_eq!(drops.get(), 1);
+        }
+        assert_eq!(drops.get(), 3);
+    }
+}
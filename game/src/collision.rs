@@ -0,0 +1,394 @@
+//! Проверка пересечений: AABB, сферы, OBB и движение с поиском момента столкновения
+//!
+//! Всё считается в трёх измерениях; для 2D-игр достаточно держать z = 0
+//! (сфера тогда работает как круг, AABB — как прямоугольник).
+
+use crate::math::{sqrt, Mat3, Vec3};
+
+/// Прямоугольный параллелепипед со сторонами вдоль осей
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min: min.min(max), max: min.max(max) }
+    }
+
+    /// По центру и половинам сторон
+    pub fn from_center(center: Vec3, half: Vec3) -> Self {
+        let half = half.abs();
+        Aabb { min: center - half, max: center + half }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn translate(&self, offset: Vec3) -> Aabb {
+        Aabb { min: self.min + offset, max: self.max + offset }
+    }
+
+    /// Расширить во все стороны на `half`: сумма Минковского с коробкой размером `2 * half`
+    pub fn expand(&self, half: Vec3) -> Aabb {
+        Aabb { min: self.min - half, max: self.max + half }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x
+            && p.y >= self.min.y && p.y <= self.max.y
+            && p.z >= self.min.z && p.z <= self.max.z
+    }
+
+    /// Касание гранями тоже считается пересечением
+    pub fn intersects(&self, o: &Aabb) -> bool {
+        self.min.x <= o.max.x && self.max.x >= o.min.x
+            && self.min.y <= o.max.y && self.max.y >= o.min.y
+            && self.min.z <= o.max.z && self.max.z >= o.min.z
+    }
+
+    /// Ближайшая к `p` точка коробки
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        p.max(self.min).min(self.max)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Sphere { center, radius }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_center(self.center, Vec3::new(self.radius, self.radius, self.radius))
+    }
+
+    pub fn intersects(&self, o: &Sphere) -> bool {
+        let r = self.radius + o.radius;
+        (o.center - self.center).length_squared() <= r * r
+    }
+
+    pub fn intersects_aabb(&self, b: &Aabb) -> bool {
+        (b.closest_point(self.center) - self.center).length_squared() <= self.radius * self.radius
+    }
+}
+
+/// Повёрнутый параллелепипед: центр, три взаимно перпендикулярные единичные оси и половины сторон вдоль них
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec3,
+    pub axes: [Vec3; 3],
+    pub half: Vec3,
+}
+
+impl Obb {
+    /// Коробка с половинами сторон `half`, повёрнутая матрицей `rotation`
+    pub fn new(center: Vec3, half: Vec3, rotation: Mat3) -> Self {
+        let axes = [rotation * Vec3::X, rotation * Vec3::Y, rotation * Vec3::Z];
+        Obb { center, axes, half: half.abs() }
+    }
+
+    pub fn from_aabb(b: &Aabb) -> Self {
+        Obb::new(b.center(), b.half_extents(), Mat3::IDENTITY)
+    }
+
+    /// Половина длины проекции коробки на направление `axis`
+    fn radius_along(&self, axis: Vec3) -> f32 {
+        let h = self.half.to_array();
+        (0..3).map(|i| h[i] * self.axes[i].dot(axis).abs()).sum()
+    }
+
+    /// Охватывающий AABB
+    pub fn bounds(&self) -> Aabb {
+        let r = Vec3::new(self.radius_along(Vec3::X), self.radius_along(Vec3::Y), self.radius_along(Vec3::Z));
+        Aabb::from_center(self.center, r)
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let d = p - self.center;
+        let h = self.half.to_array();
+        let mut q = self.center;
+        for (axis, h) in self.axes.iter().zip(h) {
+            q += *axis * d.dot(*axis).clamp(-h, h);
+        }
+        q
+    }
+
+    /// Теорема о разделяющей оси: 3 + 3 оси граней и 9 попарных векторных произведений рёбер
+    pub fn intersects(&self, o: &Obb) -> bool {
+        let d = o.center - self.center;
+        let separated = |axis: Vec3| {
+            // Почти параллельные рёбра дают нулевую ось; её уже проверили среди осей граней
+            if axis.length_squared() < 1e-10 {
+                return false;
+            }
+            d.dot(axis).abs() > self.radius_along(axis) + o.radius_along(axis)
+        };
+        for i in 0..3 {
+            if separated(self.axes[i]) || separated(o.axes[i]) {
+                return false;
+            }
+        }
+        for a in self.axes {
+            for b in o.axes {
+                if separated(a.cross(b)) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn intersects_sphere(&self, s: &Sphere) -> bool {
+        (self.closest_point(s.center) - s.center).length_squared() <= s.radius * s.radius
+    }
+}
+
+/// Пересечение двух тел: `normal` — единичный вектор от первого тела ко второму,
+/// `depth` — насколько их надо раздвинуть вдоль нормали
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    pub normal: Vec3,
+    pub depth: f32,
+}
+
+impl Contact {
+    /// Тот же контакт с точки зрения второго тела
+    pub fn flip(self) -> Contact {
+        Contact { normal: -self.normal, depth: self.depth }
+    }
+}
+
+pub fn sphere_sphere(a: &Sphere, b: &Sphere) -> Option<Contact> {
+    let d = b.center - a.center;
+    let r = a.radius + b.radius;
+    let dist2 = d.length_squared();
+    if dist2 >= r * r {
+        return None;
+    }
+    let dist = sqrt(dist2);
+    // Совпавшие центры: направление всё равно нужно, берём вертикаль
+    let normal = if dist > 0.0 { d * (1.0 / dist) } else { Vec3::Y };
+    Some(Contact { normal, depth: r - dist })
+}
+
+/// Ось наименьшего перекрытия
+pub fn aabb_aabb(a: &Aabb, b: &Aabb) -> Option<Contact> {
+    let overlap = (a.max.min(b.max) - a.min.max(b.min)).to_array();
+    if overlap.iter().any(|&o| o <= 0.0) {
+        return None;
+    }
+    let axis = (0..3).min_by(|&i, &j| overlap[i].total_cmp(&overlap[j])).unwrap();
+    let toward = (b.center() - a.center()).to_array()[axis];
+    Some(Contact { normal: axis_normal(axis, toward), depth: overlap[axis] })
+}
+
+/// Сфера `a` и коробка `b`; нормаль направлена от сферы к коробке
+pub fn sphere_aabb(a: &Sphere, b: &Aabb) -> Option<Contact> {
+    let q = b.closest_point(a.center);
+    let d = q - a.center;
+    let dist2 = d.length_squared();
+    if dist2 >= a.radius * a.radius {
+        return None;
+    }
+    if dist2 > 0.0 {
+        let dist = sqrt(dist2);
+        return Some(Contact { normal: d * (1.0 / dist), depth: a.radius - dist });
+    }
+    // Центр внутри коробки: выталкиваем через ближайшую грань
+    let (p, min, max) = (a.center.to_array(), b.min.to_array(), b.max.to_array());
+    let mut best = (f32::INFINITY, 0, 0.0);
+    for i in 0..3 {
+        let (to_min, to_max) = (p[i] - min[i], max[i] - p[i]);
+        if to_min < best.0 {
+            best = (to_min, i, 1.0);
+        }
+        if to_max < best.0 {
+            best = (to_max, i, -1.0);
+        }
+    }
+    let (dist, axis, toward) = best;
+    Some(Contact { normal: axis_normal(axis, toward), depth: a.radius + dist })
+}
+
+/// Единичный вектор вдоль оси `axis` со знаком `sign`
+fn axis_normal(axis: usize, sign: f32) -> Vec3 {
+    let s = if sign < 0.0 { -1.0 } else { 1.0 };
+    match axis {
+        0 => Vec3::new(s, 0.0, 0.0),
+        1 => Vec3::new(0.0, s, 0.0),
+        _ => Vec3::new(0.0, 0.0, s),
+    }
+}
+
+/// Момент первого касания при движении: `time` — доля пути 0.0..=1.0,
+/// `normal` — нормаль поверхности препятствия в точке касания (смотрит на движущееся тело)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub time: f32,
+    pub normal: Vec3,
+}
+
+/// Луч `origin + dir * t`, t в 0..=1, против коробки (метод пластин).
+///
+/// Если начало луча уже внутри коробки, возвращается время 0 и нулевая нормаль.
+pub fn ray_aabb(origin: Vec3, dir: Vec3, b: &Aabb) -> Option<Hit> {
+    let (o, d, min, max) = (origin.to_array(), dir.to_array(), b.min.to_array(), b.max.to_array());
+    let (mut enter, mut exit) = (0.0f32, 1.0f32);
+    let mut normal = Vec3::ZERO;
+    for i in 0..3 {
+        if d[i] == 0.0 {
+            if o[i] < min[i] || o[i] > max[i] {
+                return None;
+            }
+            continue;
+        }
+        let inv = 1.0 / d[i];
+        let (t0, t1) = ((min[i] - o[i]) * inv, (max[i] - o[i]) * inv);
+        let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+        if near > enter {
+            enter = near;
+            normal = axis_normal(i, -d[i]);
+        }
+        exit = exit.min(far);
+        if enter > exit {
+            return None;
+        }
+    }
+    Some(Hit { time: enter, normal })
+}
+
+/// Коробка `a` смещается на `motion`; когда она впервые коснётся неподвижной `b`.
+///
+/// Сводится к лучу из центра `a` против `b`, расширенной на половины сторон `a`.
+/// Если коробки пересекаются уже в начале, время 0, а нормаль — по оси наименьшего перекрытия;
+/// движение вдоль этой нормали или прочь от `b` тогда не блокируется (тело отталкивается от пола).
+pub fn sweep_aabb(a: &Aabb, motion: Vec3, b: &Aabb) -> Option<Hit> {
+    if let Some(c) = aabb_aabb(a, b) {
+        if motion.dot(-c.normal) >= 0.0 {
+            return None;
+        }
+        return Some(Hit { time: 0.0, normal: -c.normal });
+    }
+    let hit = ray_aabb(a.center(), motion, &b.expand(a.half_extents()))?;
+    // Нулевая нормаль у луча, начавшегося на самой границе: касание без перекрытия
+    if hit.normal == Vec3::ZERO {
+        return None;
+    }
+    // Движение вдоль поверхности или от неё — не столкновение
+    if motion.dot(hit.normal) >= 0.0 {
+        return None;
+    }
+    Some(hit)
+}
+
+/// Сфера `a` смещается на `motion`; когда она впервые коснётся неподвижной сферы `b`.
+///
+/// Если сферы уже пересекаются, время 0, но только когда `a` движется навстречу `b`.
+pub fn sweep_sphere(a: &Sphere, motion: Vec3, b: &Sphere) -> Option<Hit> {
+    let m = a.center - b.center;
+    let r = a.radius + b.radius;
+    let c = m.length_squared() - r * r;
+    if c < 0.0 {
+        if motion.dot(m) >= 0.0 {
+            return None;
+        }
+        return Some(Hit { time: 0.0, normal: m.normalize() });
+    }
+    // |m + motion * t|² = r²: квадратное уравнение a·t² + 2b·t + c = 0
+    let (qa, qb) = (motion.length_squared(), m.dot(motion));
+    if qb >= 0.0 || qa == 0.0 {
+        return None; // стоит на месте или удаляется
+    }
+    let disc = qb * qb - qa * c;
+    if disc < 0.0 {
+        return None;
+    }
+    let t = (-qb - sqrt(disc)) / qa;
+    if t > 1.0 {
+        return None;
+    }
+    let t = t.max(0.0);
+    Some(Hit { time: t, normal: (m + motion * t).normalize() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Mat4;
+
+    fn unit_box(x: f32, y: f32) -> Aabb {
+        Aabb::from_center(Vec3::new(x, y, 0.0), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn static_intersections() {
+        assert!(unit_box(0.0, 0.0).intersects(&unit_box(1.5, 1.5)));
+        assert!(!unit_box(0.0, 0.0).intersects(&unit_box(2.5, 0.0)));
+        let s = Sphere::new(Vec3::new(2.5, 2.5, 0.0), 1.0);
+        // Угол коробки на расстоянии 1.5√2 > 1 от центра сферы
+        assert!(!s.intersects_aabb(&unit_box(0.0, 0.0)));
+        assert!(s.intersects_aabb(&unit_box(1.0, 1.5)));
+        assert!(s.intersects(&Sphere::new(Vec3::new(2.5, 4.0, 0.0), 0.6)));
+
+        // Повёрнутые на 45° коробки: их AABB пересекаются, а сами они нет
+        let rot = Mat4::rotation_z(core::f32::consts::FRAC_PI_4).to_mat3();
+        let a = Obb::new(Vec3::ZERO, Vec3::new(1.0, 1.0, 1.0), rot);
+        let b = Obb::new(Vec3::new(2.6, 2.6, 0.0), Vec3::new(1.0, 1.0, 1.0), rot);
+        assert!(a.bounds().intersects(&b.bounds()));
+        assert!(!a.intersects(&b));
+        assert!(a.intersects(&Obb::from_aabb(&unit_box(2.3, 0.0))));
+        assert!(!a.intersects(&Obb::from_aabb(&unit_box(2.5, 0.0))));
+        assert!(a.intersects_sphere(&Sphere::new(Vec3::new(0.9, 0.9, 0.0), 0.5)));
+        assert!(!a.intersects_sphere(&Sphere::new(Vec3::new(1.2, 1.2, 0.0), 0.5)));
+    }
+
+    #[test]
+    fn contacts_point_from_first_to_second() {
+        let c = aabb_aabb(&unit_box(0.0, 0.0), &unit_box(1.5, 0.2)).unwrap();
+        assert_eq!(c.normal, Vec3::X);
+        assert!((c.depth - 0.5).abs() < 1e-6);
+        assert_eq!(aabb_aabb(&unit_box(0.0, 0.0), &unit_box(2.0, 0.0)), None);
+
+        let c = sphere_sphere(&Sphere::new(Vec3::ZERO, 1.0), &Sphere::new(Vec3::new(0.0, -1.5, 0.0), 1.0)).unwrap();
+        assert_eq!(c.normal, -Vec3::Y);
+        assert!((c.depth - 0.5).abs() < 1e-6);
+
+        // Сфера над коробкой и сфера с центром внутри неё, у верхней грани
+        let floor = Aabb::new(Vec3::new(-5.0, 0.0, -5.0), Vec3::new(5.0, 1.0, 5.0));
+        let c = sphere_aabb(&Sphere::new(Vec3::new(0.0, -0.5, 0.0), 1.0), &floor).unwrap();
+        assert_eq!(c.normal, Vec3::Y);
+        let c = sphere_aabb(&Sphere::new(Vec3::new(0.0, 0.2, 0.0), 1.0), &floor).unwrap();
+        assert_eq!(c.normal, Vec3::Y);
+        assert!((c.depth - 1.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sweeps_catch_fast_motion() {
+        // Тонкая стена, через которую коробка за один шаг проскочила бы целиком
+        let wall = Aabb::new(Vec3::new(5.0, -5.0, -1.0), Vec3::new(5.1, 5.0, 1.0));
+        let hit = sweep_aabb(&unit_box(0.0, 0.0), Vec3::new(10.0, 0.0, 0.0), &wall).unwrap();
+        assert!((hit.time - 0.4).abs() < 1e-6);
+        assert_eq!(hit.normal, -Vec3::X);
+        assert_eq!(sweep_aabb(&unit_box(0.0, 0.0), Vec3::new(3.0, 0.0, 0.0), &wall), None);
+        assert_eq!(sweep_aabb(&unit_box(0.0, 0.0), Vec3::new(-10.0, 0.0, 0.0), &wall), None);
+
+        let a = Sphere::new(Vec3::ZERO, 1.0);
+        let b = Sphere::new(Vec3::new(0.0, 10.0, 0.0), 1.0);
+        let hit = sweep_sphere(&a, Vec3::new(0.0, 20.0, 0.0), &b).unwrap();
+        assert!((hit.time - 0.4).abs() < 1e-6);
+        assert_eq!(hit.normal, -Vec3::Y);
+        assert_eq!(sweep_sphere(&a, Vec3::new(5.0, 20.0, 0.0), &b), None);
+    }
+}
@@ -0,0 +1,589 @@
+//! Демо: вращающийся закрашенный куб со спутниками, собранный из сущностей и систем.
+//!
+//! Куб сам раскручивается; стрелками его нужно удерживать, пока скорость
+//! не превысит предел, а стрелкой вверх он подпрыгивает на невидимом полу.
+//! Меню, игра, пауза и конец игры — сцены в [`SceneStack`]. Куб гудит тем
+//! выше, чем быстрее крутится. F1 открывает отладочную панель.
+
+use crate::abi::Game;
+use crate::arena::Arena;
+use crate::audio::{Adsr, Mixer, Sound, Waveform};
+use crate::camera::Camera;
+use crate::collections::{FixedVec, Handle};
+use crate::ecs::{Components, Entities, Entity, Schedule};
+use crate::font::{draw_text, draw_text_scaled, text_width};
+use crate::framebuffer::{Framebuffer, Rect};
+use crate::image;
+use crate::input::{InputState, Key};
+use crate::math::{sin_cos, Mat4, Vec2, Vec3};
+use crate::mesh::models;
+use crate::particles::{Emitter, Style};
+use crate::physics::{self, RigidBody, Shape};
+use crate::scene::{Scene, SceneStack, Transition};
+use crate::render::{draw_mesh_lit, draw_mesh_outline, draw_mesh_textured, Light, Lighting, Material, Shading, MAX_LIGHTS};
+use crate::sprite::{Animation, BlitMode, SpriteSheet};
+use crate::text::TextBuf;
+use crate::texture::{textures, Texture};
+use crate::ui::Ui;
+
+/// Лист из четырёх кадров 16x16 с прозрачным фоном
+static SPINNER: &[u8] = include_bytes!("../assets/spinner.qoi");
+
+/// Начальная скорость вращения, рад/с
+const SPIN_SPEED: f32 = 1.8;
+/// Насколько стрелки меняют скорость за секунду, рад/с²
+const SPIN_ACCEL: f32 = 3.6;
+/// Насколько куб раскручивается сам, рад/с²
+const SPIN_DRIFT: f32 = 0.3;
+/// При такой скорости куб разлетается и игра кончается, рад/с
+const MAX_SPIN: f32 = 8.0;
+/// Сколько объектов помещается в сцену
+const MAX_ENTITIES: usize = 16;
+/// Тяжесть (ось y смотрит вниз) и скорость прыжка главного куба
+const GRAVITY: Vec3 = Vec3::new(0.0, 20.0, 0.0);
+const JUMP_SPEED: f32 = 7.0;
+/// Насколько куб может не доставать до пола и всё ещё оттолкнуться
+const GROUND_GAP: f32 = 0.01;
+/// Рассеянный свет и солнце, светящее сверху из-за спины зрителя
+const AMBIENT: u32 = 0x282828;
+const SUN: Light = Light::Directional { direction: Vec3::new(0.3, 0.6, 1.0), color: 0x909090 };
+/// Докуда достаёт свет спутника
+const GLOW_RANGE: f32 = 4.0;
+
+/// Сколько искр вылетает из разлетевшегося куба
+const SPARKS: usize = 96;
+
+/// Гул куба; частота растёт со скоростью вращения
+const HUM: Sound = Sound {
+    waveform: Waveform::Saw,
+    frequency: 55.0,
+    volume: 0.15,
+    envelope: Adsr::new(0.2, 0.0, 1.0, 0.2),
+    length: None,
+};
+/// Насколько поднимается гул на каждый рад/с, Гц
+const HUM_PITCH: f32 = 20.0;
+/// Короткий сигнал паузы
+const BLIP: Sound = Sound {
+    waveform: Waveform::Square,
+    frequency: 880.0,
+    volume: 0.2,
+    envelope: Adsr::new(0.005, 0.05, 0.3, 0.05),
+    length: Some(0.06),
+};
+/// Треск разлетевшегося куба
+const CRASH: Sound = Sound {
+    waveform: Waveform::Noise,
+    frequency: 4000.0,
+    volume: 0.5,
+    envelope: Adsr::new(0.0, 0.3, 0.2, 0.4),
+    length: Some(0.3),
+};
+/// Звон монетки при старте: меандр, который затихает и на четверти поднимается выше
+static COIN: [i16; 4410] = coin();
+
+const fn coin() -> [i16; 4410] {
+    let mut samples = [0; 4410];
+    let mut i = 0;
+    while i < samples.len() {
+        let half = if i < samples.len() / 4 { 13 } else { 10 };
+        let amplitude = (12000 * (samples.len() - i) / samples.len()) as i16;
+        samples[i] = if (i / half) % 2 == 0 { amplitude } else { -amplitude };
+        i += 1;
+    }
+    samples
+}
+
+/// Положение, поворот вокруг вертикали и размер; прошлые значения нужны для интерполяции
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub position: Vec3,
+    pub angle: f32,
+    pub scale: f32,
+    prev_position: Vec3,
+    prev_angle: f32,
+}
+
+impl Transform {
+    pub fn new(position: Vec3, scale: f32) -> Self {
+        Transform { position, angle: 0.0, scale, prev_position: position, prev_angle: 0.0 }
+    }
+
+    /// Матрица модели между прошлым и текущим шагом
+    fn model(&self, alpha: f32) -> Mat4 {
+        let position = self.prev_position.lerp(self.position, alpha);
+        let angle = self.prev_angle + (self.angle - self.prev_angle) * alpha;
+        Mat4::translation(position) * Mat4::rotation_y(angle) * Mat4::scaling(Vec3::new(self.scale, self.scale, self.scale))
+    }
+}
+
+/// Вращение вокруг собственной оси, рад/с
+#[derive(Clone, Copy)]
+pub struct Spin(pub f32);
+
+/// Движение по окружности в горизонтальной плоскости вокруг начала координат
+#[derive(Clone, Copy)]
+pub struct Orbit {
+    pub radius: f32,
+    /// Угловая скорость, рад/с
+    pub speed: f32,
+    pub phase: f32,
+}
+
+/// Объект светится этим цветом (0xRRGGBB) и освещает соседей, как лампа
+#[derive(Clone, Copy)]
+pub struct Glow(pub u32);
+
+/// Все объекты сцены
+pub struct World {
+    pub entities: Entities<MAX_ENTITIES>,
+    pub transforms: Components<Transform, MAX_ENTITIES>,
+    pub spins: Components<Spin, MAX_ENTITIES>,
+    pub orbits: Components<Orbit, MAX_ENTITIES>,
+    pub glows: Components<Glow, MAX_ENTITIES>,
+    /// Куб, которым управляют стрелки
+    pub player: Option<Entity>,
+    /// Пол и главный куб для `physics::step`; положение куба берётся отсюда
+    pub bodies: [RigidBody; 2],
+    /// Клавиша G переключает затенение по граням и по вершинам
+    pub shading: Shading,
+    /// Клавиша T включает и выключает текстуру на главном кубе
+    pub textured: bool,
+}
+
+impl World {
+    fn new() -> Self {
+        let mut world = World {
+            entities: Entities::new(),
+            transforms: Components::new(),
+            spins: Components::new(),
+            orbits: Components::new(),
+            glows: Components::new(),
+            player: None,
+            bodies: [
+                // Верх пола касается низа куба 2x2x2 в начале координат
+                RigidBody::new(Vec3::new(0.0, 1.5, 0.0), Shape::Box { half: Vec3::new(10.0, 0.5, 10.0) }, 0.0),
+                RigidBody::new(Vec3::ZERO, Shape::Box { half: Vec3::new(1.0, 1.0, 1.0) }, 1.0).with_restitution(0.0),
+            ],
+            shading: Shading::Gouraud,
+            textured: true,
+        };
+        world.player = world.spawn_cube(Vec3::ZERO, 1.0, SPIN_SPEED);
+        // Два светящихся спутника на противоположных сторонах орбиты: тёплый и холодный
+        for (phase, glow) in [(0.0, 0xFFB060), (core::f32::consts::PI, 0x60A0FF)] {
+            if let Some(moon) = world.spawn_cube(Vec3::ZERO, 0.3, -2.5) {
+                world.orbits.insert(moon, Orbit { radius: 2.2, speed: 0.9, phase });
+                world.glows.insert(moon, Glow(glow));
+            }
+        }
+        world
+    }
+
+    fn spawn_cube(&mut self, position: Vec3, scale: f32, spin: f32) -> Option<Entity> {
+        let e = self.entities.spawn()?;
+        self.transforms.insert(e, Transform::new(position, scale));
+        self.spins.insert(e, Spin(spin));
+        Some(e)
+    }
+}
+
+/// Запомнить положение до шага
+fn remember_system(world: &mut World, _: &InputState, _: f32) {
+    for (_, t) in world.transforms.iter_mut() {
+        t.prev_position = t.position;
+        t.prev_angle = t.angle;
+    }
+}
+
+/// Стрелки меняют скорость вращения главного куба, а сам он понемногу раскручивается
+fn control_system(world: &mut World, input: &InputState, delta: f32) {
+    if input.pressed(Key::G) {
+        world.shading = match world.shading {
+            Shading::Flat => Shading::Gouraud,
+            Shading::Gouraud => Shading::Flat,
+        };
+    }
+    if input.pressed(Key::T) {
+        world.textured = !world.textured;
+    }
+    let Some(spin) = world.player.and_then(|p| world.spins.get_mut(p)) else {
+        return;
+    };
+    if input.is_down(Key::LEFT) {
+        spin.0 -= SPIN_ACCEL * delta;
+    }
+    if input.is_down(Key::RIGHT) {
+        spin.0 += SPIN_ACCEL * delta;
+    }
+    spin.0 += SPIN_DRIFT * delta * spin.0.signum();
+}
+
+/// Стрелка вверх подбрасывает стоящий на полу куб; дальше его ведёт физика
+fn physics_system(world: &mut World, input: &InputState, delta: f32) {
+    let [floor, player] = &mut world.bodies;
+    let grounded = player.bounds().max.y >= floor.bounds().min.y - GROUND_GAP;
+    if grounded && input.pressed(Key::UP) {
+        player.velocity.y = -JUMP_SPEED;
+    }
+    physics::step(&mut world.bodies, GRAVITY, delta);
+    let [_, player] = &world.bodies;
+    if let Some(t) = world.player.and_then(|p| world.transforms.get_mut(p)) {
+        t.position = player.position;
+    }
+}
+
+fn orbit_system(world: &mut World, _: &InputState, delta: f32) {
+    for (e, orbit) in world.orbits.iter_mut() {
+        orbit.phase += orbit.speed * delta;
+        if let Some(t) = world.transforms.get_mut(e) {
+            let (sin, cos) = sin_cos(orbit.phase);
+            t.position = Vec3::new(orbit.radius * cos, 0.0, orbit.radius * sin);
+        }
+    }
+}
+
+fn spin_system(world: &mut World, _: &InputState, delta: f32) {
+    for (e, spin) in world.spins.iter() {
+        if let Some(t) = world.transforms.get_mut(e) {
+            t.angle += spin.0 * delta;
+        }
+    }
+}
+
+/// Уровень: мир с системами и время с начала
+pub struct Level {
+    world: World,
+    systems: Schedule<World, 5>,
+    time: f32,
+}
+
+impl Level {
+    fn new() -> Self {
+        Level {
+            world: World::new(),
+            systems: Schedule::new()
+                .with(remember_system)
+                .with(control_system)
+                .with(physics_system)
+                .with(orbit_system)
+                .with(spin_system),
+            time: 0.0,
+        }
+    }
+
+    /// Скорость главного куба вышла за предел
+    fn is_lost(&self) -> bool {
+        self.spin().abs() > MAX_SPIN
+    }
+
+    /// Скорость главного куба, рад/с
+    fn spin(&self) -> f32 {
+        let world = &self.world;
+        world.player.and_then(|p| world.spins.get(p)).map_or(0.0, |spin| spin.0)
+    }
+
+    fn render(&self, fb: &mut Framebuffer<'_>, alpha: f32) {
+        fb.clear(0xFF000000);
+        fb.clear_depth();
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            core::f32::consts::FRAC_PI_3,
+            fb.aspect(),
+            0.1,
+            100.0,
+        );
+        let world = &self.world;
+        // Светящиеся объекты работают как лампы
+        let mut lights = FixedVec::<Light, MAX_LIGHTS>::new();
+        let _ = lights.push(SUN);
+        for (e, glow) in world.glows.iter() {
+            if let Some(t) = world.transforms.get(e) {
+                let position = t.prev_position.lerp(t.position, alpha);
+                let _ = lights.push(Light::Point { position, color: glow.0, range: GLOW_RANGE });
+            }
+        }
+        let lighting = Lighting { ambient: AMBIENT, lights: &lights };
+        // Контуры не проверяют глубину, поэтому дальние кубы рисуются первыми:
+        // грани ближних закроют их контуры
+        let mut order = FixedVec::<(f32, Entity, Mat4), MAX_ENTITIES>::new();
+        for (e, t) in world.transforms.iter() {
+            let model = t.model(alpha);
+            let _ = order.push((camera.to_view(model.transform_point(Vec3::ZERO)).z, e, model));
+        }
+        order.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, e, model) in order.iter() {
+            let material = match world.glows.get(*e) {
+                Some(glow) => Material { ambient: Some(0), diffuse: Some(0), emissive: glow.0 },
+                None => Material::MESH,
+            };
+            if world.textured && world.player == Some(*e) {
+                let texture = Texture::new(textures::CHECKER);
+                draw_mesh_textured(fb, &camera, &models::CUBE, model, &texture, &lighting, &material);
+            } else {
+                draw_mesh_lit(fb, &camera, &models::CUBE, model, &lighting, &material, world.shading);
+            }
+            draw_mesh_outline(fb, &camera, &models::CUBE, model, 0xFFFFFFFF);
+        }
+
+        let mut hud = TextBuf::<32>::new();
+        hud.push_str("Время ");
+        hud.push_float(self.time, 1);
+        draw_text(fb, 4, 14, hud.as_str(), 0xFFC0C0C0);
+    }
+}
+
+/// Общие данные сцен: идущий уровень, лучший результат за запуск, звук и искры
+pub struct Session {
+    pub level: Level,
+    pub best: f32,
+    pub mixer: Mixer,
+    /// Искры из центра экрана, когда куб разлетелся
+    pub sparks: Emitter<'static, SPARKS>,
+    /// Голос гула, пока идёт игра
+    hum: Option<Handle>,
+}
+
+impl Session {
+    /// Гул вслед за скоростью куба; начинается заново, если его остановили
+    fn hum(&mut self) {
+        let frequency = HUM.frequency + self.level.spin().abs() * HUM_PITCH;
+        match self.hum {
+            Some(hum) if self.mixer.is_playing(hum) => self.mixer.set_frequency(hum, frequency),
+            _ => self.hum = self.mixer.play(Sound { frequency, ..HUM }),
+        }
+    }
+
+    fn stop_hum(&mut self) {
+        if let Some(hum) = self.hum.take() {
+            self.mixer.stop(hum);
+        }
+    }
+}
+
+pub enum CubeScene {
+    /// Заставка: Enter начинает игру
+    Menu,
+    /// Игра на уровне из [`Session`]; при входе уровень начинается заново
+    Play,
+    /// Поверх игры: пробел продолжает, Escape выходит в меню
+    Pause,
+    /// Поверх проигранной игры: Enter начинает заново, Escape выходит в меню
+    GameOver { time: f32 },
+}
+
+impl Scene for CubeScene {
+    type Context = Session;
+
+    fn enter(&mut self, session: &mut Session) {
+        if let CubeScene::Play = self {
+            session.level = Level::new();
+        }
+    }
+
+    fn update(&mut self, session: &mut Session, input: &InputState, delta: f32) -> Transition<Self> {
+        match self {
+            CubeScene::Menu if input.pressed(Key::ENTER) => {
+                session.mixer.play(Sound { waveform: Waveform::Sample(&COIN), frequency: 1.0, length: None, ..BLIP });
+                Transition::FadeTo(CubeScene::Play)
+            }
+            CubeScene::Play if input.pressed(Key::SPACE) => {
+                session.stop_hum();
+                session.mixer.play(BLIP);
+                Transition::Push(CubeScene::Pause)
+            }
+            CubeScene::Play | CubeScene::Pause | CubeScene::GameOver { .. } if input.pressed(Key::ESCAPE) => {
+                session.stop_hum();
+                Transition::FadeTo(CubeScene::Menu)
+            }
+            CubeScene::Play => {
+                let level = &mut session.level;
+                level.systems.run(&mut level.world, input, delta);
+                level.time += delta;
+                if level.is_lost() {
+                    let time = level.time;
+                    session.best = session.best.max(time);
+                    session.stop_hum();
+                    session.mixer.play(CRASH);
+                    session.sparks.clear();
+                    session.sparks.burst(SPARKS);
+                    return Transition::Push(CubeScene::GameOver { time });
+                }
+                session.hum();
+                Transition::Stay
+            }
+            CubeScene::Pause if input.pressed(Key::SPACE) => {
+                session.mixer.play(BLIP);
+                Transition::Pop
+            }
+            CubeScene::GameOver { .. } if input.pressed(Key::ENTER) => Transition::FadeTo(CubeScene::Play),
+            CubeScene::GameOver { .. } => {
+                session.sparks.update(delta);
+                Transition::Stay
+            }
+            _ => Transition::Stay,
+        }
+    }
+
+    fn render(&mut self, session: &Session, fb: &mut Framebuffer<'_>, alpha: f32) {
+        let mut line = TextBuf::<48>::new();
+        let middle = fb.height() as i32 / 2;
+        match self {
+            CubeScene::Menu => {
+                fb.clear(0xFF101828);
+                draw_centered(fb, middle - 30, "КУБ", 0xFFFFB060, 3);
+                draw_centered(fb, middle + 2, "Enter - начать", 0xFFFFFFFF, 1);
+                if session.best > 0.0 {
+                    line.push_str("Рекорд ");
+                    line.push_float(session.best, 1);
+                    line.push_str(" с");
+                    draw_centered(fb, middle + 16, line.as_str(), 0xFFC0C0C0, 1);
+                }
+            }
+            CubeScene::Play => session.level.render(fb, alpha),
+            CubeScene::Pause => {
+                fb.blend_rect(fb.bounds(), 0x80000000);
+                draw_centered(fb, middle - 12, "Пауза", 0xFFFFFFFF, 2);
+                draw_centered(fb, middle + 10, "Пробел - дальше, Esc - меню", 0xFFC0C0C0, 1);
+            }
+            CubeScene::GameOver { time } => {
+                fb.blend_rect(fb.bounds(), 0xA0400000);
+                session.sparks.render(fb);
+                draw_centered(fb, middle - 20, "Куб разлетелся", 0xFFFFFFFF, 2);
+                line.push_str("Время ");
+                line.push_float(*time, 1);
+                line.push_str(" с");
+                draw_centered(fb, middle + 2, line.as_str(), 0xFFFFFFFF, 1);
+                draw_centered(fb, middle + 16, "Enter - заново, Esc - меню", 0xFFC0C0C0, 1);
+            }
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        matches!(self, CubeScene::Pause | CubeScene::GameOver { .. })
+    }
+}
+
+/// Текст по центру экрана по горизонтали
+fn draw_centered(fb: &mut Framebuffer<'_>, y: i32, text: &str, color: u32, scale: i32) {
+    let x = (fb.width() as i32 - text_width(text) as i32 * scale) / 2;
+    draw_text_scaled(fb, x, y, text, color, scale);
+}
+
+pub struct CubeGame {
+    scenes: SceneStack<CubeScene, 3>,
+    session: Session,
+    /// Счётчик кадров: сколько нарисовано за последнюю секунду игрового времени
+    fps: u32,
+    frames: u32,
+    fps_time: f32,
+    /// Индикатор в углу экрана; `None`, если не хватило памяти
+    spinner: Option<SpriteSheet<'static>>,
+    spin: Animation,
+    /// Отладочная панель по F1
+    ui: Ui,
+    debug: bool,
+}
+
+impl CubeGame {
+    pub fn scenes(&self) -> &SceneStack<CubeScene, 3> {
+        &self.scenes
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Мир последнего начатого уровня
+    pub fn world(&self) -> &World {
+        &self.session.level.world
+    }
+
+    /// Панель для настройки на ходу: освещение, текстура, громкость, скорость куба
+    fn debug_panel(&mut self, fb: &mut Framebuffer<'_>) {
+        let rect = Rect::new(fb.width() as i32 - 140, 24, 136, 102);
+        let mut frame = self.ui.begin(fb);
+        frame.window("Отладка", rect);
+        let mut line = TextBuf::<32>::new();
+        line.push_str("Время ");
+        line.push_float(self.session.level.time, 1);
+        frame.label(line.as_str());
+        let world = &mut self.session.level.world;
+        let mut gouraud = world.shading == Shading::Gouraud;
+        if frame.checkbox("Гуро", &mut gouraud) {
+            world.shading = if gouraud { Shading::Gouraud } else { Shading::Flat };
+        }
+        frame.checkbox("Текстура", &mut world.textured);
+        frame.slider("Звук", &mut self.session.mixer.volume, 0.0, 1.0);
+        if frame.button("Сбросить спин") {
+            if let Some(spin) = world.player.and_then(|p| world.spins.get_mut(p)) {
+                spin.0 = SPIN_SPEED;
+            }
+        }
+    }
+}
+
+impl Game for CubeGame {
+    fn init(screen: &Framebuffer, arena: &mut Arena) -> Self {
+        let spinner = image::load(SPINNER, arena)
+            .and_then(Result::ok)
+            .map(|img| SpriteSheet::new(img, 16, 16));
+        let center = Vec2::new(screen.width() as f32, screen.height() as f32) * 0.5;
+        let sparks = Emitter::new(center)
+            .with_speed(40.0, 160.0)
+            .with_lifetime(0.6, 1.4)
+            .with_gravity(Vec2::new(0.0, 120.0))
+            .with_colors(0xFFFFE080, 0x00FF2000)
+            .with_style(Style::Streak(0.03));
+        let mut session = Session { level: Level::new(), best: 0.0, mixer: Mixer::new(), sparks, hum: None };
+        CubeGame {
+            scenes: SceneStack::new(CubeScene::Menu, &mut session),
+            session,
+            fps: 0,
+            frames: 0,
+            fps_time: 0.0,
+            spinner,
+            spin: Animation::new(0, 4, 0.1, true),
+            ui: Ui::new(),
+            debug: false,
+        }
+    }
+
+    fn update(&mut self, input: &InputState, delta: f32) {
+        if input.pressed(Key::f(1)) {
+            self.debug = !self.debug;
+            self.ui.unfocus();
+        }
+        // Пока в панели фокус, стрелки и пробел управляют ею, а не кубом
+        let idle = InputState::new();
+        let input = if self.debug && self.ui.input(input) { &idle } else { input };
+        self.scenes.update(&mut self.session, input, delta);
+        self.spin.update(delta);
+        self.fps_time += delta;
+        if self.fps_time >= 1.0 {
+            self.fps = self.frames;
+            self.frames = 0;
+            self.fps_time -= 1.0;
+        }
+    }
+
+    fn render(&mut self, fb: &mut Framebuffer<'_>, alpha: f32) {
+        self.frames += 1;
+        self.scenes.render(&self.session, fb, alpha);
+
+        let mut hud = TextBuf::<32>::new();
+        hud.push_str("Куб  FPS ");
+        hud.push_uint(self.fps as u64, 0, b' ');
+        draw_text(fb, 4, 4, hud.as_str(), 0xFFFFFFFF);
+        if let Some(sheet) = &self.spinner {
+            let x = fb.width() as i32 - sheet.frame_width as i32 - 4;
+            sheet.draw(fb, x, 4, self.spin.frame(), BlitMode::Alpha);
+        }
+        if self.debug {
+            self.debug_panel(fb);
+        }
+    }
+
+    fn mix(&mut self, out: &mut [i16]) {
+        self.session.mixer.mix(out);
+    }
+}
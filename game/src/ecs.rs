@@ -0,0 +1,236 @@
+//! Сущности, компоненты и системы без кучи
+//!
+//! Сущность — только номер с поколением. Данные лежат в хранилищах
+//! [`Components`], по одному на тип компонента; игра собирает их в свою
+//! структуру мира. Системы — обычные функции над этим миром, [`Schedule`]
+//! вызывает их по порядку на каждом шаге `update`.
+
+use crate::collections::{FixedVec, Handle, Pool};
+use crate::input::InputState;
+
+/// Игровой объект. После `despawn` старое значение больше ни к чему не ведёт.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Entity(Handle);
+
+impl Entity {
+    /// Номер ячейки, 0 .. N; у живых сущностей не повторяется
+    pub fn index(self) -> usize {
+        self.0.index()
+    }
+}
+
+/// Список живых сущностей, не больше `N`
+pub struct Entities<const N: usize> {
+    pool: Pool<(), N>,
+}
+
+impl<const N: usize> Entities<N> {
+    pub fn new() -> Self {
+        Entities { pool: Pool::new() }
+    }
+
+    /// Новая сущность; `None`, если места нет
+    pub fn spawn(&mut self) -> Option<Entity> {
+        self.pool.insert(()).ok().map(Entity)
+    }
+
+    /// Убрать сущность. Компоненты из хранилищ мир убирает сам, см. [`Components::remove`].
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        self.pool.remove(entity.0).is_some()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.pool.contains(entity.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.pool.iter().map(|(h, _)| Entity(h))
+    }
+}
+
+impl<const N: usize> Default for Entities<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Компоненты одного типа, по ячейке на номер сущности
+pub struct Components<T, const N: usize> {
+    /// Хранится и сама сущность: компонент старой сущности не достаётся новой в той же ячейке
+    slots: [Option<(Entity, T)>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> Components<T, N> {
+    pub fn new() -> Self {
+        Components { slots: [const { None }; N], len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Добавить или заменить компонент; возвращает прежний
+    pub fn insert(&mut self, entity: Entity, value: T) -> Option<T> {
+        let slot = &mut self.slots[entity.index()];
+        match slot.replace((entity, value)) {
+            Some((owner, old)) if owner == entity => Some(old),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slots.get_mut(entity.index())?;
+        if !matches!(slot, Some((owner, _)) if *owner == entity) {
+            return None;
+        }
+        self.len -= 1;
+        slot.take().map(|(_, v)| v)
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.slots.get(entity.index())? {
+            Some((owner, value)) if *owner == entity => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(entity.index())? {
+            Some((owner, value)) if *owner == entity => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Все компоненты в порядке номеров сущностей
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().filter_map(|s| s.as_ref().map(|(e, v)| (*e, v)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots.iter_mut().filter_map(|s| s.as_mut().map(|(e, v)| (*e, v)))
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|s| *s = None);
+        self.len = 0;
+    }
+}
+
+impl<T, const N: usize> Default for Components<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Система: шаг логики над миром `W` длиной `delta` секунд
+pub type System<W> = fn(world: &mut W, input: &InputState, delta: f32);
+
+/// Упорядоченный список систем, не больше `N`
+pub struct Schedule<W, const N: usize> {
+    systems: FixedVec<System<W>, N>,
+}
+
+impl<W, const N: usize> Schedule<W, N> {
+    pub const fn new() -> Self {
+        Schedule { systems: FixedVec::new() }
+    }
+
+    /// Добавить систему в конец; системы выполняются в порядке добавления
+    pub fn with(mut self, system: System<W>) -> Self {
+        assert!(self.systems.push(system).is_ok(), "в расписании не больше {} систем", N);
+        self
+    }
+
+    pub fn run(&self, world: &mut W, input: &InputState, delta: f32) {
+        for system in self.systems.iter() {
+            system(world, input, delta);
+        }
+    }
+}
+
+impl<W, const N: usize> Default for Schedule<W, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components_follow_entity_lifetime() {
+        let mut entities = Entities::<4>::new();
+        let mut names = Components::<&str, 4>::new();
+        let a = entities.spawn().unwrap();
+        let b = entities.spawn().unwrap();
+        names.insert(a, "a");
+        assert_eq!(names.insert(a, "A"), Some("a"));
+        assert_eq!((names.get(a), names.get(b), names.len()), (Some(&"A"), None, 1));
+
+        assert!(entities.despawn(a));
+        assert!(!entities.is_alive(a) && !entities.despawn(a));
+        // Новая сущность в той же ячейке не видит компонент старой
+        let c = entities.spawn().unwrap();
+        assert_eq!(c.index(), a.index());
+        assert_eq!(names.get(c), None);
+        assert_eq!(names.remove(c), None);
+        assert_eq!(names.insert(c, "c"), None);
+        assert_eq!((names.get(a), names.get(c), names.len()), (None, Some(&"c"), 1));
+        assert_eq!(entities.iter().count(), 2);
+    }
+
+    #[test]
+    fn schedule_runs_systems_in_order() {
+        struct World {
+            positions: Components<i32, 8>,
+            velocities: Components<i32, 8>,
+            log: FixedVec<u8, 4>,
+        }
+        fn integrate(w: &mut World, _: &InputState, delta: f32) {
+            let _ = w.log.push(1);
+            for (e, v) in w.velocities.iter() {
+                if let Some(p) = w.positions.get_mut(e) {
+                    *p += *v * delta as i32;
+                }
+            }
+        }
+        fn done(w: &mut World, _: &InputState, _: f32) {
+            let _ = w.log.push(2);
+        }
+
+        let mut entities = Entities::<8>::new();
+        let mut world = World { positions: Components::new(), velocities: Components::new(), log: FixedVec::new() };
+        let moving = entities.spawn().unwrap();
+        let still = entities.spawn().unwrap();
+        world.positions.insert(moving, 0);
+        world.positions.insert(still, 5);
+        world.velocities.insert(moving, 3);
+
+        let schedule = Schedule::<World, 2>::new().with(integrate).with(done);
+        schedule.run(&mut world, &InputState::new(), 2.0);
+        assert_eq!((world.positions.get(moving), world.positions.get(still)), (Some(&6), Some(&5)));
+        assert_eq!(&world.log[..], &[1, 2]);
+    }
+}
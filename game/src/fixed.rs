@@ -0,0 +1,421 @@
+//! Числа с фиксированной точкой Q16.16 и тригонометрия на таблицах.
+//!
+//! Погрешности (проверяются тестами против libm на хосте):
+//! - `sin`/`cos`: не больше 3e-5 на всём диапазоне Q16.16 (±32768);
+//! - `atan2`: не больше 1e-4 рад;
+//! - `sqrt`: не больше 1 младшего разряда (2^-16).
+
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Число Q16.16: 16 бит целой части со знаком и 16 бит дробной
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(pub i32);
+
+const FRAC_BITS: u32 = 16;
+
+/// Четверть периода синуса в таблице
+const QUARTER: usize = 256;
+const QUARTER_BITS: u32 = 8;
+
+/// Синус на [0, π/2] в Q16.16, с лишней точкой на конце для интерполяции
+static SIN_TABLE: [i32; QUARTER + 1] = sin_table();
+
+/// 2^64 / 2π: переводит радианы Q16.16 в доли оборота Q32 (после сдвига на 48).
+/// С 32 битами дроби ошибка округления константы, умноженная на |x| до 32768,
+/// добавляла бы до 1e-5 к погрешности синуса.
+const TURNS_PER_RAD_Q64: i128 = 2_935_890_503_282_001_226;
+
+/// Число итераций CORDIC для atan2
+const CORDIC_STEPS: usize = 16;
+
+/// atan(2^-i) в Q16.16
+static ATAN_TABLE: [i32; CORDIC_STEPS] = atan_table();
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(1 << FRAC_BITS);
+    pub const HALF: Fixed = Fixed(1 << (FRAC_BITS - 1));
+    pub const MAX: Fixed = Fixed(i32::MAX);
+    pub const MIN: Fixed = Fixed(i32::MIN);
+    /// Наименьший шаг, 2^-16
+    pub const EPSILON: Fixed = Fixed(1);
+    pub const PI: Fixed = Fixed(205_887);
+    pub const FRAC_PI_2: Fixed = Fixed(102_944);
+    pub const TAU: Fixed = Fixed(411_775);
+
+    pub const fn from_raw(raw: i32) -> Self {
+        Fixed(raw)
+    }
+
+    pub const fn raw(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(x: i32) -> Self {
+        Fixed(x << FRAC_BITS)
+    }
+
+    /// Перевести из f32 с округлением к ближайшему и насыщением
+    pub fn from_f32(x: f32) -> Self {
+        let scaled = x * (1u32 << FRAC_BITS) as f32;
+        let rounded = if scaled >= 0.0 { scaled + 0.5 } else { scaled - 0.5 };
+        // `as` насыщает значения за пределами i32
+        Fixed(rounded as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / (1u32 << FRAC_BITS) as f32
+    }
+
+    /// Целая часть с округлением вниз
+    pub const fn floor_int(self) -> i32 {
+        self.0 >> FRAC_BITS
+    }
+
+    /// Ближайшее целое; в i64, чтобы значения у `Fixed::MAX` не переполнялись
+    pub const fn round_int(self) -> i32 {
+        ((self.0 as i64 + Fixed::HALF.0 as i64) >> FRAC_BITS) as i32
+    }
+
+    pub const fn frac(self) -> Fixed {
+        Fixed(self.0 & ((1 << FRAC_BITS) - 1))
+    }
+
+    pub const fn abs(self) -> Fixed {
+        Fixed(self.0.wrapping_abs())
+    }
+
+    pub fn min(self, o: Fixed) -> Fixed {
+        if self <= o { self } else { o }
+    }
+
+    pub fn max(self, o: Fixed) -> Fixed {
+        if self >= o { self } else { o }
+    }
+
+    pub fn clamp(self, lo: Fixed, hi: Fixed) -> Fixed {
+        self.max(lo).min(hi)
+    }
+
+    pub fn saturating_add(self, o: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(o.0))
+    }
+
+    pub fn saturating_sub(self, o: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(o.0))
+    }
+
+    /// Умножение с насыщением вместо переполнения
+    pub fn saturating_mul(self, o: Fixed) -> Fixed {
+        let p = (self.0 as i64 * o.0 as i64) >> FRAC_BITS;
+        Fixed(p.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+
+    /// Линейная интерполяция между `self` и `o`
+    pub fn lerp(self, o: Fixed, t: Fixed) -> Fixed {
+        self + (o - self) * t
+    }
+
+    /// Квадратный корень; для отрицательных чисел возвращает ноль
+    pub fn sqrt(self) -> Fixed {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+        // sqrt(raw * 2^16) даёт результат сразу в Q16.16
+        Fixed(isqrt((self.0 as u64) << FRAC_BITS) as i32)
+    }
+
+    /// Синус; аргумент в радианах, любой величины
+    pub fn sin(self) -> Fixed {
+        Fixed(sin_turns(to_turns(self)))
+    }
+
+    /// Косинус; аргумент в радианах, любой величины
+    pub fn cos(self) -> Fixed {
+        Fixed(sin_turns(to_turns(self).wrapping_add(1 << 30)))
+    }
+
+    pub fn sin_cos(self) -> (Fixed, Fixed) {
+        let turns = to_turns(self);
+        (Fixed(sin_turns(turns)), Fixed(sin_turns(turns.wrapping_add(1 << 30))))
+    }
+
+    /// Угол вектора (x, y) в радианах, -π..=π (CORDIC)
+    pub fn atan2(y: Fixed, x: Fixed) -> Fixed {
+        if x.0 == 0 && y.0 == 0 {
+            return Fixed::ZERO;
+        }
+        // Приводим вектор в правую полуплоскость, запоминая поворот
+        let (mut x, mut y, mut angle) = (x.0 as i64, y.0 as i64, 0i64);
+        // Растягиваем малые векторы, чтобы сдвиги CORDIC не съели точность
+        while x.abs().max(y.abs()) < 1 << 40 {
+            x <<= 1;
+            y <<= 1;
+        }
+        if x < 0 {
+            let base = if y >= 0 { Fixed::PI.0 } else { -Fixed::PI.0 } as i64;
+            x = -x;
+            y = -y;
+            angle = base;
+        }
+        // Поворачиваем к оси x, накапливая угол
+        for (i, &step) in ATAN_TABLE.iter().enumerate() {
+            let (dx, dy) = (x >> i, y >> i);
+            if y > 0 {
+                x += dy;
+                y -= dx;
+                angle += step as i64;
+            } else {
+                x -= dy;
+                y += dx;
+                angle -= step as i64;
+            }
+        }
+        if angle > Fixed::PI.0 as i64 {
+            angle -= Fixed::TAU.0 as i64;
+        } else if angle < -(Fixed::PI.0 as i64) {
+            angle += Fixed::TAU.0 as i64;
+        }
+        Fixed(angle as i32)
+    }
+}
+
+/// Радианы Q16.16 -> доля оборота в 32 битах (полный оборот = 2^32)
+fn to_turns(x: Fixed) -> u32 {
+    ((x.0 as i128 * TURNS_PER_RAD_Q64) >> (FRAC_BITS + 32)) as u32
+}
+
+/// Синус по доле оборота: 2 бита четверти, 8 бит индекса, остальное — интерполяция
+fn sin_turns(turns: u32) -> i32 {
+    const FRAC: u32 = 32 - 2 - QUARTER_BITS;
+    let quadrant = turns >> 30;
+    let pos = turns & ((1 << 30) - 1);
+    // Во второй и четвёртой четверти таблица читается в обратную сторону
+    let pos = if quadrant & 1 == 1 { (1 << 30) - pos } else { pos };
+    let idx = (pos >> FRAC) as usize;
+    let t = (pos & ((1 << FRAC) - 1)) as i64;
+    let a = SIN_TABLE[idx] as i64;
+    let b = SIN_TABLE[(idx + 1).min(QUARTER)] as i64;
+    let v = (a + (((b - a) * t) >> FRAC)) as i32;
+    if quadrant >= 2 { -v } else { v }
+}
+
+/// Целочисленный квадратный корень (поразрядный)
+fn isqrt(n: u64) -> u64 {
+    let mut rem = n;
+    let mut root = 0u64;
+    let mut bit = 1u64 << 62;
+    while bit > n {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+/// Ряд Тейлора для синуса в f64; точен на [0, π/2] при 12 членах
+const fn taylor_sin(x: f64) -> f64 {
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 12 {
+        term = -term * x * x / ((2 * n) as f64 * (2 * n + 1) as f64);
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+/// Ряд для арктангенса в f64; точен при |x| <= 1 / 2
+const fn taylor_atan(x: f64) -> f64 {
+    let mut power = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 40 {
+        power = -power * x * x;
+        sum += power / (2 * n + 1) as f64;
+        n += 1;
+    }
+    sum
+}
+
+const fn to_raw(x: f64) -> i32 {
+    let scaled = x * (1u32 << FRAC_BITS) as f64;
+    (if scaled >= 0.0 { scaled + 0.5 } else { scaled - 0.5 }) as i32
+}
+
+const fn sin_table() -> [i32; QUARTER + 1] {
+    let mut t = [0; QUARTER + 1];
+    let mut i = 0;
+    while i <= QUARTER {
+        t[i] = to_raw(taylor_sin(core::f64::consts::FRAC_PI_2 * i as f64 / QUARTER as f64));
+        i += 1;
+    }
+    t
+}
+
+const fn atan_table() -> [i32; CORDIC_STEPS] {
+    let mut t = [0; CORDIC_STEPS];
+    t[0] = to_raw(core::f64::consts::FRAC_PI_4);
+    let mut i = 1;
+    while i < CORDIC_STEPS {
+        t[i] = to_raw(taylor_atan(1.0 / (1u32 << i) as f64));
+        i += 1;
+    }
+    t
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, o: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_add(o.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, o: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_sub(o.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, o: Fixed) -> Fixed {
+        Fixed(((self.0 as i64 * o.0 as i64) >> FRAC_BITS) as i32)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+    /// Деление на ноль даёт MAX или MIN по знаку делимого
+    fn div(self, o: Fixed) -> Fixed {
+        if o.0 == 0 {
+            return if self.0 >= 0 { Fixed::MAX } else { Fixed::MIN };
+        }
+        let q = ((self.0 as i64) << FRAC_BITS) / o.0 as i64;
+        Fixed(q.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+}
+
+impl Mul<i32> for Fixed {
+    type Output = Fixed;
+    fn mul(self, k: i32) -> Fixed {
+        Fixed(self.0.wrapping_mul(k))
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(self.0.wrapping_neg())
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, o: Fixed) {
+        *self = *self + o;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, o: Fixed) {
+        *self = *self - o;
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, o: Fixed) {
+        *self = *self * o;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIN_ERR: f64 = 3e-5;
+    const ATAN_ERR: f64 = 1e-4;
+
+    fn fx(x: f64) -> Fixed {
+        Fixed::from_f32(x as f32)
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = fx(3.25);
+        let b = fx(-1.5);
+        assert_eq!((a + b).to_f32(), 1.75);
+        assert_eq!((a - b).to_f32(), 4.75);
+        assert_eq!((a * b).to_f32(), -4.875);
+        assert!(((a / b).to_f32() + 2.166_666_7).abs() < 1e-4);
+        assert_eq!(Fixed::from_int(7).floor_int(), 7);
+        assert_eq!(fx(-0.25).floor_int(), -1);
+        assert_eq!(fx(2.5).round_int(), 3);
+        assert_eq!(Fixed::MAX.round_int(), 32768);
+        assert_eq!(Fixed::ONE / Fixed::ZERO, Fixed::MAX);
+        assert_eq!(Fixed::MAX.saturating_mul(fx(2.0)), Fixed::MAX);
+    }
+
+    #[test]
+    fn constants_match_f64() {
+        use core::f64::consts::{FRAC_PI_2, PI, TAU};
+        assert_eq!(Fixed::PI, fx(PI));
+        assert_eq!(Fixed::FRAC_PI_2, fx(FRAC_PI_2));
+        assert_eq!(Fixed::TAU, fx(TAU));
+    }
+
+    #[test]
+    fn sqrt_within_one_lsb() {
+        for i in 0..20_000 {
+            let x = i as f64 * 1.618;
+            let got = fx(x).sqrt().to_f32() as f64;
+            let want = (fx(x).to_f32() as f64).sqrt();
+            assert!((got - want).abs() <= 1.0 / 65536.0, "sqrt({}) = {} want {}", x, got, want);
+        }
+    }
+
+    #[test]
+    fn sin_cos_against_libm() {
+        // Весь диапазон Q16.16: ошибка приведения к обороту растёт с |x|
+        let mut x = -32767.0f64;
+        while x < 32767.0 {
+            // Сравниваем с точным синусом того же (квантованного) аргумента;
+            // f32 у больших x теряет младшие разряды дроби, поэтому делим в f64
+            let q = fx(x);
+            let exact = q.0 as f64 / 65536.0;
+            let (s, c) = q.sin_cos();
+            assert!((s.to_f32() as f64 - exact.sin()).abs() <= SIN_ERR, "sin({})", exact);
+            assert!((c.to_f32() as f64 - exact.cos()).abs() <= SIN_ERR, "cos({})", exact);
+            assert_eq!(s, q.sin());
+            assert_eq!(c, q.cos());
+            x += 0.0517;
+        }
+    }
+
+    #[test]
+    fn atan2_against_libm() {
+        for i in 0..3600 {
+            let a = i as f64 * core::f64::consts::TAU / 3600.0;
+            for &r in &[0.01f64, 1.0, 300.0] {
+                let (y, x) = (fx(r * a.sin()), fx(r * a.cos()));
+                let want = (y.to_f32() as f64).atan2(x.to_f32() as f64);
+                let got = Fixed::atan2(y, x).to_f32() as f64;
+                let mut diff = (got - want).abs();
+                // ±π — одно и то же направление
+                if diff > core::f64::consts::PI {
+                    diff = core::f64::consts::TAU - diff;
+                }
+                assert!(diff <= ATAN_ERR, "atan2({}, {}) = {} want {}", y.to_f32(), x.to_f32(), got, want);
+            }
+        }
+        assert_eq!(Fixed::atan2(Fixed::ZERO, Fixed::ZERO), Fixed::ZERO);
+    }
+}
@@ -0,0 +1,304 @@
+//! Встроенный растровый шрифт 5x8: печатные символы ASCII и кириллица
+//!
+//! Глиф — 8 строк по 5 бит, старший из пяти битов — левый столбец.
+//! Заглавные буквы и цифры занимают строки 0..7, последняя строка — под
+//! базовой линией (хвосты у g, p, y, д, ц, щ).
+
+use crate::framebuffer::Framebuffer;
+
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 8;
+/// Шаг по горизонтали: глиф и один пустой столбец
+pub const ADVANCE: usize = GLYPH_WIDTH + 1;
+/// Шаг между строками текста
+pub const LINE_HEIGHT: usize = GLYPH_HEIGHT + 2;
+
+/// Число глифов ASCII (' '..='~')
+const ASCII_GLYPHS: usize = 95;
+/// Первый глиф кириллицы ('А'..='я', затем 'Ё' и 'ё')
+const CYRILLIC: usize = ASCII_GLYPHS;
+const YO: usize = CYRILLIC + 64;
+/// Глиф для символов, которых нет в шрифте
+const REPLACEMENT: usize = YO + 2;
+
+static GLYPHS: [[u8; GLYPH_HEIGHT]; REPLACEMENT + 1] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00], // '!'
+    [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A, 0x00], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04, 0x00], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03, 0x00], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D, 0x00], // '&'
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02, 0x00], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x06, 0x02, 0x04], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E, 0x00], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F, 0x00], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E, 0x00], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02, 0x00], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E, 0x00], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E, 0x00], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08, 0x00], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E, 0x00], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08, 0x00], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08, 0x00], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04, 0x00], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E, 0x00], // '@'
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E, 0x00], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C, 0x00], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F, 0x00], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10, 0x00], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F, 0x00], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C, 0x00], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11, 0x00], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F, 0x00], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11, 0x00], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x00], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10, 0x00], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D, 0x00], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11, 0x00], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E, 0x00], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A, 0x00], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11, 0x00], // 'X'
+    [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04, 0x00], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F, 0x00], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E, 0x00], // '['
+    [0x10, 0x10, 0x08, 0x04, 0x02, 0x01, 0x01, 0x00], // '\\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E, 0x00], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E, 0x00], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E, 0x00], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F, 0x00], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08, 0x00], // 'f'
+    [0x00, 0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12, 0x00], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x15, 0x15, 0x00], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10, 0x00], // 'r'
+    [0x00, 0x00, 0x0F, 0x10, 0x0E, 0x01, 0x1E, 0x00], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06, 0x00], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A, 0x00], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F, 0x00], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02, 0x00], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08, 0x00], // '}'
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00, 0x00], // '~'
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00], // 'А'
+    [0x1F, 0x10, 0x10, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 'Б'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 'В'
+    [0x1F, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00], // 'Г'
+    [0x0E, 0x0A, 0x0A, 0x0A, 0x0A, 0x0A, 0x1F, 0x11], // 'Д'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F, 0x00], // 'Е'
+    [0x15, 0x15, 0x15, 0x0E, 0x15, 0x15, 0x15, 0x00], // 'Ж'
+    [0x0E, 0x11, 0x01, 0x06, 0x01, 0x11, 0x0E, 0x00], // 'З'
+    [0x11, 0x11, 0x13, 0x15, 0x19, 0x11, 0x11, 0x00], // 'И'
+    [0x0A, 0x11, 0x13, 0x15, 0x19, 0x11, 0x11, 0x00], // 'Й'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11, 0x00], // 'К'
+    [0x07, 0x09, 0x09, 0x09, 0x09, 0x09, 0x11, 0x00], // 'Л'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11, 0x00], // 'М'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00], // 'Н'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 'О'
+    [0x1F, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x00], // 'П'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10, 0x00], // 'Р'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E, 0x00], // 'С'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 'Т'
+    [0x11, 0x11, 0x11, 0x0F, 0x01, 0x11, 0x0E, 0x00], // 'У'
+    [0x04, 0x0E, 0x15, 0x15, 0x15, 0x0E, 0x04, 0x00], // 'Ф'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11, 0x00], // 'Х'
+    [0x12, 0x12, 0x12, 0x12, 0x12, 0x12, 0x1F, 0x01], // 'Ц'
+    [0x11, 0x11, 0x11, 0x0F, 0x01, 0x01, 0x01, 0x00], // 'Ч'
+    [0x15, 0x15, 0x15, 0x15, 0x15, 0x15, 0x1F, 0x00], // 'Ш'
+    [0x15, 0x15, 0x15, 0x15, 0x15, 0x15, 0x1F, 0x01], // 'Щ'
+    [0x18, 0x08, 0x08, 0x0E, 0x09, 0x09, 0x0E, 0x00], // 'Ъ'
+    [0x11, 0x11, 0x11, 0x19, 0x15, 0x15, 0x19, 0x00], // 'Ы'
+    [0x10, 0x10, 0x10, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 'Ь'
+    [0x0E, 0x11, 0x01, 0x07, 0x01, 0x11, 0x0E, 0x00], // 'Э'
+    [0x12, 0x15, 0x15, 0x1D, 0x15, 0x15, 0x12, 0x00], // 'Ю'
+    [0x0F, 0x11, 0x11, 0x0F, 0x05, 0x09, 0x11, 0x00], // 'Я'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 'а'
+    [0x0F, 0x10, 0x1E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 'б'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x11, 0x1E, 0x00], // 'в'
+    [0x00, 0x00, 0x1F, 0x10, 0x10, 0x10, 0x10, 0x00], // 'г'
+    [0x00, 0x00, 0x0E, 0x0A, 0x0A, 0x0A, 0x1F, 0x11], // 'д'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 'е'
+    [0x00, 0x00, 0x15, 0x15, 0x0E, 0x15, 0x15, 0x00], // 'ж'
+    [0x00, 0x00, 0x0E, 0x11, 0x06, 0x11, 0x0E, 0x00], // 'з'
+    [0x00, 0x00, 0x11, 0x13, 0x15, 0x19, 0x11, 0x00], // 'и'
+    [0x0A, 0x04, 0x11, 0x13, 0x15, 0x19, 0x11, 0x00], // 'й'
+    [0x00, 0x00, 0x12, 0x14, 0x18, 0x14, 0x12, 0x00], // 'к'
+    [0x00, 0x00, 0x07, 0x09, 0x09, 0x09, 0x11, 0x00], // 'л'
+    [0x00, 0x00, 0x11, 0x1B, 0x15, 0x11, 0x11, 0x00], // 'м'
+    [0x00, 0x00, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x00], // 'н'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 'о'
+    [0x00, 0x00, 0x1F, 0x11, 0x11, 0x11, 0x11, 0x00], // 'п'
+    [0x00, 0x00, 0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10], // 'р'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E, 0x00], // 'с'
+    [0x00, 0x00, 0x1F, 0x04, 0x04, 0x04, 0x04, 0x00], // 'т'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'у'
+    [0x00, 0x04, 0x0E, 0x15, 0x15, 0x0E, 0x04, 0x04], // 'ф'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00], // 'х'
+    [0x00, 0x00, 0x12, 0x12, 0x12, 0x12, 0x1F, 0x01], // 'ц'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x01, 0x00], // 'ч'
+    [0x00, 0x00, 0x15, 0x15, 0x15, 0x15, 0x1F, 0x00], // 'ш'
+    [0x00, 0x00, 0x15, 0x15, 0x15, 0x15, 0x1F, 0x01], // 'щ'
+    [0x00, 0x00, 0x18, 0x08, 0x0E, 0x09, 0x0E, 0x00], // 'ъ'
+    [0x00, 0x00, 0x11, 0x11, 0x19, 0x15, 0x19, 0x00], // 'ы'
+    [0x00, 0x00, 0x10, 0x10, 0x1E, 0x11, 0x1E, 0x00], // 'ь'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x01, 0x0E, 0x00], // 'э'
+    [0x00, 0x00, 0x12, 0x15, 0x1D, 0x15, 0x12, 0x00], // 'ю'
+    [0x00, 0x00, 0x0F, 0x11, 0x0F, 0x09, 0x11, 0x00], // 'я'
+    [0x0A, 0x00, 0x1F, 0x10, 0x1E, 0x10, 0x1F, 0x00], // 'Ё'
+    [0x0A, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 'ё'
+    [0x1F, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1F, 0x00], // '\u{FFFD}'
+];
+
+/// Битовая маска символа; для отсутствующих в шрифте — рамка
+pub fn glyph(c: char) -> &'static [u8; GLYPH_HEIGHT] {
+    let index = match c {
+        ' '..='~' => c as usize - ' ' as usize,
+        'А'..='я' => CYRILLIC + (c as usize - 'А' as usize),
+        'Ё' => YO,
+        'ё' => YO + 1,
+        _ => REPLACEMENT,
+    };
+    &GLYPHS[index]
+}
+
+/// Ширина текста в пикселях (для многострочного — самой длинной строки)
+pub fn text_width(text: &str) -> usize {
+    text.split('\n')
+        .map(|line| line.chars().count() * ADVANCE)
+        .max()
+        .unwrap_or(0)
+        .saturating_sub(1)
+}
+
+/// Высота текста в пикселях с учётом переводов строк
+pub fn text_height(text: &str) -> usize {
+    (text.split('\n').count() - 1) * LINE_HEIGHT + GLYPH_HEIGHT
+}
+
+/// Нарисовать символ, левый верхний угол ячейки в (x, y)
+pub fn draw_char(fb: &mut Framebuffer<'_>, x: i32, y: i32, c: char, color: u32, scale: i32) {
+    for (row, bits) in glyph(c).iter().enumerate() {
+        for col in 0..GLYPH_WIDTH {
+            if bits & (0x10 >> col) == 0 {
+                continue;
+            }
+            let (px, py) = (x + col as i32 * scale, y + row as i32 * scale);
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    fb.put_pixel(px + dx, py + dy, color);
+                }
+            }
+        }
+    }
+}
+
+/// Нарисовать текст, '\n' переводит строку.
+///
+/// Возвращает x, с которого продолжится текст после последнего символа.
+pub fn draw_text(fb: &mut Framebuffer<'_>, x: i32, y: i32, text: &str, color: u32) -> i32 {
+    draw_text_scaled(fb, x, y, text, color, 1)
+}
+
+/// То же, что `draw_text`, но каждый пиксель глифа — квадрат `scale` x `scale`
+pub fn draw_text_scaled(fb: &mut Framebuffer<'_>, x: i32, y: i32, text: &str, color: u32, scale: i32) -> i32 {
+    let (mut cx, mut cy) = (x, y);
+    for c in text.chars() {
+        if c == '\n' {
+            cx = x;
+            cy += LINE_HEIGHT as i32 * scale;
+            continue;
+        }
+        if c != ' ' {
+            draw_char(fb, cx, cy, c, color, scale);
+        }
+        cx += ADVANCE as i32 * scale;
+    }
+    cx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_letter_has_a_glyph() {
+        let letters = ('!'..='~').chain('А'..='я').chain(['Ё', 'ё']);
+        for c in letters {
+            assert!(glyph(c).iter().any(|&row| row != 0), "пустой глиф {:?}", c);
+            assert!(glyph(c).iter().all(|&row| row < 0x20), "глиф {:?} шире 5 точек", c);
+            assert_ne!(glyph(c), glyph('\u{FFFD}'), "{:?}", c);
+        }
+        assert!(glyph(' ').iter().all(|&row| row == 0));
+        assert_eq!(glyph('€'), glyph('\u{FFFD}'));
+        assert_eq!(glyph('Р'), glyph('P'));
+    }
+
+    #[test]
+    fn measures_text() {
+        assert_eq!(text_width(""), 0);
+        assert_eq!(text_width("A"), GLYPH_WIDTH);
+        assert_eq!(text_width("Счёт"), 4 * ADVANCE - 1);
+        assert_eq!(text_width("ab\nabcd\n"), 4 * ADVANCE - 1);
+        assert_eq!(text_height("ab"), GLYPH_HEIGHT);
+        assert_eq!(text_height("a\nb"), LINE_HEIGHT + GLYPH_HEIGHT);
+    }
+
+    #[test]
+    fn draws_text_into_buffer() {
+        const W: usize = 16;
+        const H: usize = 24;
+        let mut px = [0u32; W * H];
+        let mut depth = [0.0f32; W * H];
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        let end = draw_text(&mut fb, 0, 0, "I\nЖ", 1);
+        assert_eq!(end, ADVANCE as i32);
+        // Верх 'I' — перекладина .###.
+        assert_eq!(&px[..5], &[0, 1, 1, 1, 0]);
+        // Вторая строка начинается с 'Ж' (#.#.#)
+        let row = LINE_HEIGHT * W;
+        assert_eq!(&px[row..row + 5], &[1, 0, 1, 0, 1]);
+
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.clear(0);
+        draw_text_scaled(&mut fb, 0, 0, "I", 2, 2);
+        assert_eq!(&px[..10], &[0, 0, 2, 2, 2, 2, 2, 2, 0, 0]);
+        assert_eq!(&px[W..W + 10], &[0, 0, 2, 2, 2, 2, 2, 2, 0, 0]);
+    }
+}
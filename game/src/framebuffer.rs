@@ -0,0 +1,690 @@
+//! Описание кадрового буфера, которое передаёт хост, и рисование в него
+
+use crate::math::Vec2;
+use crate::sprite::blend;
+use crate::texture::Texture;
+
+/// Формат 32-битного пикселя (как в DRM, порядок от старшего бита к младшему)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 0xXXRRGGBB: в памяти байты B, G, R, X
+    Xrgb8888 = 0,
+    /// 0xXXBBGGRR: в памяти байты R, G, B, X
+    Xbgr8888 = 1,
+}
+
+impl PixelFormat {
+    /// Перевести цвет игры (0xAARRGGBB) в слово этого формата
+    #[inline]
+    pub fn pack(self, argb: u32) -> u32 {
+        match self {
+            PixelFormat::Xrgb8888 => argb,
+            PixelFormat::Xbgr8888 => {
+                (argb & 0xFF00_FF00) | ((argb >> 16) & 0xFF) | ((argb & 0xFF) << 16)
+            }
+        }
+    }
+
+    /// Обратное к `pack`: слово этого формата -> 0xXXRRGGBB
+    #[inline]
+    pub fn unpack(self, word: u32) -> u32 {
+        // Перестановка R и B — сама себе обратная
+        self.pack(word)
+    }
+}
+
+/// Прямоугольник на экране или в изображении
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Rect { x, y, w, h }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.w && y < self.y + self.h
+    }
+
+    /// Общая часть двух прямоугольников; если они не пересекаются — пустой
+    /// прямоугольник в начале координат, чтобы его угол не лежал за экраном
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.w).min(other.x + other.w);
+        let y1 = (self.y + self.h).min(other.y + other.h);
+        if x1 <= x0 || y1 <= y0 {
+            return Rect::new(0, 0, 0, 0);
+        }
+        Rect::new(x0, y0, x1 - x0, y1 - y0)
+    }
+}
+
+/// Кадровый буфер, выделенный хостом. Передаётся в `game::init`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FramebufferInfo {
+    /// Физический адрес первого пикселя
+    pub base: u64,
+    pub width: u32,
+    pub height: u32,
+    /// Длина строки в байтах (не меньше width * 4)
+    pub stride: u32,
+    pub format: PixelFormat,
+}
+
+impl FramebufferInfo {
+    /// Сколько байт занимает буфер
+    pub fn size_bytes(&self) -> usize {
+        self.stride as usize * self.height as usize
+    }
+}
+
+/// Видимый кадровый буфер хоста.
+///
+/// Игра в него не рисует: готовый кадр копируется сюда из заднего буфера
+/// вызовом [`Display::present`], поэтому хост никогда не видит кадр наполовину.
+pub struct Display {
+    base: *mut u8,
+    width: usize,
+    height: usize,
+    /// Длина строки в байтах
+    stride: usize,
+    format: PixelFormat,
+}
+
+impl Display {
+    /// # Safety
+    /// `info.base` должен указывать на доступную для записи память размером
+    /// `info.size_bytes()`, которая живёт всё время работы.
+    pub unsafe fn new(info: &FramebufferInfo) -> Self {
+        Display {
+            base: info.base as *mut u8,
+            width: info.width as usize,
+            height: info.height as usize,
+            stride: info.stride as usize,
+            format: info.format,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Скопировать готовый кадр в видимый буфер построчно
+    pub fn present(&mut self, back: &Framebuffer<'_>) {
+        assert!(back.width == self.width && back.height == self.height, "размер заднего буфера не совпадает с экраном");
+        assert!(back.format == self.format, "формат заднего буфера не совпадает с экраном");
+        for (y, row) in back.pixels.chunks_exact(back.width).enumerate() {
+            unsafe {
+                let dst = self.base.add(y * self.stride) as *mut u32;
+                core::ptr::copy_nonoverlapping(row.as_ptr(), dst, row.len());
+            }
+        }
+    }
+}
+
+/// Цель рисования: пиксели в обычной памяти (задний буфер) и буфер глубины того же размера
+pub struct Framebuffer<'a> {
+    /// Строки без отступов, слова уже в формате `format`
+    pixels: &'a mut [u32],
+    width: usize,
+    height: usize,
+    format: PixelFormat,
+    /// Для каждого пикселя хранится глубина ближайшей нарисованной точки
+    depth: &'a mut [f32],
+}
+
+impl<'a> Framebuffer<'a> {
+    /// Рисовать в обычный срез (строки без отступов, формат XRGB8888)
+    pub fn from_slice(pixels: &'a mut [u32], width: usize, height: usize, depth: &'a mut [f32]) -> Self {
+        Self::with_format(pixels, width, height, PixelFormat::Xrgb8888, depth)
+    }
+
+    /// Рисовать в срез, слова которого хранятся в формате `format`
+    pub fn with_format(
+        pixels: &'a mut [u32],
+        width: usize,
+        height: usize,
+        format: PixelFormat,
+        depth: &'a mut [f32],
+    ) -> Self {
+        assert!(pixels.len() >= width * height, "срез пикселей меньше экрана");
+        assert!(depth.len() >= width * height, "буфер глубины меньше экрана");
+        Framebuffer {
+            pixels: &mut pixels[..width * height],
+            width,
+            height,
+            format,
+            depth: &mut depth[..width * height],
+        }
+    }
+
+    /// Пиксели кадра в формате `format()`
+    pub fn pixels(&self) -> &[u32] {
+        self.pixels
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Отношение ширины к высоте
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    /// Залить весь экран цветом
+    pub fn clear(&mut self, color: u32) {
+        fill_wide(self.pixels, self.format.pack(color));
+    }
+
+    /// Сбросить буфер глубины перед новым кадром
+    pub fn clear_depth(&mut self) {
+        // f32 и u32 одного размера, любое значение u32 — допустимый f32
+        let words = unsafe { core::slice::from_raw_parts_mut(self.depth.as_mut_ptr() as *mut u32, self.depth.len()) };
+        fill_wide(words, f32::INFINITY.to_bits());
+    }
+
+    pub fn put_pixel(&mut self, x: i32, y: i32, color: u32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        self.pixels[y as usize * self.width + x as usize] = self.format.pack(color);
+    }
+
+    /// Прочитать цвет пикселя (0xFFRRGGBB); за пределами экрана — 0
+    pub fn get_pixel(&self, x: i32, y: i32) -> u32 {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return 0;
+        }
+        self.format.unpack(self.pixels[y as usize * self.width + x as usize]) | 0xFF00_0000
+    }
+
+    /// Весь экран как прямоугольник
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    /// Залить строку `y` от `x0` до `x1` включительно; часть за экраном отбрасывается
+    pub fn fill_span(&mut self, y: i32, x0: i32, x1: i32, color: u32) {
+        let (x0, x1) = (x0.min(x1).max(0), x0.max(x1).min(self.width as i32 - 1));
+        if y < 0 || y >= self.height as i32 || x0 > x1 {
+            return;
+        }
+        let row = y as usize * self.width;
+        fill_wide(&mut self.pixels[row + x0 as usize..=row + x1 as usize], self.format.pack(color));
+    }
+
+    /// Залить прямоугольник; часть за экраном отбрасывается
+    pub fn fill_rect(&mut self, rect: Rect, color: u32) {
+        let r = rect.intersect(&self.bounds());
+        for y in r.y..r.y + r.h {
+            self.fill_span(y, r.x, r.x + r.w - 1, color);
+        }
+    }
+
+    /// Наложить на прямоугольник цвет 0xAARRGGBB с его прозрачностью (затемнение, подложка окна)
+    pub fn blend_rect(&mut self, rect: Rect, color: u32) {
+        let r = rect.intersect(&self.bounds());
+        let format = self.format;
+        for y in r.y..r.y + r.h {
+            let row = y as usize * self.width;
+            for p in &mut self.pixels[row + r.x as usize..row + (r.x + r.w) as usize] {
+                *p = format.pack(blend(format.unpack(*p), color));
+            }
+        }
+    }
+
+    /// Отрезок по Брезенхему. Сначала он обрезается по экрану (Коэн — Сазерленд),
+    /// поэтому пиксели пишутся без проверки границ, а отрезок целиком за экраном
+    /// не стоит ничего.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: u32) {
+        let Some((mut x0, mut y0, x1, y1)) = clip_line(x0, y0, x1, y1, self.width as i32 - 1, self.height as i32 - 1) else {
+            return;
+        };
+        if y0 == y1 {
+            return self.fill_span(y0, x0, x1, color);
+        }
+        let color = self.format.pack(color);
+        let dx = (x1 - x0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let dy = -(y1 - y0).abs();
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            self.pixels[y0 as usize * self.width + x0 as usize] = color;
+            if x0 == x1 && y0 == y1 { break; }
+            let e2 = 2 * err;
+            if e2 >= dy { err += dy; x0 += sx; }
+            if e2 <= dx { err += dx; y0 += sy; }
+        }
+    }
+
+    /// Залить треугольник с проверкой глубины.
+    ///
+    /// Вершины задаются как (x, y, глубина) в экранных координатах, глубина растёт от зрителя.
+    /// Лицевые грани идут по часовой стрелке на экране (ось y смотрит вниз),
+    /// треугольники с обратным обходом отбрасываются.
+    pub fn fill_triangle(&mut self, v0: (i32, i32, f32), v1: (i32, i32, f32), v2: (i32, i32, f32), color: u32) {
+        let (x0, y0, z0) = v0;
+        let (x1, y1, z1) = v1;
+        let (x2, y2, z2) = v2;
+        let area = edge(x0, y0, x1, y1, x2, y2);
+        if area <= 0 {
+            return; // задняя или вырожденная грань
+        }
+        let (depth, dzdx, dzdy) = plane([x0, x1, x2], [y0, y1, y2], [z0, z1, z2], area);
+        let color = self.format.pack(color);
+        let (min_y, max_y) = (y0.min(y1).min(y2).max(0), y0.max(y1).max(y2).min(self.height as i32 - 1));
+        for y in min_y..=max_y {
+            let Some((left, right)) = row_span([(x0, y0), (x1, y1), (x2, y2)], y, self.width as i32) else {
+                continue;
+            };
+            let row = y as usize * self.width;
+            let mut z = depth + dzdx * (left - x0) as f32 + dzdy * (y - y0) as f32;
+            for idx in row + left as usize..=row + right as usize {
+                if z < self.depth[idx] {
+                    self.depth[idx] = z;
+                    self.pixels[idx] = color;
+                }
+                z += dzdx;
+            }
+        }
+    }
+
+    /// Залить треугольник с проверкой глубины, плавно смешивая цвета вершин (затенение по Гуро).
+    ///
+    /// Вершины и отбрасывание задних граней — как у [`Framebuffer::fill_triangle`].
+    /// Каналы цвета считаются в целых числах Q16.16 с шагом на пиксель.
+    pub fn fill_triangle_gouraud(&mut self, v0: (i32, i32, f32), v1: (i32, i32, f32), v2: (i32, i32, f32), colors: [u32; 3]) {
+        let (x0, y0, z0) = v0;
+        let (x1, y1, z1) = v1;
+        let (x2, y2, z2) = v2;
+        let area = edge(x0, y0, x1, y1, x2, y2);
+        if area <= 0 {
+            return;
+        }
+        let (depth, dzdx, dzdy) = plane([x0, x1, x2], [y0, y1, y2], [z0, z1, z2], area);
+        // Плоскость канала: c(x, y) = c0 + dx * (x - x0) + dy * (y - y0). Узкий треугольник
+        // даёт крутой наклон, поэтому значения в i64, а не в i32
+        let channel = |shift: u32| {
+            let c = colors.map(|c| ((c >> shift) & 0xFF) as i64);
+            let (d1, d2) = (c[1] - c[0], c[2] - c[0]);
+            let dx = ((d1 * (y2 - y0) as i64 - d2 * (y1 - y0) as i64) << 16) / area as i64;
+            let dy = ((d2 * (x1 - x0) as i64 - d1 * (x2 - x0) as i64) << 16) / area as i64;
+            ((c[0] << 16) + (1 << 15), dx, dy)
+        };
+        let planes = [channel(16), channel(8), channel(0)];
+        let (min_y, max_y) = (y0.min(y1).min(y2).max(0), y0.max(y1).max(y2).min(self.height as i32 - 1));
+        for y in min_y..=max_y {
+            let Some((left, right)) = row_span([(x0, y0), (x1, y1), (x2, y2)], y, self.width as i32) else {
+                continue;
+            };
+            let row = y as usize * self.width;
+            let mut z = depth + dzdx * (left - x0) as f32 + dzdy * (y - y0) as f32;
+            let mut c = planes.map(|(start, dx, dy)| start + dx * (left - x0) as i64 + dy * (y - y0) as i64);
+            for idx in row + left as usize..=row + right as usize {
+                if z < self.depth[idx] {
+                    self.depth[idx] = z;
+                    let [r, g, b] = c.map(|v| (v >> 16).clamp(0, 255) as u32);
+                    self.pixels[idx] = self.format.pack(0xFF00_0000 | r << 16 | g << 8 | b);
+                }
+                z += dzdx;
+                for (v, p) in c.iter_mut().zip(&planes) {
+                    *v += p.1;
+                }
+            }
+        }
+    }
+
+    /// Залить треугольник текстурой с проверкой глубины и коррекцией перспективы.
+    ///
+    /// Отбрасывание задних граней — как у [`Framebuffer::fill_triangle`]. Экранные
+    /// u / z, v / z и 1 / z меняются линейно, поэтому интерполируются они, а (u, v)
+    /// восстанавливается делением в каждом пикселе. Тексель умножается на `tint`;
+    /// тексели с альфой меньше половины не рисуются.
+    pub fn fill_triangle_textured(&mut self, v: [TexVertex; 3], texture: &Texture<'_>, tint: u32) {
+        let [a, b, c] = v;
+        let area = edge(a.x, a.y, b.x, b.y, c.x, c.y);
+        if area <= 0 {
+            return;
+        }
+        let plane = |attr: fn(&TexVertex) -> f32| plane([a.x, b.x, c.x], [a.y, b.y, c.y], v.map(|v| attr(&v)), area);
+        let planes = [
+            plane(|v| v.depth),
+            plane(|v| v.inv_z),
+            plane(|v| v.uv.x * v.inv_z),
+            plane(|v| v.uv.y * v.inv_z),
+        ];
+        let min_y = a.y.min(b.y).min(c.y).max(0);
+        let max_y = a.y.max(b.y).max(c.y).min(self.height as i32 - 1);
+        for y in min_y..=max_y {
+            let Some((left, right)) = row_span([(a.x, a.y), (b.x, b.y), (c.x, c.y)], y, self.width as i32) else {
+                continue;
+            };
+            let at = |(start, dx, dy): (f32, f32, f32)| {
+                (start + dx * (left - a.x) as f32 + dy * (y - a.y) as f32, dx)
+            };
+            self.fill_textured_span(y, left, right, planes.map(at), texture, tint);
+        }
+    }
+
+    /// Текстурированный отрезок строки `y` от `x0` до `x1` включительно. `attrs` —
+    /// (значение в `x0`, шаг по x) для глубины, 1 / z, u / z и v / z.
+    fn fill_textured_span(&mut self, y: i32, x0: i32, x1: i32, attrs: [(f32, f32); 4], texture: &Texture<'_>, tint: u32) {
+        let [mut depth, mut inv_z, mut u, mut v] = attrs.map(|(value, _)| value);
+        let [dd, dw, du, dv] = attrs.map(|(_, step)| step);
+        let row = y as usize * self.width;
+        for x in x0..=x1 {
+            let idx = row + x as usize;
+            if depth < self.depth[idx] && inv_z > 0.0 {
+                let z = 1.0 / inv_z;
+                let texel = texture.sample(u * z, v * z);
+                if texel >> 24 >= 0x80 {
+                    self.depth[idx] = depth;
+                    self.pixels[idx] = self.format.pack(modulate(texel, tint));
+                }
+            }
+            depth += dd;
+            inv_z += dw;
+            u += du;
+            v += dv;
+        }
+    }
+}
+
+/// Вершина текстурированного треугольника на экране
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TexVertex {
+    pub x: i32,
+    pub y: i32,
+    /// Глубина, как у [`Framebuffer::fill_triangle`]
+    pub depth: f32,
+    /// 1 / z вершины в пространстве камеры
+    pub inv_z: f32,
+    pub uv: Vec2,
+}
+
+/// Умножить RGB цвета на `tint`; результат непрозрачный
+#[inline]
+fn modulate(color: u32, tint: u32) -> u32 {
+    let channel = |shift: u32| (((color >> shift) & 0xFF) * (((tint >> shift) & 0xFF) + 1)) >> 8 << shift;
+    0xFF00_0000 | channel(16) | channel(8) | channel(0)
+}
+
+/// Залить срез словом `value` блоками по 64 байта: компилятор превращает блок в
+/// несколько широких записей без проверок границ на каждый пиксель
+#[inline]
+fn fill_wide(dst: &mut [u32], value: u32) {
+    const BLOCK: usize = 16;
+    let block = [value; BLOCK];
+    let mut chunks = dst.chunks_exact_mut(BLOCK);
+    for chunk in &mut chunks {
+        chunk.copy_from_slice(&block);
+    }
+    chunks.into_remainder().fill(value);
+}
+
+/// Коды областей Коэна — Сазерленда: где точка относительно экрана
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+fn outcode(x: i64, y: i64, max_x: i64, max_y: i64) -> u8 {
+    let mut code = 0;
+    if x < 0 { code |= LEFT } else if x > max_x { code |= RIGHT }
+    if y < 0 { code |= TOP } else if y > max_y { code |= BOTTOM }
+    code
+}
+
+/// Обрезать отрезок по прямоугольнику (0, 0)..=(max_x, max_y); `None`, если он весь снаружи
+fn clip_line(x0: i32, y0: i32, x1: i32, y1: i32, max_x: i32, max_y: i32) -> Option<(i32, i32, i32, i32)> {
+    if max_x < 0 || max_y < 0 {
+        return None;
+    }
+    // В i64: произведения разностей координат не помещаются в i32
+    let (mut x0, mut y0, mut x1, mut y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+    let (max_x, max_y) = (max_x as i64, max_y as i64);
+    let mut code0 = outcode(x0, y0, max_x, max_y);
+    let mut code1 = outcode(x1, y1, max_x, max_y);
+    loop {
+        if code0 | code1 == 0 {
+            return Some((x0 as i32, y0 as i32, x1 as i32, y1 as i32));
+        }
+        if code0 & code1 != 0 {
+            return None;
+        }
+        // Переносим внешний конец на границу, за которой он лежит
+        let code = if code0 != 0 { code0 } else { code1 };
+        let (x, y) = if code & TOP != 0 {
+            (x0 + (x1 - x0) * -y0 / (y1 - y0), 0)
+        } else if code & BOTTOM != 0 {
+            (x0 + (x1 - x0) * (max_y - y0) / (y1 - y0), max_y)
+        } else if code & LEFT != 0 {
+            (0, y0 + (y1 - y0) * -x0 / (x1 - x0))
+        } else {
+            (max_x, y0 + (y1 - y0) * (max_x - x0) / (x1 - x0))
+        };
+        if code == code0 {
+            (x0, y0) = (x, y);
+            code0 = outcode(x0, y0, max_x, max_y);
+        } else {
+            (x1, y1) = (x, y);
+            code1 = outcode(x1, y1, max_x, max_y);
+        }
+    }
+}
+
+/// Пиксели строки `y` внутри треугольника (по часовой стрелке), обрезанные по ширине экрана.
+/// Совпадают с пикселями, у которых все три [`edge`] неотрицательны.
+fn row_span(v: [(i32, i32); 3], y: i32, width: i32) -> Option<(i32, i32)> {
+    let (mut left, mut right) = (0, width - 1);
+    for k in 0..3 {
+        let ((px, py), (qx, qy)) = (v[(k + 1) % 3], v[(k + 2) % 3]);
+        // Ребро линейно по x: w(x) = s * x + m >= 0 отрезает полуплоскость
+        let s = -(qy - py);
+        let m = (qx - px) * (y - py) + (qy - py) * px;
+        match s.signum() {
+            1 => left = left.max(-m.div_euclid(s)),
+            -1 => right = right.min(m.div_euclid(-s)),
+            _ if m < 0 => return None,
+            _ => {}
+        }
+    }
+    (left <= right).then_some((left, right))
+}
+
+/// Плоскость атрибута со значениями `a` в вершинах: (значение в первой вершине, шаг по x, шаг по y)
+fn plane(x: [i32; 3], y: [i32; 3], a: [f32; 3], area: i32) -> (f32, f32, f32) {
+    let (d1, d2) = (a[1] - a[0], a[2] - a[0]);
+    let inv_area = 1.0 / area as f32;
+    let dx = (d1 * (y[2] - y[0]) as f32 - d2 * (y[1] - y[0]) as f32) * inv_area;
+    let dy = (d2 * (x[1] - x[0]) as f32 - d1 * (x[2] - x[0]) as f32) * inv_area;
+    (a[0], dx, dy)
+}
+
+/// Удвоенная знаковая площадь треугольника (a, b, c) в экранных координатах
+fn edge(ax: i32, ay: i32, bx: i32, by: i32, cx: i32, cy: i32) -> i32 {
+    (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Image;
+    use crate::texture::Wrap;
+
+    const W: usize = 8;
+    const H: usize = 8;
+
+    fn buffers() -> ([u32; W * H], [f32; W * H]) {
+        ([0; W * H], [f32::INFINITY; W * H])
+    }
+
+    #[test]
+    fn put_pixel_clips_to_screen() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.put_pixel(-1, 0, 1);
+        fb.put_pixel(0, H as i32, 1);
+        fb.put_pixel(3, 2, 0xFF00FF00);
+        assert_eq!(px.iter().filter(|&&p| p != 0).count(), 1);
+        assert_eq!(px[2 * W + 3], 0xFF00FF00);
+    }
+
+    #[test]
+    fn lines_are_clipped_to_screen() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        // Целиком за экраном — ничего; концы далеко снаружи не переполняют вычисления
+        fb.draw_line(-5, -5, 20, -1, 1);
+        fb.draw_line(i32::MIN / 2, 9, i32::MAX / 2, 9, 1);
+        assert!(px.iter().all(|&p| p == 0));
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.draw_line(-100, 3, 100, 3, 2);
+        fb.draw_line(-2, -2, 10, 10, 3);
+        fb.draw_line(6, 20, 6, -20, 4);
+        assert!((0..W).filter(|&x| x != 3 && x != 6).all(|x| px[3 * W + x] == 2));
+        assert!((0..H).filter(|&i| i != 6).all(|i| px[i * W + i] == 3));
+        assert!((0..H).all(|y| px[y * W + 6] == 4));
+        assert_eq!(px.iter().filter(|&&p| p != 0).count(), W + H - 1 + H - 2);
+    }
+
+    #[test]
+    fn spans_and_rects_fill_inside_screen() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.fill_span(1, 5, -3, 1);
+        fb.fill_span(-1, 0, 7, 1);
+        fb.fill_rect(Rect::new(6, 6, 10, 10), 2);
+        assert_eq!(&px[W..2 * W], &[1, 1, 1, 1, 1, 1, 0, 0]);
+        assert_eq!(px.iter().filter(|&&p| p == 2).count(), 4);
+        assert_eq!(px[7 * W + 7], 2);
+        // Невыровненный срез: заливаются и края, и середина
+        let mut words = [0u32; 11];
+        fill_wide(&mut words[1..10], 7);
+        assert_eq!(words, [0, 7, 7, 7, 7, 7, 7, 7, 7, 7, 0]);
+    }
+
+    #[test]
+    fn rects_off_screen_draw_nothing() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        // Справа, снизу и левее экрана: пересечение пустое и не указывает за буфер
+        for rect in [Rect::new(20, 0, 5, 8), Rect::new(0, 9, 8, 3), Rect::new(-6, 2, 4, 4)] {
+            assert_eq!(rect.intersect(&fb.bounds()), Rect::new(0, 0, 0, 0));
+            fb.blend_rect(rect, 0x80FFFFFF);
+            fb.fill_rect(rect, 3);
+        }
+        assert!(px.iter().all(|&p| p == 0));
+    }
+
+    #[test]
+    fn triangle_culling_and_depth() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        // Обход против часовой стрелки — задняя грань, ничего не рисуется
+        fb.fill_triangle((0, 0, 0.5), (0, 7, 0.5), (7, 0, 0.5), 1);
+        assert!(px.iter().all(|&p| p == 0));
+
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.fill_triangle((0, 0, 0.5), (7, 0, 0.5), (0, 7, 0.5), 1);
+        // Дальний треугольник не перекрывает ближний, ближний перекрывает дальний
+        fb.fill_triangle((0, 0, 0.9), (7, 0, 0.9), (0, 7, 0.9), 2);
+        fb.fill_triangle((0, 0, 0.1), (3, 0, 0.1), (0, 3, 0.1), 3);
+        assert_eq!(px[0], 3);
+        assert_eq!(px[5], 1);
+        assert_eq!(px[7 * W + 7], 0);
+    }
+
+    #[test]
+    fn gouraud_blends_vertex_colors() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.fill_triangle_gouraud((0, 0, 0.5), (7, 0, 0.5), (0, 7, 0.5), [0xFF000000, 0xFFFE0000, 0xFF0000FE]);
+        // В вершинах — их цвета, посередине ребра — половина, красный растёт только по x
+        assert_eq!(px[0], 0xFF000000);
+        assert_eq!(px[7], 0xFFFE0000);
+        assert_eq!(px[7 * W], 0xFF0000FE);
+        assert_eq!(px[3 * W + 3], 0xFF6D006D);
+        assert_eq!((px[W + 2] >> 16) & 0xFF, (px[5 * W + 2] >> 16) & 0xFF);
+    }
+
+    #[test]
+    fn texture_is_perspective_correct() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        let stripes = [0xFF0000FF, 0xFFFF0000];
+        let texture = Texture::new(Image::new(2, 1, &stripes)).with_wrap(Wrap::Clamp);
+        let vertex = |x, y, inv_z, u| TexVertex { x, y, depth: 0.5, inv_z, uv: Vec2::new(u, 0.0) };
+        // Правый край втрое дальше левого: на экране середина текстуры смещается вправо
+        let tri = [vertex(0, 0, 1.0, 0.0), vertex(7, 0, 1.0 / 3.0, 1.0), vertex(0, 7, 1.0, 0.0)];
+        fb.fill_triangle_textured(tri, &texture, 0xFFFFFFFF);
+        assert_eq!(px[0], 0xFF0000FF);
+        assert_eq!(px[5], 0xFF0000FF, "при линейной интерполяции здесь был бы красный");
+        assert_eq!(px[7], 0xFFFF0000);
+        assert_eq!(px[7 * W + 1], 0);
+        // Те же пиксели, что у заливки одним цветом
+        let (mut flat, mut flat_depth) = buffers();
+        let mut fb = Framebuffer::from_slice(&mut flat, W, H, &mut flat_depth);
+        fb.fill_triangle((0, 0, 0.5), (7, 0, 0.5), (0, 7, 0.5), 1);
+        assert!(px.iter().zip(&flat).all(|(&t, &f)| (t != 0) == (f != 0)));
+    }
+
+    #[test]
+    fn present_copies_rows_with_stride() {
+        let (mut px, mut depth) = buffers();
+        let mut fb = Framebuffer::with_format(&mut px, W, H, PixelFormat::Xbgr8888, &mut depth);
+        fb.clear(0xFF000000);
+        fb.put_pixel(1, 1, 0xFF112233);
+        assert_eq!(fb.get_pixel(1, 1), 0xFF112233);
+        // Строка видимого буфера длиннее строки заднего на 2 слова
+        let stride = W + 2;
+        let mut front = [0u32; (W + 2) * H];
+        let info = FramebufferInfo {
+            base: front.as_mut_ptr() as u64,
+            width: W as u32,
+            height: H as u32,
+            stride: (stride * 4) as u32,
+            format: PixelFormat::Xbgr8888,
+        };
+        unsafe { Display::new(&info) }.present(&fb);
+        assert_eq!(front[stride + 1], 0xFF332211);
+        assert_eq!(front[stride], 0xFF000000);
+        assert_eq!(front[W], 0);
+    }
+
+    #[test]
+    fn xbgr_swaps_red_and_blue() {
+        assert_eq!(PixelFormat::Xbgr8888.pack(0xFF112233), 0xFF332211);
+        assert_eq!(PixelFormat::Xrgb8888.pack(0xFF112233), 0xFF112233);
+    }
+}
@@ -0,0 +1,353 @@
+//! Запуск игры на хосте (feature `std`): кадр рисуется в обычный `Vec<u32>`.
+//!
+//! Позволяет тестировать рисование и отлаживать игру без mykvm и vmm.
+
+use std::io::{self, Write};
+use std::vec;
+use std::vec::Vec;
+
+use crate::abi::Game;
+use crate::arena::Arena;
+use crate::framebuffer::Framebuffer;
+use crate::input::{Event, InputState, Key};
+use crate::timestep::FixedTimestep;
+
+/// Экран в памяти хоста; пиксели в формате 0xAARRGGBB, строки без отступов
+pub struct Surface {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+    depth: Vec<f32>,
+}
+
+impl Surface {
+    pub fn new(width: usize, height: usize) -> Self {
+        Surface {
+            width,
+            height,
+            pixels: vec![0; width * height],
+            depth: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Цель рисования поверх этой поверхности
+    pub fn framebuffer(&mut self) -> Framebuffer<'_> {
+        Framebuffer::from_slice(&mut self.pixels, self.width, self.height, &mut self.depth)
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Записать кадр в формате PPM (P6)
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut data = std::format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.reserve(self.pixels.len() * 3);
+        for &px in &self.pixels {
+            data.extend_from_slice(&[(px >> 16) as u8, (px >> 8) as u8, px as u8]);
+        }
+        out.write_all(&data)
+    }
+}
+
+/// Игра вместе с экраном и памятью, которую в госте выдаёт ядро.
+///
+/// Ввод в тестах подаётся вручную: [`HostedGame::send`], [`HostedGame::tap`],
+/// [`HostedGame::type_text`] — так же, как ядро передаёт события от VMM.
+pub struct HostedGame<G: Game> {
+    // Игра может держать срезы из `scratch`, поэтому объявлена раньше него
+    // и удаляется первой
+    game: G,
+    surface: Surface,
+    input: InputState,
+    timestep: FixedTimestep,
+    _scratch: Vec<u8>,
+}
+
+impl<G: Game> HostedGame<G> {
+    /// Создать игру с экраном `width` x `height` и `scratch_size` байтами памяти
+    pub fn new(width: usize, height: usize, scratch_size: usize) -> Self {
+        let mut surface = Surface::new(width, height);
+        let mut scratch = vec![0u8; scratch_size];
+        // Буфер Vec не переезжает, пока жив HostedGame
+        let mut arena = unsafe { Arena::new(scratch.as_mut_ptr(), scratch.len()) };
+        let game = G::init(&surface.framebuffer(), &mut arena);
+        HostedGame {
+            game,
+            surface,
+            input: InputState::new(),
+            timestep: FixedTimestep::new(G::TICK, G::MAX_STEPS),
+            _scratch: scratch,
+        }
+    }
+
+    pub fn game(&mut self) -> &mut G {
+        &mut self.game
+    }
+
+    /// Передать событие; игра увидит его в следующем `update`
+    pub fn send(&mut self, event: Event) {
+        self.input.push(event);
+    }
+
+    /// Нажать и отпустить клавишу в одном кадре
+    pub fn tap(&mut self, key: Key) {
+        self.send(Event::KeyDown(key));
+        self.send(Event::KeyUp(key));
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.send(Event::Text(c));
+        }
+    }
+
+    /// Прогнать игру на `elapsed` секунд, как это делает ядро
+    pub fn update(&mut self, elapsed: f32) {
+        self.timestep.run(&mut self.game, &mut self.input, elapsed);
+    }
+
+    /// Ровно один шаг симуляции
+    pub fn step(&mut self) {
+        self.update(G::TICK);
+    }
+
+    pub fn render(&mut self) {
+        self.game.render(&mut self.surface.framebuffer(), self.timestep.alpha());
+    }
+
+    pub fn surface(&self) -> &Surface {
+        &self.surface
+    }
+
+    /// Следующие `out.len()` отсчётов звука игры, как их получает кольцо VMM
+    pub fn mix(&mut self, out: &mut [i16]) {
+        self.game.mix(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{CubeGame, CubeScene};
+    use crate::input::MouseButton;
+
+    /// Игра, в которой из меню уже начат уровень
+    fn playing(width: usize, height: usize) -> HostedGame<CubeGame> {
+        let mut game = HostedGame::<CubeGame>::new(width, height, 1 << 20);
+        game.tap(Key::ENTER);
+        game.step();
+        while game.game().scenes().is_fading() {
+            game.step();
+        }
+        game
+    }
+
+    #[test]
+    fn cube_renders_in_the_middle() {
+        let mut game = playing(160, 120);
+        game.step();
+        game.render();
+        let s = game.surface();
+        // Углы экрана очищены, в центре виден куб
+        assert_eq!(s.pixel(0, 0), 0xFF000000);
+        assert_eq!(s.pixel(159, 119), 0xFF000000);
+        assert_ne!(s.pixel(80, 60), 0xFF000000);
+        // Индикатор из встроенного листа спрайтов в правом верхнем углу
+        assert_ne!(s.pixel(148, 12), 0xFF000000);
+    }
+
+    #[test]
+    fn menu_starts_the_game() {
+        let mut game = HostedGame::<CubeGame>::new(160, 120, 1 << 20);
+        game.step();
+        assert!(matches!(game.game().scenes().top(), Some(CubeScene::Menu)));
+        game.render();
+        // Фон меню, а не чёрный экран игры
+        assert_eq!(game.surface().pixel(0, 119), 0xFF101828);
+        let mut game = playing(160, 120);
+        assert!(matches!(game.game().scenes().top(), Some(CubeScene::Play)));
+        // Escape возвращает в меню через затемнение
+        game.tap(Key::ESCAPE);
+        for _ in 0..60 {
+            game.step();
+        }
+        assert!(matches!(game.game().scenes().top(), Some(CubeScene::Menu)));
+    }
+
+    #[test]
+    fn input_reaches_the_game() {
+        let mut paused = playing(64, 64);
+        let mut running = playing(64, 64);
+        paused.tap(Key::SPACE);
+        for _ in 0..10 {
+            paused.step();
+            running.step();
+        }
+        // Пауза легла поверх игры, и мир под ней стоит; работающий куб повернулся
+        let angle = |g: &mut HostedGame<CubeGame>| {
+            let world = g.game().world();
+            world.transforms.get(world.player.unwrap()).unwrap().angle
+        };
+        assert!(matches!(paused.game().scenes().top(), Some(CubeScene::Pause)));
+        assert_eq!(angle(&mut paused), 0.0);
+        assert!(angle(&mut running) > 0.0);
+        // Пробел снимает паузу
+        paused.tap(Key::SPACE);
+        paused.step();
+        paused.step();
+        assert!(angle(&mut paused) > 0.0);
+    }
+
+    #[test]
+    fn cube_jumps_and_lands_on_the_floor() {
+        let mut game = playing(64, 64);
+        let height = |g: &mut HostedGame<CubeGame>| {
+            let world = g.game().world();
+            world.transforms.get(world.player.unwrap()).unwrap().position.y
+        };
+        // Стоящий куб пол держит
+        for _ in 0..10 {
+            game.step();
+        }
+        assert!(height(&mut game).abs() < 0.01);
+        game.tap(Key::UP);
+        let mut top = 0.0f32;
+        for _ in 0..60 {
+            game.step();
+            top = top.min(height(&mut game));
+        }
+        // Подлетел на v²/2g ≈ 1.2 и вернулся на пол
+        assert!(top < -1.0, "{}", top);
+        assert!(height(&mut game).abs() < 0.01, "{}", height(&mut game));
+    }
+
+    #[test]
+    fn spinning_too_fast_ends_the_game() {
+        let mut game = playing(64, 64);
+        game.send(Event::KeyDown(Key::RIGHT));
+        for _ in 0..180 {
+            game.step();
+            if game.game().scenes().scenes().len() > 1 {
+                break;
+            }
+        }
+        assert!(matches!(game.game().scenes().top(), Some(CubeScene::GameOver { .. })));
+        assert!(game.game().session().best > 1.0);
+        // Из куба вылетели искры, и они ещё летят
+        assert!(!game.game().session().sparks.is_empty());
+    }
+
+    #[test]
+    fn debug_panel_toggles_texture() {
+        let mut game = playing(160, 120);
+        game.tap(Key::f(1));
+        game.step();
+        game.render();
+        // Окно панели справа, строка «Текстура» — третья под заголовком
+        game.send(Event::MouseMove { x: 60, y: 81 });
+        game.send(Event::MouseDown(MouseButton::Left));
+        game.send(Event::MouseUp(MouseButton::Left));
+        game.step();
+        assert!(game.game().world().textured);
+        game.render();
+        assert!(!game.game().world().textured);
+        assert_eq!(game.surface().pixel(100, 30), crate::ui::Theme::DARK.title);
+    }
+
+    #[test]
+    fn focused_panel_keeps_keys_from_the_game() {
+        let mut game = playing(160, 120);
+        game.tap(Key::f(1));
+        game.step();
+        game.render();
+        // Tab ставит фокус на «Гуро», пробел снимает флажок, но не ставит паузу
+        game.tap(Key::TAB);
+        game.step();
+        game.render();
+        game.tap(Key::SPACE);
+        game.step();
+        game.render();
+        assert_eq!(game.game().world().shading, crate::render::Shading::Flat);
+        assert!(matches!(game.game().scenes().top(), Some(CubeScene::Play)));
+        // Стрелка вверх переводит фокус, а куб не прыгает
+        game.tap(Key::UP);
+        for _ in 0..10 {
+            game.step();
+        }
+        let world = game.game().world();
+        assert!(world.transforms.get(world.player.unwrap()).unwrap().position.y.abs() < 0.01);
+    }
+
+    #[test]
+    fn game_hums_and_pause_silences_it() {
+        let mut game = HostedGame::<CubeGame>::new(64, 64, 1 << 20);
+        let mut out = std::vec![0i16; 11025];
+        game.step();
+        game.mix(&mut out);
+        assert!(out.iter().all(|&s| s == 0));
+        // Звон при старте и гул куба
+        let mut game = playing(64, 64);
+        game.step();
+        game.mix(&mut out);
+        assert!(out[5000..].iter().any(|&s| s != 0));
+        // На паузе гул затухает, сигнал паузы короткий
+        game.tap(Key::SPACE);
+        game.step();
+        game.mix(&mut out);
+        game.mix(&mut out[..1000]);
+        assert!(out[..1000].iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn speed_does_not_depend_on_frame_rate() {
+        // Секунда игры при 30 и при 144 кадрах в секунду
+        let mut slow = playing(64, 64);
+        let mut fast = playing(64, 64);
+        for _ in 0..30 {
+            slow.update(1.0 / 30.0);
+        }
+        for _ in 0..144 {
+            fast.update(1.0 / 144.0);
+        }
+        slow.render();
+        fast.render();
+        let (a, b) = (&slow.surface().pixels()[24 * 64..], &fast.surface().pixels()[24 * 64..]);
+        // Допускаем расхождение на краях граней из-за разной доли интерполяции
+        let differ = a.iter().zip(b).filter(|(x, y)| x != y).count();
+        assert!(differ < a.len() / 50, "{} пикселей отличаются", differ);
+    }
+
+    #[test]
+    fn cube_renders_at_any_resolution() {
+        for &(w, h) in &[(1, 1), (33, 200), (320, 20)] {
+            let mut game = HostedGame::<CubeGame>::new(w, h, 0);
+            game.render();
+            assert_eq!(game.surface().pixels().len(), w * h);
+        }
+    }
+
+    #[test]
+    fn ppm_header_and_size() {
+        let mut surface = Surface::new(3, 2);
+        surface.framebuffer().put_pixel(0, 0, 0xFF102030);
+        let mut out = Vec::new();
+        surface.write_ppm(&mut out).unwrap();
+        let header = b"P6\n3 2\n255\n";
+        assert!(out.starts_with(header));
+        assert_eq!(out.len(), header.len() + 3 * 2 * 3);
+        assert_eq!(&out[header.len()..header.len() + 3], &[0x10, 0x20, 0x30]);
+    }
+}
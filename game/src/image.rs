@@ -0,0 +1,242 @@
+//! Изображения и разбор встроенных файлов без кучи: PPM (P6) и QOI
+//!
+//! Файлы подключаются через `include_bytes!`, пиксели распаковываются в память
+//! из `Arena` или в срез вызывающего.
+
+use crate::arena::Arena;
+
+/// Изображение 0xAARRGGBB, строки без отступов
+#[derive(Clone, Copy)]
+pub struct Image<'a> {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: &'a [u32],
+}
+
+impl<'a> Image<'a> {
+    pub fn new(width: usize, height: usize, pixels: &'a [u32]) -> Self {
+        assert!(pixels.len() >= width * height, "пикселей меньше, чем width * height");
+        Image { width, height, pixels }
+    }
+
+    #[inline]
+    pub fn pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels[y * self.width + x]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    /// Неизвестный или неподдерживаемый формат
+    Format,
+    /// Данные кончились раньше, чем изображение
+    Truncated,
+    /// Буфер для пикселей меньше изображения
+    BufferTooSmall,
+}
+
+/// Наибольшая сторона изображения; защищает от переполнения при битых заголовках
+pub const MAX_SIDE: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Ppm,
+    Qoi,
+}
+
+/// Размеры изображения (ширина, высота) по заголовку файла
+pub fn dimensions(data: &[u8]) -> Result<(usize, usize), ImageError> {
+    header(data).map(|(_, w, h, _)| (w, h))
+}
+
+/// Распаковать файл в `out`; возвращает размеры изображения
+pub fn decode_into(data: &[u8], out: &mut [u32]) -> Result<(usize, usize), ImageError> {
+    let (kind, width, height, body) = header(data)?;
+    if out.len() < width * height {
+        return Err(ImageError::BufferTooSmall);
+    }
+    let out = &mut out[..width * height];
+    match kind {
+        Kind::Ppm => decode_ppm(&data[body..], out)?,
+        Kind::Qoi => decode_qoi(&data[body..], out)?,
+    }
+    Ok((width, height))
+}
+
+/// Распаковать файл в память арены. `None`, если арене не хватило места.
+pub fn load(data: &[u8], arena: &mut Arena) -> Option<Result<Image<'static>, ImageError>> {
+    let (width, height) = match dimensions(data) {
+        Ok(size) => size,
+        Err(e) => return Some(Err(e)),
+    };
+    let pixels = arena.alloc_slice(width * height, 0u32)?;
+    Some(decode_into(data, pixels).map(|_| Image::new(width, height, pixels)))
+}
+
+/// Формат, размеры и смещение начала пиксельных данных
+fn header(data: &[u8]) -> Result<(Kind, usize, usize, usize), ImageError> {
+    let (kind, width, height, body) = if data.starts_with(b"qoif") {
+        if data.len() < 14 {
+            return Err(ImageError::Truncated);
+        }
+        let w = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let h = u32::from_be_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        // data[12] — число каналов, data[13] — цветовое пространство: на распаковку не влияют
+        (Kind::Qoi, w, h, 14)
+    } else if data.starts_with(b"P6") {
+        let mut pos = 2;
+        let w = ppm_number(data, &mut pos)?;
+        let h = ppm_number(data, &mut pos)?;
+        if ppm_number(data, &mut pos)? != 255 {
+            return Err(ImageError::Format);
+        }
+        // После maxval ровно один пробельный символ
+        (Kind::Ppm, w, h, pos + 1)
+    } else {
+        return Err(ImageError::Format);
+    };
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(ImageError::Format);
+    }
+    if body > data.len() {
+        return Err(ImageError::Truncated);
+    }
+    Ok((kind, width, height, body))
+}
+
+/// Прочитать десятичное число заголовка PPM, пропуская пробелы и комментарии
+fn ppm_number(data: &[u8], pos: &mut usize) -> Result<usize, ImageError> {
+    loop {
+        match data.get(*pos) {
+            Some(c) if c.is_ascii_whitespace() => *pos += 1,
+            Some(b'#') => {
+                while !matches!(data.get(*pos), Some(b'\n') | None) {
+                    *pos += 1;
+                }
+            }
+            Some(_) => break,
+            None => return Err(ImageError::Truncated),
+        }
+    }
+    let start = *pos;
+    let mut value = 0usize;
+    while let Some(&c) = data.get(*pos).filter(|c| c.is_ascii_digit()) {
+        value = value.saturating_mul(10).saturating_add((c - b'0') as usize);
+        *pos += 1;
+    }
+    if *pos == start {
+        return Err(ImageError::Format);
+    }
+    Ok(value)
+}
+
+fn decode_ppm(body: &[u8], out: &mut [u32]) -> Result<(), ImageError> {
+    if body.len() < out.len() * 3 {
+        return Err(ImageError::Truncated);
+    }
+    for (px, rgb) in out.iter_mut().zip(body.chunks_exact(3)) {
+        *px = 0xFF00_0000 | (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32;
+    }
+    Ok(())
+}
+
+/// Разбор потока QOI (https://qoiformat.org/qoi-specification.pdf)
+fn decode_qoi(body: &[u8], out: &mut [u32]) -> Result<(), ImageError> {
+    const OP_RGB: u8 = 0xFE;
+    const OP_RGBA: u8 = 0xFF;
+    let mut index = [[0u8; 4]; 64];
+    let mut px = [0u8, 0, 0, 255];
+    let mut pos = 0;
+    let mut i = 0;
+    let byte = |pos: usize| body.get(pos).copied().ok_or(ImageError::Truncated);
+    while i < out.len() {
+        let op = byte(pos)?;
+        pos += 1;
+        let mut run = 1;
+        match op {
+            OP_RGB => {
+                px[..3].copy_from_slice(body.get(pos..pos + 3).ok_or(ImageError::Truncated)?);
+                pos += 3;
+            }
+            OP_RGBA => {
+                px.copy_from_slice(body.get(pos..pos + 4).ok_or(ImageError::Truncated)?);
+                pos += 4;
+            }
+            _ => match op >> 6 {
+                0 => px = index[op as usize],
+                1 => {
+                    px[0] = px[0].wrapping_add((op >> 4) & 3).wrapping_sub(2);
+                    px[1] = px[1].wrapping_add((op >> 2) & 3).wrapping_sub(2);
+                    px[2] = px[2].wrapping_add(op & 3).wrapping_sub(2);
+                }
+                2 => {
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    let next = byte(pos)?;
+                    pos += 1;
+                    px[0] = px[0].wrapping_add(dg.wrapping_sub(8).wrapping_add(next >> 4));
+                    px[1] = px[1].wrapping_add(dg);
+                    px[2] = px[2].wrapping_add(dg.wrapping_sub(8).wrapping_add(next & 0xF));
+                }
+                _ => run = (op & 0x3F) as usize + 1,
+            },
+        }
+        let hash = (px[0] as usize * 3 + px[1] as usize * 5 + px[2] as usize * 7 + px[3] as usize * 11) % 64;
+        index[hash] = px;
+        let argb = (px[3] as u32) << 24 | (px[0] as u32) << 16 | (px[1] as u32) << 8 | px[2] as u32;
+        let end = (i + run).min(out.len());
+        for p in &mut out[i..end] {
+            *p = argb;
+        }
+        i = end;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_ppm_with_comments() {
+        let data = b"P6\n# tiny\n2 1\n255\n\x10\x20\x30\xFF\x00\x80";
+        let mut out = [0u32; 2];
+        assert_eq!(decode_into(data, &mut out), Ok((2, 1)));
+        assert_eq!(out, [0xFF102030, 0xFFFF0080]);
+        assert_eq!(decode_into(&data[..data.len() - 1], &mut out), Err(ImageError::Truncated));
+        assert_eq!(decode_into(b"P6 2 1 65535\n", &mut out), Err(ImageError::Format));
+        assert_eq!(decode_into(data, &mut out[..1]), Err(ImageError::BufferTooSmall));
+    }
+
+    #[test]
+    fn decodes_every_qoi_op() {
+        #[rustfmt::skip]
+        let data = [
+            b'q', b'o', b'i', b'f', 0, 0, 0, 7, 0, 0, 0, 1, 4, 0,
+            0xFE, 100, 50, 25,       // RGB
+            0x40 | 3 << 4 | 1 << 2,  // DIFF: r+1, g-1, b-2
+            0x80 | (32 + 5), 0x8A,   // LUMA: g+5, r+5, b+7
+            0xC0 | 1,                // RUN 2
+            0xFF, 1, 2, 3, 128,      // RGBA
+            10,                      // INDEX: хэш первого пикселя
+            0, 0, 0, 0, 0, 0, 0, 1,  // конец потока
+        ];
+        let mut out = [0u32; 7];
+        assert_eq!(decode_into(&data, &mut out), Ok((7, 1)));
+        assert_eq!(out, [
+            0xFF643219, 0xFF653117, 0xFF6A361E, 0xFF6A361E, 0xFF6A361E, 0x80010203, 0xFF643219,
+        ]);
+        assert_eq!(decode_into(&data[..20], &mut out), Err(ImageError::Truncated));
+    }
+
+    #[test]
+    fn embedded_sprite_sheet_loads() {
+        let data = include_bytes!("../assets/spinner.qoi");
+        let (w, h) = dimensions(data).unwrap();
+        let mut out = [0u32; 64 * 16];
+        assert_eq!(decode_into(data, &mut out), Ok((w, h)));
+        assert_eq!((w, h), (64, 16));
+        // Угол кадра прозрачен, центр закрашен
+        assert_eq!(out[0] >> 24, 0);
+        assert_eq!(out[8 * w + 8] >> 24, 0xFF);
+    }
+}
@@ -0,0 +1,410 @@
+//! Ввод: клавиатура, текст и мышь.
+//!
+//! Ядро читает события из устройства VMM по одному 32-битному слову
+//! (см. [`Event::decode`]) и передаёт их игре; игра видит состояние
+//! на текущий кадр через [`InputState`].
+
+use crate::collections::FixedVec;
+use crate::text::TextBuf;
+
+/// Код клавиши: номер из таблицы USB HID (Keyboard/Keypad page)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key(pub u8);
+
+impl Key {
+    pub const A: Key = Key(0x04);
+    pub const B: Key = Key(0x05);
+    pub const C: Key = Key(0x06);
+    pub const D: Key = Key(0x07);
+    pub const E: Key = Key(0x08);
+    pub const F: Key = Key(0x09);
+    pub const G: Key = Key(0x0A);
+    pub const H: Key = Key(0x0B);
+    pub const I: Key = Key(0x0C);
+    pub const J: Key = Key(0x0D);
+    pub const K: Key = Key(0x0E);
+    pub const L: Key = Key(0x0F);
+    pub const M: Key = Key(0x10);
+    pub const N: Key = Key(0x11);
+    pub const O: Key = Key(0x12);
+    pub const P: Key = Key(0x13);
+    pub const Q: Key = Key(0x14);
+    pub const R: Key = Key(0x15);
+    pub const S: Key = Key(0x16);
+    pub const T: Key = Key(0x17);
+    pub const U: Key = Key(0x18);
+    pub const V: Key = Key(0x19);
+    pub const W: Key = Key(0x1A);
+    pub const X: Key = Key(0x1B);
+    pub const Y: Key = Key(0x1C);
+    pub const Z: Key = Key(0x1D);
+    /// Цифры верхнего ряда: `Key::digit(0)` .. `Key::digit(9)`
+    pub const NUM1: Key = Key(0x1E);
+    pub const NUM0: Key = Key(0x27);
+    pub const ENTER: Key = Key(0x28);
+    pub const ESCAPE: Key = Key(0x29);
+    pub const BACKSPACE: Key = Key(0x2A);
+    pub const TAB: Key = Key(0x2B);
+    pub const SPACE: Key = Key(0x2C);
+    /// Функциональные клавиши: `Key::f(1)` .. `Key::f(12)`
+    pub const F1: Key = Key(0x3A);
+    pub const RIGHT: Key = Key(0x4F);
+    pub const LEFT: Key = Key(0x50);
+    pub const DOWN: Key = Key(0x51);
+    pub const UP: Key = Key(0x52);
+    pub const LEFT_CTRL: Key = Key(0xE0);
+    pub const LEFT_SHIFT: Key = Key(0xE1);
+    pub const LEFT_ALT: Key = Key(0xE2);
+    pub const RIGHT_CTRL: Key = Key(0xE4);
+    pub const RIGHT_SHIFT: Key = Key(0xE5);
+    pub const RIGHT_ALT: Key = Key(0xE6);
+
+    /// Буква латиницы 'a'..='z' (регистр не важен)
+    pub fn letter(c: char) -> Option<Key> {
+        let c = c.to_ascii_lowercase();
+        c.is_ascii_lowercase().then(|| Key(Key::A.0 + (c as u8 - b'a')))
+    }
+
+    pub fn digit(n: u8) -> Key {
+        match n {
+            0 => Key::NUM0,
+            _ => Key(Key::NUM1.0 + (n.min(9) - 1)),
+        }
+    }
+
+    pub fn f(n: u8) -> Key {
+        Key(Key::F1.0 + (n.clamp(1, 12) - 1))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left = 0,
+    Right = 1,
+    Middle = 2,
+}
+
+impl MouseButton {
+    fn from_u8(n: u8) -> Option<MouseButton> {
+        match n {
+            0 => Some(MouseButton::Left),
+            1 => Some(MouseButton::Right),
+            2 => Some(MouseButton::Middle),
+            _ => None,
+        }
+    }
+}
+
+/// Событие ввода
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    KeyDown(Key),
+    KeyUp(Key),
+    /// Введённый символ (с учётом раскладки и регистра)
+    Text(char),
+    /// Положение курсора в пикселях экрана
+    MouseMove { x: i32, y: i32 },
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    /// Прокрутка колеса; положительная — от себя
+    Wheel(i32),
+}
+
+// Вид события в старших 4 битах слова; 0 — событий нет
+const KIND_KEY_DOWN: u32 = 1;
+const KIND_KEY_UP: u32 = 2;
+const KIND_TEXT: u32 = 3;
+const KIND_MOUSE_MOVE: u32 = 4;
+const KIND_MOUSE_DOWN: u32 = 5;
+const KIND_MOUSE_UP: u32 = 6;
+const KIND_WHEEL: u32 = 7;
+/// Координаты мыши занимают по 14 бит
+const MOUSE_MASK: u32 = 0x3FFF;
+
+impl Event {
+    /// Разобрать слово из устройства ввода VMM.
+    ///
+    /// Старшие 4 бита — вид события, остальные 28 — данные:
+    /// код клавиши, символ Unicode, x и y мыши (по 14 бит), номер кнопки
+    /// или прокрутка (i8). `None` для 0 и неизвестных слов.
+    pub fn decode(word: u32) -> Option<Event> {
+        let payload = word & 0x0FFF_FFFF;
+        Some(match word >> 28 {
+            KIND_KEY_DOWN => Event::KeyDown(Key(payload as u8)),
+            KIND_KEY_UP => Event::KeyUp(Key(payload as u8)),
+            KIND_TEXT => Event::Text(char::from_u32(payload)?),
+            KIND_MOUSE_MOVE => Event::MouseMove {
+                x: (payload & MOUSE_MASK) as i32,
+                y: ((payload >> 14) & MOUSE_MASK) as i32,
+            },
+            KIND_MOUSE_DOWN => Event::MouseDown(MouseButton::from_u8(payload as u8)?),
+            KIND_MOUSE_UP => Event::MouseUp(MouseButton::from_u8(payload as u8)?),
+            KIND_WHEEL => Event::Wheel(payload as u8 as i8 as i32),
+            _ => return None,
+        })
+    }
+
+    /// Обратное к `decode`; координаты мыши обрезаются до 0..16383, прокрутка до i8
+    pub fn encode(&self) -> u32 {
+        let (kind, payload) = match *self {
+            Event::KeyDown(key) => (KIND_KEY_DOWN, key.0 as u32),
+            Event::KeyUp(key) => (KIND_KEY_UP, key.0 as u32),
+            Event::Text(c) => (KIND_TEXT, c as u32),
+            Event::MouseMove { x, y } => {
+                let clamp = |v: i32| v.clamp(0, MOUSE_MASK as i32) as u32;
+                (KIND_MOUSE_MOVE, clamp(x) | clamp(y) << 14)
+            }
+            Event::MouseDown(b) => (KIND_MOUSE_DOWN, b as u32),
+            Event::MouseUp(b) => (KIND_MOUSE_UP, b as u32),
+            Event::Wheel(d) => (KIND_WHEEL, d.clamp(-128, 127) as i8 as u8 as u32),
+        };
+        kind << 28 | payload
+    }
+}
+
+/// Сколько событий одного кадра хранится для `events()`; лишние не попадают
+/// в очередь, но состояние клавиш и мыши всё равно обновляют
+pub const MAX_EVENTS: usize = 64;
+
+/// Состояние ввода на текущий кадр
+pub struct InputState {
+    /// Удерживаемые клавиши, по биту на код
+    keys: [u64; 4],
+    /// Нажатые и отпущенные за кадр: короткое нажатие внутри кадра не теряется
+    pressed_keys: [u64; 4],
+    released_keys: [u64; 4],
+    mouse_x: i32,
+    mouse_y: i32,
+    buttons: u8,
+    pressed_buttons: u8,
+    released_buttons: u8,
+    wheel: i32,
+    text: TextBuf<64>,
+    events: FixedVec<Event, MAX_EVENTS>,
+}
+
+impl InputState {
+    pub const fn new() -> Self {
+        InputState {
+            keys: [0; 4],
+            pressed_keys: [0; 4],
+            released_keys: [0; 4],
+            mouse_x: 0,
+            mouse_y: 0,
+            buttons: 0,
+            pressed_buttons: 0,
+            released_buttons: 0,
+            wheel: 0,
+            text: TextBuf::new(),
+            events: FixedVec::new(),
+        }
+    }
+
+    /// Учесть событие
+    pub fn push(&mut self, event: Event) {
+        match event {
+            Event::KeyDown(key) => {
+                // Автоповтор не считается новым нажатием
+                if !self.is_down(key) {
+                    set_bit(&mut self.pressed_keys, key, true);
+                }
+                set_bit(&mut self.keys, key, true);
+            }
+            Event::KeyUp(key) => {
+                if self.is_down(key) {
+                    set_bit(&mut self.released_keys, key, true);
+                }
+                set_bit(&mut self.keys, key, false);
+            }
+            Event::Text(c) => self.text.push_char(c),
+            Event::MouseMove { x, y } => {
+                self.mouse_x = x;
+                self.mouse_y = y;
+            }
+            Event::MouseDown(b) => {
+                self.pressed_buttons |= !self.buttons & 1 << b as u8;
+                self.buttons |= 1 << b as u8;
+            }
+            Event::MouseUp(b) => {
+                self.released_buttons |= self.buttons & 1 << b as u8;
+                self.buttons &= !(1 << b as u8);
+            }
+            Event::Wheel(d) => self.wheel += d,
+        }
+        let _ = self.events.push(event);
+    }
+
+    /// Учесть слово из устройства ввода; неизвестные слова пропускаются
+    pub fn push_raw(&mut self, word: u32) {
+        if let Some(event) = Event::decode(word) {
+            self.push(event);
+        }
+    }
+
+    /// Начать новый кадр: сбросить нажатия, события, текст и прокрутку.
+    /// Удерживаемые клавиши и положение мыши сохраняются.
+    pub fn begin_frame(&mut self) {
+        self.pressed_keys = [0; 4];
+        self.released_keys = [0; 4];
+        self.pressed_buttons = 0;
+        self.released_buttons = 0;
+        self.wheel = 0;
+        self.text.clear();
+        self.events.clear();
+    }
+
+    /// Клавиша удерживается
+    pub fn is_down(&self, key: Key) -> bool {
+        bit(&self.keys, key)
+    }
+
+    /// Клавишу нажали в этом кадре (даже если уже отпустили)
+    pub fn pressed(&self, key: Key) -> bool {
+        bit(&self.pressed_keys, key)
+    }
+
+    /// Клавишу отпустили в этом кадре
+    pub fn released(&self, key: Key) -> bool {
+        bit(&self.released_keys, key)
+    }
+
+    /// Положение курсора в пикселях экрана
+    pub fn mouse(&self) -> (i32, i32) {
+        (self.mouse_x, self.mouse_y)
+    }
+
+    pub fn mouse_down(&self, button: MouseButton) -> bool {
+        self.buttons & (1 << button as u8) != 0
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons & (1 << button as u8) != 0
+    }
+
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.released_buttons & (1 << button as u8) != 0
+    }
+
+    /// Прокрутка колеса за кадр
+    pub fn wheel(&self) -> i32 {
+        self.wheel
+    }
+
+    /// Текст, введённый за кадр
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    /// События кадра в порядке поступления
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+}
+
+fn bit(set: &[u64; 4], Key(k): Key) -> bool {
+    set[k as usize / 64] & (1 << (k % 64)) != 0
+}
+
+fn set_bit(set: &mut [u64; 4], Key(k): Key, value: bool) {
+    if value {
+        set[k as usize / 64] |= 1 << (k % 64);
+    } else {
+        set[k as usize / 64] &= !(1 << (k % 64));
+    }
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_roundtrip_through_words() {
+        let events = [
+            Event::KeyDown(Key::A),
+            Event::KeyUp(Key::RIGHT_ALT),
+            Event::Text('ж'),
+            Event::Text('\u{1F600}'),
+            Event::MouseMove { x: 639, y: 16383 },
+            Event::MouseDown(MouseButton::Middle),
+            Event::MouseUp(MouseButton::Left),
+            Event::Wheel(-3),
+        ];
+        for e in events {
+            assert_eq!(Event::decode(e.encode()), Some(e));
+        }
+        assert_eq!(Event::decode(0), None);
+        assert_eq!(Event::decode(0xF000_0000), None);
+        assert_eq!(Event::decode(5 << 28 | 7), None);
+        assert_eq!(Event::MouseMove { x: -5, y: 20000 }.encode(), 4 << 28 | 16383 << 14);
+    }
+
+    #[test]
+    fn key_helpers() {
+        assert_eq!(Key::letter('Z'), Some(Key::Z));
+        assert_eq!(Key::letter('1'), None);
+        assert_eq!(Key::digit(1), Key::NUM1);
+        assert_eq!(Key::digit(9), Key(0x26));
+        assert_eq!(Key::digit(0), Key::NUM0);
+        assert_eq!(Key::f(12), Key(0x45));
+    }
+
+    #[test]
+    fn tracks_state_across_frames() {
+        let mut input = InputState::new();
+        input.push(Event::KeyDown(Key::SPACE));
+        input.push(Event::KeyDown(Key::RIGHT_SHIFT));
+        input.push(Event::Text('Ы'));
+        input.push(Event::MouseMove { x: 10, y: 20 });
+        input.push(Event::MouseDown(MouseButton::Left));
+        input.push(Event::Wheel(2));
+        assert!(input.pressed(Key::SPACE) && input.is_down(Key::RIGHT_SHIFT));
+        assert!(input.mouse_pressed(MouseButton::Left));
+        assert_eq!(input.mouse(), (10, 20));
+        assert_eq!((input.text(), input.wheel(), input.events().len()), ("Ы", 2, 6));
+
+        input.begin_frame();
+        assert!(input.is_down(Key::SPACE) && !input.pressed(Key::SPACE));
+        assert!(input.mouse_down(MouseButton::Left) && !input.mouse_pressed(MouseButton::Left));
+        assert_eq!((input.text(), input.wheel(), input.events().len()), ("", 0, 0));
+
+        input.push_raw(Event::KeyUp(Key::SPACE).encode());
+        input.push_raw(Event::MouseUp(MouseButton::Left).encode());
+        input.push_raw(0);
+        assert!(input.released(Key::SPACE) && !input.is_down(Key::SPACE));
+        assert!(input.mouse_released(MouseButton::Left));
+        assert_eq!(input.events(), &[Event::KeyUp(Key::SPACE), Event::MouseUp(MouseButton::Left)]);
+        // Положение мыши сохраняется между кадрами
+        assert_eq!(input.mouse(), (10, 20));
+    }
+
+    #[test]
+    fn short_tap_and_autorepeat() {
+        let mut input = InputState::new();
+        input.push(Event::KeyDown(Key::ENTER));
+        input.push(Event::KeyUp(Key::ENTER));
+        assert!(input.pressed(Key::ENTER) && input.released(Key::ENTER) && !input.is_down(Key::ENTER));
+
+        input.begin_frame();
+        input.push(Event::KeyDown(Key::LEFT));
+        input.begin_frame();
+        input.push(Event::KeyDown(Key::LEFT));
+        assert!(input.is_down(Key::LEFT) && !input.pressed(Key::LEFT));
+    }
+
+    #[test]
+    fn queue_overflow_keeps_state() {
+        let mut input = InputState::new();
+        for _ in 0..MAX_EVENTS {
+            input.push(Event::Wheel(1));
+        }
+        input.push(Event::KeyDown(Key::Q));
+        assert_eq!(input.events().len(), MAX_EVENTS);
+        assert!(input.is_down(Key::Q));
+    }
+}
@@ -0,0 +1,692 @@
+//! Векторы, матрицы и кватернионы для no_std.
+//!
+//! Матрицы хранятся по строкам (`m[строка][столбец]`) и умножаются на
+//! вектор-столбец справа: `v' = M * v`. Пространство камеры: x вправо,
+//! y вниз, z вперёд.
+
+use core::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// π/2, разбитое на три части для точного приведения аргумента (Коди — Уэйт)
+const PIO2_HI: f32 = 1.570_312_5;
+const PIO2_MID: f32 = 4.837_513e-4;
+const PIO2_LO: f32 = 7.549_79e-8;
+
+/// Приведение аргумента: x = k * π/2 + r, |r| <= π/4. Возвращает (k mod 4, r).
+///
+/// Точно при |x| до ~1e4; дальше погрешность растёт вместе с аргументом.
+fn reduce(x: f32) -> (u32, f32) {
+    let k = x * core::f32::consts::FRAC_2_PI;
+    let k = if k >= 0.0 { (k + 0.5) as i32 } else { (k - 0.5) as i32 };
+    let kf = k as f32;
+    let r = ((x - kf * PIO2_HI) - kf * PIO2_MID) - kf * PIO2_LO;
+    ((k & 3) as u32, r)
+}
+
+/// Многочлены Cephes на [-π/4, π/4], погрешность около 1 ulp
+fn sin_poly(r: f32) -> f32 {
+    let z = r * r;
+    r + r * z * (-1.666_665_5e-1 + z * (8.332_161e-3 + z * -1.951_529_6e-4))
+}
+
+fn cos_poly(r: f32) -> f32 {
+    let z = r * r;
+    1.0 - 0.5 * z + z * z * (4.166_664_6e-2 + z * (-1.388_731_6e-3 + z * 2.443_315_7e-5))
+}
+
+/// Синус и косинус одного аргумента.
+///
+/// Абсолютная погрешность не больше 2e-7 при |x| <= 1e4 (проверяется тестами).
+pub fn sin_cos(x: f32) -> (f32, f32) {
+    let (q, r) = reduce(x);
+    let (s, c) = (sin_poly(r), cos_poly(r));
+    match q {
+        0 => (s, c),
+        1 => (c, -s),
+        2 => (-s, -c),
+        _ => (-c, s),
+    }
+}
+
+pub fn sin(x: f32) -> f32 {
+    sin_cos(x).0
+}
+
+pub fn cos(x: f32) -> f32 {
+    sin_cos(x).1
+}
+
+/// Арктангенс на [0, +inf) (Cephes atanf), погрешность около 2 ulp
+fn atan_pos(x: f32) -> f32 {
+    use core::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+    let (base, x) = if x > 2.414_213_6 {
+        (FRAC_PI_2, -1.0 / x)
+    } else if x > 0.414_213_57 {
+        (FRAC_PI_4, (x - 1.0) / (x + 1.0))
+    } else {
+        (0.0, x)
+    };
+    let z = x * x;
+    base + (((8.053_744_5e-2 * z - 1.387_768_6e-1) * z + 1.997_771_1e-1) * z - 3.333_295e-1) * z * x + x
+}
+
+/// Угол вектора (x, y) в радианах, -π..=π
+pub fn atan2(y: f32, x: f32) -> f32 {
+    use core::f32::consts::PI;
+    if x == 0.0 && y == 0.0 {
+        return 0.0;
+    }
+    let a = if x == 0.0 {
+        core::f32::consts::FRAC_PI_2
+    } else {
+        let t = atan_pos((y / x).abs());
+        if x > 0.0 { t } else { PI - t }
+    };
+    if y < 0.0 { -a } else { a }
+}
+
+/// Квадратный корень (начальное приближение по битам float + итерации Ньютона)
+pub fn sqrt(x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let mut y = f32::from_bits((x.to_bits() >> 1) + 0x1FC0_0000);
+    for _ in 0..3 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2::new(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn dot(self, o: Vec2) -> f32 {
+        self.x * o.x + self.y * o.y
+    }
+
+    /// z-компонента векторного произведения (удвоенная знаковая площадь)
+    pub fn perp_dot(self, o: Vec2) -> f32 {
+        self.x * o.y - self.y * o.x
+    }
+
+    pub fn length(self) -> f32 {
+        sqrt(self.dot(self))
+    }
+
+    pub fn normalize(self) -> Vec2 {
+        let len = self.length();
+        if len > 0.0 { self * (1.0 / len) } else { self }
+    }
+
+    pub fn lerp(self, o: Vec2, t: f32) -> Vec2 {
+        self + (o - self) * t
+    }
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+    pub const X: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+    pub const Y: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+    pub const Z: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    pub fn dot(self, o: Vec3) -> f32 {
+        self.x * o.x + self.y * o.y + self.z * o.z
+    }
+
+    pub fn cross(self, o: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * o.z - self.z * o.y,
+            self.z * o.x - self.x * o.z,
+            self.x * o.y - self.y * o.x,
+        )
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f32 {
+        sqrt(self.dot(self))
+    }
+
+    pub fn normalize(self) -> Vec3 {
+        let len = self.length();
+        if len > 0.0 { self * (1.0 / len) } else { self }
+    }
+
+    pub fn lerp(self, o: Vec3, t: f32) -> Vec3 {
+        self + (o - self) * t
+    }
+
+    /// Покомпонентное произведение
+    pub fn scale(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x * o.x, self.y * o.y, self.z * o.z)
+    }
+
+    /// Покомпонентный минимум
+    pub fn min(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x.min(o.x), self.y.min(o.y), self.z.min(o.z))
+    }
+
+    /// Покомпонентный максимум
+    pub fn max(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x.max(o.x), self.y.max(o.y), self.z.max(o.z))
+    }
+
+    pub fn abs(self) -> Vec3 {
+        Vec3::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    pub fn to_array(self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+}
+
+impl Vec4 {
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Vec4 { x, y, z, w }
+    }
+
+    pub fn dot(self, o: Vec4) -> f32 {
+        self.x * o.x + self.y * o.y + self.z * o.z + self.w * o.w
+    }
+
+    pub fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    pub fn lerp(self, o: Vec4, t: f32) -> Vec4 {
+        self + (o - self) * t
+    }
+}
+
+macro_rules! impl_vec_ops {
+    ($t:ident { $($f:ident),+ }) => {
+        impl Add for $t {
+            type Output = $t;
+            fn add(self, o: $t) -> $t { $t { $($f: self.$f + o.$f),+ } }
+        }
+        impl Sub for $t {
+            type Output = $t;
+            fn sub(self, o: $t) -> $t { $t { $($f: self.$f - o.$f),+ } }
+        }
+        impl Mul<f32> for $t {
+            type Output = $t;
+            fn mul(self, k: f32) -> $t { $t { $($f: self.$f * k),+ } }
+        }
+        impl Neg for $t {
+            type Output = $t;
+            fn neg(self) -> $t { $t { $($f: -self.$f),+ } }
+        }
+        impl AddAssign for $t {
+            fn add_assign(&mut self, o: $t) { $(self.$f += o.$f;)+ }
+        }
+        impl SubAssign for $t {
+            fn sub_assign(&mut self, o: $t) { $(self.$f -= o.$f;)+ }
+        }
+    };
+}
+
+impl_vec_ops!(Vec2 { x, y });
+impl_vec_ops!(Vec3 { x, y, z });
+impl_vec_ops!(Vec4 { x, y, z, w });
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat3 {
+    pub m: [[f32; 3]; 3],
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3 { m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]] };
+
+    pub fn from_rows(r0: Vec3, r1: Vec3, r2: Vec3) -> Self {
+        Mat3 { m: [[r0.x, r0.y, r0.z], [r1.x, r1.y, r1.z], [r2.x, r2.y, r2.z]] }
+    }
+
+    pub fn transpose(&self) -> Mat3 {
+        let m = &self.m;
+        Mat3 { m: [[m[0][0], m[1][0], m[2][0]], [m[0][1], m[1][1], m[2][1]], [m[0][2], m[1][2], m[2][2]]] }
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Обратная матрица; `None`, если матрица вырождена
+    pub fn inverse(&self) -> Option<Mat3> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let m = &self.m;
+        let k = 1.0 / det;
+        Some(Mat3 {
+            m: [
+                [
+                    (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * k,
+                    (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * k,
+                    (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * k,
+                ],
+                [
+                    (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * k,
+                    (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * k,
+                    (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * k,
+                ],
+                [
+                    (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * k,
+                    (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * k,
+                    (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * k,
+                ],
+            ],
+        })
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+    fn mul(self, o: Mat3) -> Mat3 {
+        let mut r = [[0.0; 3]; 3];
+        for (i, row) in r.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.m[i][k] * o.m[k][j]).sum();
+            }
+        }
+        Mat3 { m: r }
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        m: [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]],
+    };
+
+    pub fn translation(t: Vec3) -> Self {
+        let mut r = Mat4::IDENTITY;
+        r.m[0][3] = t.x;
+        r.m[1][3] = t.y;
+        r.m[2][3] = t.z;
+        r
+    }
+
+    pub fn scaling(s: Vec3) -> Self {
+        let mut r = Mat4::IDENTITY;
+        r.m[0][0] = s.x;
+        r.m[1][1] = s.y;
+        r.m[2][2] = s.z;
+        r
+    }
+
+    pub fn from_mat3(a: Mat3) -> Self {
+        let mut r = Mat4::IDENTITY;
+        for i in 0..3 {
+            r.m[i][..3].copy_from_slice(&a.m[i]);
+        }
+        r
+    }
+
+    pub fn rotation_x(angle: f32) -> Self {
+        let (s, c) = (sin(angle), cos(angle));
+        Mat4::from_mat3(Mat3 { m: [[1.0, 0.0, 0.0], [0.0, c, -s], [0.0, s, c]] })
+    }
+
+    pub fn rotation_y(angle: f32) -> Self {
+        let (s, c) = (sin(angle), cos(angle));
+        Mat4::from_mat3(Mat3 { m: [[c, 0.0, s], [0.0, 1.0, 0.0], [-s, 0.0, c]] })
+    }
+
+    pub fn rotation_z(angle: f32) -> Self {
+        let (s, c) = (sin(angle), cos(angle));
+        Mat4::from_mat3(Mat3 { m: [[c, -s, 0.0], [s, c, 0.0], [0.0, 0.0, 1.0]] })
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        Mat4::from_mat3(q.to_mat3())
+    }
+
+    /// Матрица вида: переводит мир в пространство камеры, смотрящей из `eye` на `target`.
+    ///
+    /// `down` задаёт направление «вниз» на экране (ось y камеры).
+    pub fn look_at(eye: Vec3, target: Vec3, down: Vec3) -> Self {
+        let z = (target - eye).normalize();
+        let x = down.cross(z).normalize();
+        let y = z.cross(x);
+        let rot = Mat4::from_mat3(Mat3::from_rows(x, y, z));
+        rot * Mat4::translation(-eye)
+    }
+
+    /// Перспективная проекция в пространство отсечения.
+    ///
+    /// После деления на w: x, y в -1..=1, z в 0 (near) ..= 1 (far).
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let half = fov_y * 0.5;
+        let f = cos(half) / sin(half);
+        let q = far / (far - near);
+        Mat4 {
+            m: [
+                [f / aspect, 0.0, 0.0, 0.0],
+                [0.0, f, 0.0, 0.0],
+                [0.0, 0.0, q, -q * near],
+                [0.0, 0.0, 1.0, 0.0],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        let mut r = [[0.0; 4]; 4];
+        for (i, row) in r.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = self.m[j][i];
+            }
+        }
+        Mat4 { m: r }
+    }
+
+    /// Верхний левый блок 3x3 (поворот и масштаб)
+    pub fn to_mat3(&self) -> Mat3 {
+        let m = &self.m;
+        Mat3 { m: [[m[0][0], m[0][1], m[0][2]], [m[1][0], m[1][1], m[1][2]], [m[2][0], m[2][1], m[2][2]]] }
+    }
+
+    /// Преобразовать точку (w = 1) без перспективного деления
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        (*self * p.extend(1.0)).truncate()
+    }
+
+    /// Преобразовать направление (w = 0)
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        (*self * v.extend(0.0)).truncate()
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, o: Mat4) -> Mat4 {
+        let mut r = [[0.0; 4]; 4];
+        for (i, row) in r.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..4).map(|k| self.m[i][k] * o.m[k][j]).sum();
+            }
+        }
+        Mat4 { m: r }
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+    fn mul(self, v: Vec4) -> Vec4 {
+        let row = |i: usize| Vec4::new(self.m[i][0], self.m[i][1], self.m[i][2], self.m[i][3]).dot(v);
+        Vec4::new(row(0), row(1), row(2), row(3))
+    }
+}
+
+/// Кватернион поворота `w + xi + yj + zk`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+
+    /// Поворот на `angle` радиан вокруг оси `axis` (ось нормализуется)
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let a = axis.normalize();
+        let (s, c) = (sin(angle * 0.5), cos(angle * 0.5));
+        Quat { x: a.x * s, y: a.y * s, z: a.z * s, w: c }.normalize()
+    }
+
+    pub fn length(self) -> f32 {
+        sqrt(self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w)
+    }
+
+    pub fn normalize(self) -> Quat {
+        let len = self.length();
+        if len == 0.0 {
+            return Quat::IDENTITY;
+        }
+        let k = 1.0 / len;
+        Quat { x: self.x * k, y: self.y * k, z: self.z * k, w: self.w * k }
+    }
+
+    /// Сопряжённый кватернион; для единичного это обратный поворот
+    pub fn conjugate(self) -> Quat {
+        Quat { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    /// Повернуть вектор
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        let u = Vec3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        v + t * self.w + u.cross(t)
+    }
+
+    pub fn to_mat3(self) -> Mat3 {
+        let Quat { x, y, z, w } = self;
+        Mat3 {
+            m: [
+                [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+                [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+                [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+            ],
+        }
+    }
+
+    /// Интерполяция поворотов: нормализованная линейная (nlerp)
+    pub fn nlerp(self, o: Quat, t: f32) -> Quat {
+        // Выбираем короткую дугу
+        let dot = self.x * o.x + self.y * o.y + self.z * o.z + self.w * o.w;
+        let o = if dot < 0.0 { Quat { x: -o.x, y: -o.y, z: -o.z, w: -o.w } } else { o };
+        Quat {
+            x: self.x + (o.x - self.x) * t,
+            y: self.y + (o.y - self.y) * t,
+            z: self.z + (o.z - self.z) * t,
+            w: self.w + (o.w - self.w) * t,
+        }
+        .normalize()
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+    /// Композиция поворотов: сначала `o`, затем `self`
+    fn mul(self, o: Quat) -> Quat {
+        Quat {
+            w: self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            x: self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            y: self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            z: self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: f32 = 1e-4;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < EPS
+    }
+
+    fn close3(a: Vec3, b: Vec3) -> bool {
+        close(a.x, b.x) && close(a.y, b.y) && close(a.z, b.z)
+    }
+
+    #[test]
+    fn sqrt_matches_host() {
+        for &x in &[0.0f32, 1e-4, 0.25, 1.0, 2.0, 10.0, 12345.0] {
+            assert!((sqrt(x) - x.sqrt()).abs() <= 1e-5 * x.sqrt().max(1.0), "sqrt({})", x);
+        }
+    }
+
+    #[test]
+    fn vector_basics() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(-2.0, 0.5, 4.0);
+        assert_eq!(a + b, Vec3::new(-1.0, 2.5, 7.0));
+        assert_eq!(a.dot(b), 11.0);
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert!(close(Vec3::new(3.0, 4.0, 0.0).normalize().length(), 1.0));
+        assert_eq!(Vec2::new(1.0, 0.0).perp_dot(Vec2::new(0.0, 1.0)), 1.0);
+    }
+
+    #[test]
+    fn translate_and_scale() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        assert_eq!(m.transform_point(Vec3::new(1.0, 1.0, 1.0)), Vec3::new(3.0, 4.0, 5.0));
+        assert_eq!(m.transform_vector(Vec3::new(1.0, 1.0, 1.0)), Vec3::new(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn trig_against_libm() {
+        let mut x = -10_000.0f32;
+        while x < 10_000.0 {
+            let (s, c) = sin_cos(x);
+            let xd = x as f64;
+            assert!((s as f64 - xd.sin()).abs() <= 2e-7, "sin({})", x);
+            assert!((c as f64 - xd.cos()).abs() <= 2e-7, "cos({})", x);
+            x += 0.377;
+        }
+        for i in 0..3600 {
+            let a = i as f32 * core::f32::consts::TAU / 3600.0;
+            let (y, x) = (3.0 * a.sin(), 3.0 * a.cos());
+            let want = (y as f64).atan2(x as f64);
+            let got = atan2(y, x) as f64;
+            let diff = (got - want).abs();
+            assert!(diff <= 4e-7 || (diff - core::f64::consts::TAU).abs() <= 4e-7, "atan2({}, {})", y, x);
+        }
+    }
+
+    #[test]
+    fn rotation_y_matches_hand_written() {
+        // cos 0.7 и sin 0.7, посчитанные заранее
+        let (cos, sin) = (0.764_842_2, 0.644_217_7);
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        let expected = Vec3::new(v.x * cos + v.z * sin, v.y, -v.x * sin + v.z * cos);
+        assert!(close3(Mat4::rotation_y(0.7).transform_point(v), expected));
+    }
+
+    #[test]
+    fn rotations_agree_with_quaternion() {
+        let angle = core::f32::consts::FRAC_PI_2;
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        let pairs = [
+            (Mat4::rotation_x(angle), Vec3::X),
+            (Mat4::rotation_y(angle), Vec3::Y),
+            (Mat4::rotation_z(angle), Vec3::Z),
+        ];
+        for (m, axis) in pairs {
+            assert!(close3(m.transform_point(v), Quat::from_axis_angle(axis, angle).rotate(v)));
+        }
+        assert!(close3(Mat4::rotation_y(angle).transform_point(Vec3::X), -Vec3::Z));
+        assert!(close3(Mat4::rotation_z(angle).transform_point(Vec3::X), Vec3::Y));
+    }
+
+    #[test]
+    fn quaternion_matrix_agrees_with_rotate() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, -0.5), 0.8);
+        let v = Vec3::new(0.5, -1.0, 2.0);
+        assert!(close3(Mat4::from_quat(q).transform_point(v), q.rotate(v)));
+        assert!(close(q.rotate(v).length(), v.length()));
+    }
+
+    #[test]
+    fn quaternion_composition() {
+        let a = Quat::from_axis_angle(Vec3::Y, 0.5);
+        let b = Quat::from_axis_angle(Vec3::X, 0.3);
+        let v = Vec3::new(0.5, -1.0, 2.0);
+        assert!(close3((a * b).rotate(v), a.rotate(b.rotate(v))));
+        assert!(close3(a.conjugate().rotate(a.rotate(v)), v));
+    }
+
+    #[test]
+    fn mat3_inverse() {
+        let m = Mat4::rotation_y(0.7).to_mat3() * Mat3::from_rows(Vec3::X * 2.0, Vec3::Y, Vec3::Z * 0.5);
+        let id = m * m.inverse().unwrap();
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!(close(id.m[i][j], if i == j { 1.0 } else { 0.0 }));
+            }
+        }
+    }
+
+    #[test]
+    fn look_at_and_perspective() {
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, -5.0), Vec3::ZERO, Vec3::Y);
+        assert!(close3(view.transform_point(Vec3::ZERO), Vec3::new(0.0, 0.0, 5.0)));
+        assert!(close3(view.transform_point(Vec3::X), Vec3::new(1.0, 0.0, 5.0)));
+        assert!(close3(view.transform_point(Vec3::Y), Vec3::new(0.0, 1.0, 5.0)));
+
+        let (aspect, near, far) = (1.5, 1.0, 10.0);
+        let proj = Mat4::perspective(1.0, aspect, near, far);
+        let f = proj.m[1][1];
+        // Точка на углу ближней плоскости
+        let corner = proj * Vec4::new(aspect / f, 1.0 / f, near, 1.0);
+        assert!(close(corner.x / corner.w, 1.0) && close(corner.y / corner.w, 1.0));
+        assert!(close(corner.z / corner.w, 0.0));
+        let back = proj * Vec4::new(0.0, 0.0, far, 1.0);
+        assert!(close(back.z / back.w, 1.0));
+
+        // Угол обзора 90°: f = 1, угол ближней плоскости лежит на (aspect, 1, 1)
+        let proj = Mat4::perspective(core::f32::consts::FRAC_PI_2, aspect, near, far);
+        assert!(close(proj.m[1][1], 1.0));
+        let corner = proj * Vec4::new(aspect, 1.0, near, 1.0);
+        assert!(close(corner.x / corner.w, 1.0) && close(corner.y / corner.w, 1.0));
+    }
+}
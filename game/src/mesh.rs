@@ -0,0 +1,74 @@
+//! Треугольные сетки. Модели из `assets/*.obj` превращаются в [`Mesh`]
+//! при сборке (см. `build.rs`) и лежат в [`models`].
+
+use crate::collision::Aabb;
+use crate::math::{Vec2, Vec3};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex {
+    pub position: Vec3,
+    /// Единичная нормаль, смотрит наружу
+    pub normal: Vec3,
+    pub uv: Vec2,
+}
+
+/// Ребро для обводки: две вершины и две смежные грани (`u16::MAX`, если грань одна)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Edge {
+    pub vertices: [u16; 2],
+    pub faces: [u16; 2],
+}
+
+/// Сетка из треугольников. Лицевые треугольники обходятся по часовой
+/// стрелке при взгляде снаружи (y вниз, как в пространстве камеры).
+#[derive(Clone, Copy, Debug)]
+pub struct Mesh<'a> {
+    pub vertices: &'a [Vertex],
+    /// Индексы вершин треугольников
+    pub triangles: &'a [[u16; 3]],
+    /// Цвет каждого треугольника (из материала модели)
+    pub colors: &'a [u32],
+    /// Рёбра силуэта и изломов: по ним рисуется контур без диагоналей граней
+    pub edges: &'a [Edge],
+}
+
+impl Mesh<'_> {
+    /// Вершины треугольника `index`
+    pub fn triangle(&self, index: usize) -> [&Vertex; 3] {
+        self.triangles[index].map(|i| &self.vertices[i as usize])
+    }
+
+    /// Охватывающий AABB в координатах модели
+    pub fn bounds(&self) -> Aabb {
+        let first = self.vertices.first().map_or(Vec3::ZERO, |v| v.position);
+        let (min, max) = self.vertices.iter().fold((first, first), |(lo, hi), v| (lo.min(v.position), hi.max(v.position)));
+        Aabb::new(min, max)
+    }
+}
+
+/// Модели, собранные из `assets/*.obj`: `assets/cube.obj` — [`models::CUBE`]
+pub mod models {
+    use super::{Edge, Mesh, Vertex};
+    use crate::math::{Vec2, Vec3};
+
+    include!(concat!(env!("OUT_DIR"), "/meshes.rs"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_is_imported_from_obj() {
+        let cube = &models::CUBE;
+        // 6 квадратных граней по 4 вершины со своей нормалью, по 2 треугольника
+        assert_eq!((cube.vertices.len(), cube.triangles.len(), cube.colors.len()), (24, 12, 12));
+        // Диагонали квадратов не обводятся, остаются 12 рёбер куба
+        assert_eq!(cube.edges.len(), 12);
+        assert!(cube.edges.iter().all(|e| e.faces[1] != u16::MAX));
+        assert_eq!(cube.bounds(), Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)));
+        assert_eq!(&cube.colors[..2], &[0xFFFF4040, 0xFFFF4040]);
+        let [a, b, c] = cube.triangle(0);
+        assert_eq!((a.position, b.uv, c.normal), (Vec3::new(-1.0, -1.0, -1.0), Vec2::new(1.0, 0.0), -Vec3::Z));
+    }
+}
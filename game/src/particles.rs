@@ -0,0 +1,302 @@
+//! Частицы для эффектов: искры, дым, осколки.
+//!
+//! [`Emitter`] рождает частицы в точке экрана — равномерно с заданной частотой
+//! или пачкой, — двигает их с гравитацией и меняет цвет от рождения к смерти.
+//! Частицы лежат в пуле фиксированного размера: когда он полон, новые не рождаются.
+//! Координаты в пикселях, ось y направлена вниз.
+
+use crate::collections::{FixedVec, Handle, Pool};
+use crate::framebuffer::Framebuffer;
+use crate::image::Image;
+use crate::math::{sin_cos, Vec2};
+use crate::sprite::{add, blend};
+use crate::texture::lerp;
+
+/// Как рисуются частицы
+#[derive(Clone, Copy)]
+pub enum Style<'a> {
+    /// Пиксель, смешанный по альфе цвета
+    Point,
+    /// Изображение с центром в частице: цвет текселя умножается на цвет частицы
+    /// и прибавляется к экрану
+    Sprite(Image<'a>),
+    /// Штрих вдоль скорости длиной в путь за столько секунд; альфа гасит цвет к чёрному
+    Streak(f32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// Прожитая доля жизни 0.0..=1.0
+    pub fn life(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+}
+
+/// Источник до `N` частиц
+pub struct Emitter<'a, const N: usize> {
+    particles: Pool<Particle, N>,
+    pub position: Vec2,
+    /// Частиц в секунду; 0 — только пачки [`Emitter::burst`]
+    pub rate: f32,
+    /// Время жизни частицы выбирается случайно в этих пределах, секунды
+    pub lifetime: (f32, f32),
+    /// Начальная скорость, пикс/с
+    pub speed: (f32, f32),
+    /// Направление вылета (рад, 0 — вправо, −π/2 — вверх) и наибольшее отклонение от него
+    pub direction: f32,
+    pub spread: f32,
+    /// Ускорение всех частиц, пикс/с²
+    pub gravity: Vec2,
+    /// Цвет 0xAARRGGBB при рождении и в конце жизни
+    pub colors: (u32, u32),
+    pub style: Style<'a>,
+    /// Дробная часть частиц, которые пора родить
+    pending: f32,
+    seed: u32,
+}
+
+impl<'a, const N: usize> Emitter<'a, N> {
+    /// Белые точки во все стороны, секунду жизни, без гравитации и без потока
+    pub fn new(position: Vec2) -> Self {
+        Emitter {
+            particles: Pool::new(),
+            position,
+            rate: 0.0,
+            lifetime: (1.0, 1.0),
+            speed: (20.0, 40.0),
+            direction: 0.0,
+            spread: core::f32::consts::PI,
+            gravity: Vec2::ZERO,
+            colors: (0xFFFFFFFF, 0x00FFFFFF),
+            style: Style::Point,
+            pending: 0.0,
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate.max(0.0);
+        self
+    }
+
+    pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max.max(min));
+        self
+    }
+
+    pub fn with_speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max.max(min));
+        self
+    }
+
+    pub fn with_direction(mut self, direction: f32, spread: f32) -> Self {
+        self.direction = direction;
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn with_colors(mut self, start: u32, end: u32) -> Self {
+        self.colors = (start, end);
+        self
+    }
+
+    pub fn with_style(mut self, style: Style<'a>) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Другая последовательность случайных чисел (не 0)
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn particles(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter().map(|(_, p)| p)
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+    }
+
+    /// Родить сразу `count` частиц; возвращает, сколько поместилось
+    pub fn burst(&mut self, count: usize) -> usize {
+        (0..count).take_while(|_| self.spawn()).count()
+    }
+
+    /// Состарить и сдвинуть частицы, убрать умершие и родить новые по `rate`
+    pub fn update(&mut self, delta: f32) {
+        let mut dead = FixedVec::<Handle, N>::new();
+        for (handle, p) in self.particles.iter_mut() {
+            p.age += delta;
+            if p.age >= p.lifetime {
+                let _ = dead.push(handle);
+                continue;
+            }
+            p.velocity += self.gravity * delta;
+            p.position += p.velocity * delta;
+        }
+        for &handle in dead.iter() {
+            self.particles.remove(handle);
+        }
+        self.pending += self.rate * delta;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if !self.spawn() {
+                // Пул полон: лишние частицы не копятся до освобождения места
+                self.pending = 0.0;
+            }
+        }
+    }
+
+    pub fn render(&self, fb: &mut Framebuffer<'_>) {
+        for (_, p) in self.particles.iter() {
+            let color = lerp(self.colors.0, self.colors.1, (p.life() * 256.0) as u32);
+            let (x, y) = (p.position.x as i32, p.position.y as i32);
+            match self.style {
+                Style::Point => {
+                    if fb.bounds().contains(x, y) {
+                        let under = fb.get_pixel(x, y);
+                        fb.put_pixel(x, y, blend(under, color));
+                    }
+                }
+                Style::Sprite(image) => draw_glow(fb, x, y, &image, color),
+                Style::Streak(length) => {
+                    let tail = p.position - p.velocity * length;
+                    fb.draw_line(tail.x as i32, tail.y as i32, x, y, blend(0xFF000000, color));
+                }
+            }
+        }
+    }
+
+    fn spawn(&mut self) -> bool {
+        if self.particles.is_full() {
+            return false;
+        }
+        let angle = self.direction + self.spread * (self.random() * 2.0 - 1.0);
+        let speed = self.speed.0 + (self.speed.1 - self.speed.0) * self.random();
+        let lifetime = self.lifetime.0 + (self.lifetime.1 - self.lifetime.0) * self.random();
+        let (sin, cos) = sin_cos(angle);
+        let particle = Particle {
+            position: self.position,
+            velocity: Vec2::new(cos, sin) * speed,
+            age: 0.0,
+            lifetime: lifetime.max(f32::EPSILON),
+        };
+        self.particles.insert(particle).is_ok()
+    }
+
+    /// Случайное число 0.0..1.0 (xorshift32)
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+/// Прибавить изображение, окрашенное в `color`, с центром в (x, y)
+fn draw_glow(fb: &mut Framebuffer<'_>, x: i32, y: i32, image: &Image<'_>, color: u32) {
+    let (left, top) = (x - image.width as i32 / 2, y - image.height as i32 / 2);
+    for iy in 0..image.height {
+        for ix in 0..image.width {
+            let (px, py) = (left + ix as i32, top + iy as i32);
+            if !fb.bounds().contains(px, py) {
+                continue;
+            }
+            let texel = image.pixel(ix, iy);
+            let channel = |shift: u32| (((texel >> shift) & 0xFF) * (((color >> shift) & 0xFF) + 1)) >> 8 << shift;
+            let tinted = channel(24) | channel(16) | channel(8) | channel(0);
+            let under = fb.get_pixel(px, py);
+            fb.put_pixel(px, py, add(under, tinted));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particles_fall_fade_and_die() {
+        let mut sparks = Emitter::<8>::new(Vec2::new(8.0, 8.0))
+            .with_speed(0.0, 0.0)
+            .with_lifetime(1.0, 1.0)
+            .with_gravity(Vec2::new(0.0, 10.0))
+            .with_colors(0xFFFF0000, 0x00FF0000);
+        // Пул на 8 частиц: из десяти поместилось восемь
+        assert_eq!(sparks.burst(10), 8);
+        sparks.clear();
+        sparks.burst(1);
+        for _ in 0..5 {
+            sparks.update(0.1);
+        }
+        let p = sparks.particles().next().unwrap();
+        assert!(p.position.y > 9.0 && p.position.x == 8.0);
+        assert!((p.life() - 0.5).abs() < 1e-4);
+        // На середине жизни точка наполовину прозрачна
+        let (mut px, mut depth) = ([0xFF000000u32; 256], [0f32; 256]);
+        let mut fb = Framebuffer::from_slice(&mut px, 16, 16, &mut depth);
+        sparks.render(&mut fb);
+        let lit: std::vec::Vec<u32> = px.iter().copied().filter(|&c| c != 0xFF000000).collect();
+        assert_eq!(lit.len(), 1);
+        assert!(lit[0] >> 16 & 0xFF > 0x70 && lit[0] >> 16 & 0xFF < 0x90);
+        for _ in 0..6 {
+            sparks.update(0.1);
+        }
+        assert!(sparks.is_empty());
+    }
+
+    #[test]
+    fn rate_streaks_and_glow() {
+        let mut jet = Emitter::<64>::new(Vec2::new(2.0, 8.0))
+            .with_rate(100.0)
+            .with_speed(50.0, 50.0)
+            .with_direction(0.0, 0.0)
+            .with_style(Style::Streak(0.04));
+        // Сто частиц в секунду — пять за 0.05 с, по прямой вправо
+        jet.update(0.05);
+        assert_eq!(jet.len(), 5);
+        jet.update(0.02);
+        assert_eq!(jet.len(), 7);
+        let (mut px, mut depth) = ([0xFF000000u32; 256], [0f32; 256]);
+        let mut fb = Framebuffer::from_slice(&mut px, 16, 16, &mut depth);
+        jet.render(&mut fb);
+        // Частица, прожившая 0.02 с, сдвинулась на пиксель и тянет штрих назад
+        assert_ne!(px[8 * 16 + 3], 0xFF000000);
+        assert!(px[..8 * 16].iter().all(|&c| c == 0xFF000000));
+
+        let dot = [0xFFFFFFFFu32];
+        let mut glow = Emitter::<4>::new(Vec2::new(4.0, 4.0))
+            .with_speed(0.0, 0.0)
+            .with_colors(0x40FF0000, 0x40FF0000)
+            .with_style(Style::Sprite(Image::new(1, 1, &dot)));
+        glow.burst(2);
+        let mut fb = Framebuffer::from_slice(&mut px, 16, 16, &mut depth);
+        fb.clear(0xFF000000);
+        glow.render(&mut fb);
+        // Две полупрозрачные красные частицы в одной точке складываются
+        assert_eq!(px[4 * 16 + 4], 0xFF800000);
+    }
+}
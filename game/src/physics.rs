@@ -0,0 +1,250 @@
+//! Простая физика твёрдых тел: гравитация, движение без проскакивания сквозь
+//! неподвижные препятствия и разрешение столкновений импульсами.
+//!
+//! Вращение тел не моделируется: коробки всегда стоят вдоль осей. `step`
+//! вызывается из `Game::update` с его `delta`, так что шаг всегда одинаковый.
+
+use crate::collision::{aabb_aabb, sphere_aabb, sphere_sphere, sweep_aabb, sweep_sphere, Aabb, Contact, Hit, Sphere};
+use crate::math::Vec3;
+
+/// На сколько перекрытие гасится за шаг; остаток убирается на следующих
+const CORRECTION: f32 = 0.8;
+/// Допустимое перекрытие: без него лежащие друг на друге тела дрожат
+const SLOP: f32 = 0.001;
+/// Сколько раз за шаг тело может упереться и продолжить путь вдоль поверхности
+const MAX_SLIDES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Sphere { radius: f32 },
+    /// Коробка вдоль осей с половинами сторон `half`
+    Box { half: Vec3 },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RigidBody {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub shape: Shape,
+    /// Упругость 0.0 (удар гасит скорость) ..= 1.0 (скорость сохраняется)
+    pub restitution: f32,
+    /// 1 / масса; 0 у неподвижных тел
+    inv_mass: f32,
+}
+
+impl RigidBody {
+    /// Тело массы `mass`; масса 0 — неподвижное препятствие (стена, пол)
+    pub fn new(position: Vec3, shape: Shape, mass: f32) -> Self {
+        let inv_mass = if mass > 0.0 { 1.0 / mass } else { 0.0 };
+        RigidBody { position, velocity: Vec3::ZERO, shape, restitution: 0.5, inv_mass }
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.inv_mass == 0.0
+    }
+
+    pub fn inv_mass(&self) -> f32 {
+        self.inv_mass
+    }
+
+    /// Мгновенно изменить импульс; на неподвижные тела не действует
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.velocity += impulse * self.inv_mass;
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        match self.shape {
+            Shape::Sphere { radius } => Sphere::new(self.position, radius).bounds(),
+            Shape::Box { half } => Aabb::from_center(self.position, half),
+        }
+    }
+}
+
+/// Пересечение тел; нормаль от `a` к `b`
+pub fn contact(a: &RigidBody, b: &RigidBody) -> Option<Contact> {
+    match (a.shape, b.shape) {
+        (Shape::Sphere { radius: ra }, Shape::Sphere { radius: rb }) => {
+            sphere_sphere(&Sphere::new(a.position, ra), &Sphere::new(b.position, rb))
+        }
+        (Shape::Sphere { radius }, Shape::Box { .. }) => sphere_aabb(&Sphere::new(a.position, radius), &b.bounds()),
+        (Shape::Box { .. }, Shape::Sphere { radius }) => {
+            sphere_aabb(&Sphere::new(b.position, radius), &a.bounds()).map(Contact::flip)
+        }
+        (Shape::Box { .. }, Shape::Box { .. }) => aabb_aabb(&a.bounds(), &b.bounds()),
+    }
+}
+
+/// Развести пересёкшиеся тела: импульс по нормали и сдвиг из перекрытия
+pub fn resolve(a: &mut RigidBody, b: &mut RigidBody, c: Contact) {
+    let inv_sum = a.inv_mass + b.inv_mass;
+    if inv_sum == 0.0 {
+        return;
+    }
+    // Скорость сближения вдоль нормали; если тела уже расходятся, импульс не нужен
+    let closing = (b.velocity - a.velocity).dot(c.normal);
+    if closing < 0.0 {
+        let e = a.restitution.max(b.restitution);
+        let j = -(1.0 + e) * closing / inv_sum;
+        a.apply_impulse(c.normal * -j);
+        b.apply_impulse(c.normal * j);
+    }
+    let shift = c.normal * ((c.depth - SLOP).max(0.0) * CORRECTION / inv_sum);
+    a.position -= shift * a.inv_mass;
+    b.position += shift * b.inv_mass;
+}
+
+/// Первое касание подвижного тела `body` с неподвижным `obstacle` при смещении на `motion`
+fn sweep(body: &RigidBody, motion: Vec3, obstacle: &RigidBody) -> Option<Hit> {
+    match (body.shape, obstacle.shape) {
+        (Shape::Sphere { radius: ra }, Shape::Sphere { radius: rb }) => {
+            sweep_sphere(&Sphere::new(body.position, ra), motion, &Sphere::new(obstacle.position, rb))
+        }
+        // Сфера против коробки считается по охватывающему кубу: у рёбер касание чуть раньше
+        _ => sweep_aabb(&body.bounds(), motion, &obstacle.bounds()),
+    }
+}
+
+/// Шаг симуляции длиной `delta` секунд.
+///
+/// Подвижные тела двигаются до первого касания с неподвижными (быстрое тело
+/// не проскочит тонкую стену) и отскакивают от них, а остаток пути скользит
+/// вдоль поверхности; затем попарно разрешаются пересечения подвижных тел между собой.
+pub fn step(bodies: &mut [RigidBody], gravity: Vec3, delta: f32) {
+    for i in 0..bodies.len() {
+        if bodies[i].is_static() {
+            continue;
+        }
+        bodies[i].velocity += gravity * delta;
+        let mut motion = bodies[i].velocity * delta;
+        for _ in 0..MAX_SLIDES {
+            let mut first: Option<Hit> = None;
+            for (j, obstacle) in bodies.iter().enumerate() {
+                if j == i || !obstacle.is_static() {
+                    continue;
+                }
+                if let Some(hit) = sweep(&bodies[i], motion, obstacle) {
+                    if first.is_none_or(|f| hit.time < f.time) {
+                        first = Some(hit);
+                    }
+                }
+            }
+            let body = &mut bodies[i];
+            let Some(hit) = first else {
+                body.position += motion;
+                break;
+            };
+            body.position += motion * hit.time;
+            // Отражение от неподвижной поверхности
+            let vn = body.velocity.dot(hit.normal);
+            if vn < 0.0 {
+                body.velocity -= hit.normal * ((1.0 + body.restitution) * vn);
+            }
+            // Остаток пути без составляющей вдоль нормали: тело скользит по полу
+            let rest = motion * (1.0 - hit.time);
+            motion = rest - hit.normal * rest.dot(hit.normal);
+        }
+    }
+    for i in 0..bodies.len() {
+        let (head, tail) = bodies.split_at_mut(i + 1);
+        let a = &mut head[i];
+        for b in tail.iter_mut() {
+            if a.is_static() && b.is_static() {
+                continue;
+            }
+            if let Some(c) = contact(a, b) {
+                resolve(a, b, c);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAVITY: Vec3 = Vec3::new(0.0, 9.8, 0.0);
+
+    fn ball(x: f32, y: f32) -> RigidBody {
+        RigidBody::new(Vec3::new(x, y, 0.0), Shape::Sphere { radius: 0.5 }, 1.0)
+    }
+
+    /// Ось y смотрит вниз, как на экране: верх пола на y = 4.5
+    fn floor() -> RigidBody {
+        RigidBody::new(Vec3::new(0.0, 5.0, 0.0), Shape::Box { half: Vec3::new(10.0, 0.5, 10.0) }, 0.0)
+    }
+
+    #[test]
+    fn ball_bounces_on_floor_and_settles() {
+        let mut bodies = [floor(), ball(0.0, 0.0).with_restitution(0.5)];
+        let mut bounced = false;
+        for _ in 0..600 {
+            step(&mut bodies, GRAVITY, 1.0 / 60.0);
+            bounced |= bodies[1].velocity.y < -1.0;
+            assert!(bodies[1].position.y <= 4.0 + 0.05, "мяч провалился: {:?}", bodies[1].position);
+        }
+        assert!(bounced);
+        assert!((bodies[1].position.y - 4.0).abs() < 0.05);
+        assert!(bodies[1].velocity.y.abs() < 0.5);
+        assert_eq!(bodies[0].position, Vec3::new(0.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn fast_body_does_not_tunnel() {
+        let wall = RigidBody::new(Vec3::new(5.0, 0.0, 0.0), Shape::Box { half: Vec3::new(0.05, 5.0, 5.0) }, 0.0);
+        let bullet = ball(0.0, 0.0).with_velocity(Vec3::new(600.0, 0.0, 0.0)).with_restitution(1.0);
+        let mut bodies = [wall, bullet];
+        step(&mut bodies, Vec3::ZERO, 1.0 / 60.0);
+        assert!(bodies[1].position.x <= 4.5 + 1e-3, "{:?}", bodies[1].position);
+        assert_eq!(bodies[1].velocity, Vec3::new(-600.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn equal_balls_exchange_velocity() {
+        let a = ball(0.0, 0.0).with_velocity(Vec3::new(2.0, 0.0, 0.0)).with_restitution(1.0);
+        let b = ball(0.95, 0.0).with_restitution(1.0);
+        let mut bodies = [a, b];
+        step(&mut bodies, Vec3::ZERO, 1.0 / 60.0);
+        assert!(bodies[0].velocity.x.abs() < 1e-5);
+        assert!((bodies[1].velocity.x - 2.0).abs() < 1e-5);
+        assert!(bodies[1].position.x - bodies[0].position.x > 0.95);
+    }
+
+    #[test]
+    fn resting_ball_jumps_off_the_floor() {
+        let mut bodies = [floor(), ball(0.0, 4.0).with_restitution(0.0)];
+        for _ in 0..60 {
+            step(&mut bodies, GRAVITY, 1.0 / 60.0);
+        }
+        let rest = bodies[1].position;
+        bodies[1].velocity = Vec3::new(3.0, -5.0, 0.0);
+        for _ in 0..10 {
+            step(&mut bodies, GRAVITY, 1.0 / 60.0);
+        }
+        // За 1/6 с: вправо на 0.5, вверх на 5/6 - g/72
+        let moved = bodies[1].position - rest;
+        assert!((moved.x - 0.5).abs() < 1e-3, "{:?}", moved);
+        assert!((moved.y + 5.0 / 6.0 - 9.8 / 72.0).abs() < 0.02, "{:?}", moved);
+    }
+
+    #[test]
+    fn box_slides_along_the_floor() {
+        let crate_box = RigidBody::new(Vec3::new(0.0, 4.0, 0.0), Shape::Box { half: Vec3::new(0.5, 0.5, 0.5) }, 1.0);
+        let mut bodies = [floor(), crate_box.with_velocity(Vec3::new(3.0, 0.0, 0.0)).with_restitution(0.0)];
+        for _ in 0..30 {
+            step(&mut bodies, GRAVITY, 1.0 / 60.0);
+        }
+        // Трения нет: за полсекунды коробка проезжает 1.5 и остаётся на полу
+        assert!((bodies[1].position.x - 1.5).abs() < 1e-3, "{:?}", bodies[1].position);
+        assert!((bodies[1].position.y - 4.0).abs() < 0.01, "{:?}", bodies[1].position);
+    }
+}
@@ -0,0 +1,193 @@
+//! Профилировщик кадра: именованные участки кода, замеренные по счётчику тактов.
+//!
+//! `profile_scope!("render")` засекает RDTSC и при выходе из блока записывает
+//! участок в кольцевой буфер, который выделил хост. Ядро после каждого кадра
+//! сообщает VMM, сколько записей накопилось, и тот выгружает их в формате
+//! Chrome trace (vmm/src/profile.rs). Пока буфер не подключён через [`install`],
+//! участки ничего не пишут.
+//!
+//! Кольцо одно на всю программу: ядро и игры слинкованы в один образ и пишут
+//! в него из одного потока.
+
+use core::sync::atomic::{fence, AtomicPtr, AtomicU32, Ordering};
+
+/// Кольцевой буфер профилировщика, который выделяет хост. Передаётся ядру в `BootInfo`.
+///
+/// В памяти по адресу `base` лежат два счётчика u32 — сколько записей с начала
+/// работы сделал гость и сколько забрал хост (оба по модулю 2^32), — а за ними
+/// `capacity` записей [`Record`]. Раскладка совпадает с vmm/src/profile.rs.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ProfileInfo {
+    /// Физический адрес буфера; 0 — профилировщика нет
+    pub base: u64,
+    pub capacity: u32,
+    pub _reserved: u32,
+}
+
+impl ProfileInfo {
+    /// Профилировщика нет
+    pub const NONE: ProfileInfo = ProfileInfo { base: 0, capacity: 0, _reserved: 0 };
+}
+
+/// Один замеренный участок
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Значение RDTSC на входе
+    pub start: u64,
+    /// Адрес имени (UTF-8) в памяти гостя
+    pub name: u64,
+    /// Длительность в тактах; длинные участки обрезаются до `u32::MAX`
+    pub duration: u32,
+    pub name_len: u32,
+}
+
+/// Запись в кольцо хоста
+pub struct Ring {
+    written: *mut u32,
+    records: *mut Record,
+    capacity: u32,
+}
+
+impl Ring {
+    /// `None`, если хост не дал буфер.
+    ///
+    /// # Safety
+    /// Если `info.base` не 0, он должен быть выровнен на 8 и указывать на доступную
+    /// для записи память размером 8 + 24 * `info.capacity` байт, которая живёт всё время работы.
+    pub unsafe fn new(info: &ProfileInfo) -> Option<Self> {
+        if info.base == 0 || info.capacity == 0 {
+            return None;
+        }
+        let base = info.base as *mut u32;
+        Some(Ring { written: base, records: base.add(2).cast(), capacity: info.capacity })
+    }
+
+    /// Сколько записей сделано с начала работы (по модулю 2^32)
+    pub fn written(&self) -> u32 {
+        unsafe { self.written.read_volatile() }
+    }
+
+    /// Записать участок. Если хост не успевает забирать записи, старые затираются.
+    pub fn record(&self, name: &'static str, start: u64, end: u64) {
+        let written = self.written();
+        let record = Record {
+            start,
+            name: name.as_ptr() as u64,
+            duration: end.saturating_sub(start).min(u32::MAX as u64) as u32,
+            name_len: name.len() as u32,
+        };
+        unsafe {
+            self.records.add((written % self.capacity) as usize).write_volatile(record);
+            // Хост должен увидеть запись раньше нового счётчика
+            fence(Ordering::Release);
+            self.written.write_volatile(written.wrapping_add(1));
+        }
+    }
+}
+
+static RING_BASE: AtomicPtr<u32> = AtomicPtr::new(core::ptr::null_mut());
+static RING_CAPACITY: AtomicU32 = AtomicU32::new(0);
+
+/// Подключить кольцо хоста; до этого участки не записываются.
+///
+/// # Safety
+/// Те же требования к `info`, что у [`Ring::new`].
+pub unsafe fn install(info: &ProfileInfo) {
+    if Ring::new(info).is_some() {
+        RING_CAPACITY.store(info.capacity, Ordering::Relaxed);
+        RING_BASE.store(info.base as *mut u32, Ordering::Release);
+    }
+}
+
+/// Подключённое кольцо
+fn ring() -> Option<Ring> {
+    let base = RING_BASE.load(Ordering::Acquire);
+    let info = ProfileInfo { base: base as u64, capacity: RING_CAPACITY.load(Ordering::Relaxed), _reserved: 0 };
+    // Адрес пришёл из `install`
+    unsafe { Ring::new(&info) }
+}
+
+/// Сколько записей сделано с начала работы; 0, если кольца нет
+pub fn written() -> u32 {
+    ring().map_or(0, |ring| ring.written())
+}
+
+/// Текущее значение счётчика тактов
+#[cfg(target_arch = "x86_64")]
+pub fn now() -> u64 {
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub fn now() -> u64 {
+    0
+}
+
+/// Замер участка: начинается при создании, записывается при уничтожении.
+/// Обычно создаётся макросом [`profile_scope!`].
+pub struct Scope {
+    name: &'static str,
+    start: u64,
+}
+
+impl Scope {
+    pub fn new(name: &'static str) -> Self {
+        Scope { name, start: now() }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if let Some(ring) = ring() {
+            ring.record(self.name, self.start, now());
+        }
+    }
+}
+
+/// Замерить остаток блока: `profile_scope!("render");`
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profile::Scope::new($name);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(memory: &mut [u64]) -> Ring {
+        let info = ProfileInfo { base: memory.as_mut_ptr() as u64, capacity: 2, _reserved: 0 };
+        unsafe { Ring::new(&info) }.unwrap()
+    }
+
+    #[test]
+    fn records_land_after_the_counters() {
+        // Счётчики и две записи по 24 байта
+        let mut memory = [0u64; 7];
+        let ring = ring(&mut memory);
+        let name = "render";
+        ring.record(name, 1000, 1250);
+        assert_eq!(ring.written(), 1);
+        assert_eq!(memory[0], 1);
+        assert_eq!((memory[1], memory[2]), (1000, name.as_ptr() as u64));
+        assert_eq!(memory[3], 250 | (6 << 32));
+        // Часы не идут назад: длительность не бывает отрицательной
+        ring.record(name, 10, 5);
+        assert_eq!(memory[6] as u32, 0);
+    }
+
+    #[test]
+    fn full_ring_overwrites_oldest_records() {
+        let mut memory = [0u64; 7];
+        let ring = ring(&mut memory);
+        for start in 1..=3 {
+            ring.record("step", start, start + 1);
+        }
+        assert_eq!(ring.written(), 3);
+        // Третья запись легла на место первой
+        assert_eq!((memory[1], memory[4]), (3, 2));
+    }
+}
@@ -0,0 +1,416 @@
+//! Рисование трёхмерной геометрии через камеру, освещение
+
+use crate::camera::{Camera, MAX_CLIPPED};
+use crate::collections::FixedVec;
+use crate::fixed::Fixed;
+use crate::framebuffer::{Framebuffer, TexVertex};
+use crate::math::{Mat4, Vec2, Vec3};
+use crate::mesh::Mesh;
+use crate::texture::Texture;
+
+/// Нарисовать отрезок, заданный в пространстве камеры, с отсечением
+pub fn draw_line_3d(fb: &mut Framebuffer<'_>, camera: &Camera, a: Vec3, b: Vec3, color: u32) {
+    if let Some((a, b)) = camera.clip_line(a, b) {
+        let (w, h) = (fb.width(), fb.height());
+        let (x0, y0, _) = camera.project(a, w, h);
+        let (x1, y1, _) = camera.project(b, w, h);
+        fb.draw_line(x0, y0, x1, y1, color);
+    }
+}
+
+/// Залить треугольник, заданный в пространстве камеры, с отсечением
+pub fn fill_triangle_3d(fb: &mut Framebuffer<'_>, camera: &Camera, a: Vec3, b: Vec3, c: Vec3, color: u32) {
+    let mut poly = [Vec3::ZERO; MAX_CLIPPED];
+    let n = camera.clip_triangle(&[a, b, c], &mut poly);
+    if n < 3 {
+        return;
+    }
+    let (w, h) = (fb.width(), fb.height());
+    let first = camera.project(poly[0], w, h);
+    for i in 1..n - 1 {
+        fb.fill_triangle(first, camera.project(poly[i], w, h), camera.project(poly[i + 1], w, h), color);
+    }
+}
+
+/// Залить треугольник с цветами вершин, заданный в пространстве камеры, с отсечением.
+///
+/// Цвета новых вершин, появившихся при отсечении, смешиваются из исходных.
+pub fn fill_triangle_gouraud_3d(fb: &mut Framebuffer<'_>, camera: &Camera, tri: [Vec3; 3], colors: [u32; 3]) {
+    let mut poly = [Vec3::ZERO; MAX_CLIPPED];
+    let n = camera.clip_triangle(&tri, &mut poly);
+    if n < 3 {
+        return;
+    }
+    let (w, h) = (fb.width(), fb.height());
+    let vertex = |p: Vec3| (camera.project(p, w, h), mix(colors, barycentric(p, tri)));
+    let (first, c0) = vertex(poly[0]);
+    for i in 1..n - 1 {
+        let (v1, c1) = vertex(poly[i]);
+        let (v2, c2) = vertex(poly[i + 1]);
+        fb.fill_triangle_gouraud(first, v1, v2, [c0, c1, c2]);
+    }
+}
+
+/// Залить треугольник текстурой, заданный в пространстве камеры, с отсечением.
+///
+/// `uv` — координаты текстуры в вершинах; у вершин, появившихся при отсечении,
+/// они смешиваются из исходных. Тексели умножаются на `tint`.
+pub fn fill_triangle_textured_3d(
+    fb: &mut Framebuffer<'_>,
+    camera: &Camera,
+    tri: [Vec3; 3],
+    uv: [Vec2; 3],
+    texture: &Texture<'_>,
+    tint: u32,
+) {
+    let mut poly = [Vec3::ZERO; MAX_CLIPPED];
+    let n = camera.clip_triangle(&tri, &mut poly);
+    if n < 3 {
+        return;
+    }
+    let (w, h) = (fb.width(), fb.height());
+    let vertex = |p: Vec3| {
+        let (x, y, depth) = camera.project(p, w, h);
+        let [a, b, c] = barycentric(p, tri);
+        // После отсечения по ближней плоскости z > 0
+        TexVertex { x, y, depth, inv_z: 1.0 / p.z, uv: uv[0] * a + uv[1] * b + uv[2] * c }
+    };
+    let first = vertex(poly[0]);
+    for i in 1..n - 1 {
+        fb.fill_triangle_textured([first, vertex(poly[i]), vertex(poly[i + 1])], texture, tint);
+    }
+}
+
+/// Барицентрические координаты точки `p` в плоскости треугольника
+fn barycentric(p: Vec3, [a, b, c]: [Vec3; 3]) -> [f32; 3] {
+    let (e0, e1, ep) = (b - a, c - a, p - a);
+    let (d00, d01, d11) = (e0.dot(e0), e0.dot(e1), e1.dot(e1));
+    let (dp0, dp1) = (ep.dot(e0), ep.dot(e1));
+    let denom = d00 * d11 - d01 * d01;
+    if denom == 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    let v = (d11 * dp0 - d01 * dp1) / denom;
+    let w = (d00 * dp1 - d01 * dp0) / denom;
+    [1.0 - v - w, v, w]
+}
+
+/// Смешать три цвета с весами
+fn mix(colors: [u32; 3], weights: [f32; 3]) -> u32 {
+    let channel = |shift: u32| {
+        let sum: f32 = (0..3).map(|i| ((colors[i] >> shift) & 0xFF) as f32 * weights[i]).sum();
+        (sum + 0.5).clamp(0.0, 255.0) as u32
+    };
+    0xFF00_0000 | channel(16) << 16 | channel(8) << 8 | channel(0)
+}
+
+/// Нарисовать сетку, расположенную в мире матрицей `model`.
+///
+/// Грани, повёрнутые от зрителя, отбрасываются; яркость грани — по квадрату
+/// косинуса между её нормалью и осью взгляда. Если задан `outline`, этим цветом
+/// обводятся рёбра видимых граней (без диагоналей, см. [`Mesh::edges`]).
+pub fn draw_mesh(fb: &mut Framebuffer<'_>, camera: &Camera, mesh: &Mesh, model: &Mat4, outline: Option<u32>) {
+    let view = |i: u16| camera.to_view(model.transform_point(mesh.vertices[i as usize].position));
+    // Нормаль лицевого треугольника в пространстве камеры; `None`, если он повёрнут от зрителя
+    let front = |t: usize| {
+        let [a, b, c] = mesh.triangles[t].map(view);
+        // При обходе по часовой стрелке нормаль смотрит внутрь: грань лицевая, если она смотрит от зрителя
+        let n = (b - a).cross(c - a);
+        (n.dot(a) > 0.0).then_some((n, [a, b, c]))
+    };
+    for t in 0..mesh.triangles.len() {
+        let Some((n, [a, b, c])) = front(t) else {
+            continue;
+        };
+        let len2 = n.length_squared();
+        let facing = if len2 > 0.0 { n.z * n.z / len2 } else { 0.0 };
+        fill_triangle_3d(fb, camera, a, b, c, shade(mesh.colors[t], 0.2 + 0.8 * facing));
+    }
+    if let Some(color) = outline {
+        draw_mesh_outline(fb, camera, mesh, model, color);
+    }
+}
+
+/// Обвести рёбра видимых граней сетки (без диагоналей, см. [`Mesh::edges`])
+pub fn draw_mesh_outline(fb: &mut Framebuffer<'_>, camera: &Camera, mesh: &Mesh, model: &Mat4, color: u32) {
+    let view = |i: u16| camera.to_view(model.transform_point(mesh.vertices[i as usize].position));
+    let visible = |f: u16| {
+        if f == u16::MAX {
+            return false;
+        }
+        let [a, b, c] = mesh.triangles[f as usize].map(view);
+        (b - a).cross(c - a).dot(a) > 0.0
+    };
+    for edge in mesh.edges {
+        if visible(edge.faces[0]) || visible(edge.faces[1]) {
+            draw_line_3d(fb, camera, view(edge.vertices[0]), view(edge.vertices[1]), color);
+        }
+    }
+}
+
+/// Как освещается сетка
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shading {
+    /// Один цвет на треугольник: свет считается в центре грани по её нормали
+    Flat,
+    /// Свет считается в вершинах по их нормалям, цвет плавно меняется по треугольнику
+    Gouraud,
+}
+
+/// Источник света. Цвета — 0xRRGGBB, старший байт не используется.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Light {
+    /// Бесконечно далёкий источник (солнце); `direction` — куда летят лучи
+    Directional { direction: Vec3, color: u32 },
+    /// Лампа: освещённость линейно спадает до нуля на расстоянии `range`
+    Point { position: Vec3, color: u32, range: f32 },
+}
+
+/// Сколько источников учитывается; остальные отбрасываются
+pub const MAX_LIGHTS: usize = 8;
+
+/// Свет сцены: рассеянный без направления и источники
+#[derive(Clone, Copy, Debug)]
+pub struct Lighting<'a> {
+    pub ambient: u32,
+    pub lights: &'a [Light],
+}
+
+/// Цвета поверхности
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Material {
+    /// Как отражается рассеянный свет; `None` — цвет треугольника сетки
+    pub ambient: Option<u32>,
+    /// Как отражается свет источников; `None` — цвет треугольника сетки
+    pub diffuse: Option<u32>,
+    /// Собственное свечение, не зависит от света
+    pub emissive: u32,
+}
+
+impl Material {
+    /// Цвета из самой сетки, без свечения
+    pub const MESH: Material = Material { ambient: None, diffuse: None, emissive: 0 };
+}
+
+/// Нарисовать сетку с освещением.
+///
+/// Свет считается в мировых координатах в числах Q16.16, цвета смешиваются в целых;
+/// сцена должна помещаться в ±180 единиц, иначе квадраты расстояний насыщаются.
+pub fn draw_mesh_lit(
+    fb: &mut Framebuffer<'_>,
+    camera: &Camera,
+    mesh: &Mesh,
+    model: &Mat4,
+    lighting: &Lighting,
+    material: &Material,
+    shading: Shading,
+) {
+    let rig = LightRig::new(lighting);
+    // Нормали переводятся обратной транспонированной матрицей: так они остаются
+    // перпендикулярными граням и при неравномерном масштабе
+    let normal_matrix = model.to_mat3().inverse().map_or(model.to_mat3(), |m| m.transpose());
+    let world = |i: u16| model.transform_point(mesh.vertices[i as usize].position);
+    for (t, tri) in mesh.triangles.iter().enumerate() {
+        let p = tri.map(world);
+        let v = p.map(|p| camera.to_view(p));
+        if (v[1] - v[0]).cross(v[2] - v[0]).dot(v[0]) <= 0.0 {
+            continue;
+        }
+        let base = mesh.colors[t];
+        match shading {
+            Shading::Flat => {
+                // Обход по часовой стрелке: наружу смотрит (c - a) x (b - a)
+                let normal = (p[2] - p[0]).cross(p[1] - p[0]).normalize();
+                let center = (p[0] + p[1] + p[2]) * (1.0 / 3.0);
+                fill_triangle_3d(fb, camera, v[0], v[1], v[2], rig.color(center, normal, base, material));
+            }
+            Shading::Gouraud => {
+                let colors = [0, 1, 2].map(|k| {
+                    let normal = (normal_matrix * mesh.vertices[tri[k] as usize].normal).normalize();
+                    rig.color(p[k], normal, base, material)
+                });
+                fill_triangle_gouraud_3d(fb, camera, v, colors);
+            }
+        }
+    }
+}
+
+/// Нарисовать сетку с текстурой по координатам `uv` её вершин.
+///
+/// Текстура умножается на цвет треугольника, освещённый по граням, как у
+/// [`draw_mesh_lit`] с [`Shading::Flat`].
+pub fn draw_mesh_textured(
+    fb: &mut Framebuffer<'_>,
+    camera: &Camera,
+    mesh: &Mesh,
+    model: &Mat4,
+    texture: &Texture<'_>,
+    lighting: &Lighting,
+    material: &Material,
+) {
+    let rig = LightRig::new(lighting);
+    for (t, tri) in mesh.triangles.iter().enumerate() {
+        let p = tri.map(|i| model.transform_point(mesh.vertices[i as usize].position));
+        let v = p.map(|p| camera.to_view(p));
+        if (v[1] - v[0]).cross(v[2] - v[0]).dot(v[0]) <= 0.0 {
+            continue;
+        }
+        let normal = (p[2] - p[0]).cross(p[1] - p[0]).normalize();
+        let center = (p[0] + p[1] + p[2]) * (1.0 / 3.0);
+        let tint = rig.color(center, normal, mesh.colors[t], material);
+        let uv = tri.map(|i| mesh.vertices[i as usize].uv);
+        fill_triangle_textured_3d(fb, camera, v, uv, texture, tint);
+    }
+}
+
+/// Вектор Q16.16
+#[derive(Clone, Copy)]
+struct FixedVec3 {
+    x: Fixed,
+    y: Fixed,
+    z: Fixed,
+}
+
+impl FixedVec3 {
+    fn new(v: Vec3) -> Self {
+        FixedVec3 { x: Fixed::from_f32(v.x), y: Fixed::from_f32(v.y), z: Fixed::from_f32(v.z) }
+    }
+
+    fn sub(self, o: FixedVec3) -> FixedVec3 {
+        FixedVec3 { x: self.x - o.x, y: self.y - o.y, z: self.z - o.z }
+    }
+
+    fn dot(self, o: FixedVec3) -> Fixed {
+        self.x.saturating_mul(o.x).saturating_add(self.y.saturating_mul(o.y)).saturating_add(self.z.saturating_mul(o.z))
+    }
+}
+
+/// Каналы цвета как доли 0.0..=1.0: 255 * 257 = 65535 ≈ 1.0
+fn channels(color: u32) -> [Fixed; 3] {
+    [16, 8, 0].map(|shift| Fixed::from_raw(((color >> shift) & 0xFF) as i32 * 257))
+}
+
+enum FixedLight {
+    /// `to_light` — единичный вектор навстречу лучам
+    Directional { to_light: FixedVec3, color: [Fixed; 3] },
+    Point { position: FixedVec3, color: [Fixed; 3], range: Fixed },
+}
+
+/// Освещение, переведённое в Q16.16 один раз на сетку
+struct LightRig {
+    ambient: [Fixed; 3],
+    lights: FixedVec<FixedLight, MAX_LIGHTS>,
+}
+
+impl LightRig {
+    fn new(lighting: &Lighting) -> Self {
+        let lights = lighting
+            .lights
+            .iter()
+            .map(|light| match *light {
+                Light::Directional { direction, color } => FixedLight::Directional {
+                    to_light: FixedVec3::new(-direction.normalize()),
+                    color: channels(color),
+                },
+                Light::Point { position, color, range } => FixedLight::Point {
+                    position: FixedVec3::new(position),
+                    color: channels(color),
+                    range: Fixed::from_f32(range),
+                },
+            })
+            .collect();
+        LightRig { ambient: channels(lighting.ambient), lights }
+    }
+
+    /// Освещённость источниками точки `p` с единичной нормалью `n`, по каналам
+    fn diffuse(&self, p: FixedVec3, n: FixedVec3) -> [Fixed; 3] {
+        let mut sum = [Fixed::ZERO; 3];
+        for light in self.lights.iter() {
+            let (k, color) = match light {
+                FixedLight::Directional { to_light, color } => (n.dot(*to_light), color),
+                FixedLight::Point { position, color, range } => {
+                    let l = position.sub(p);
+                    let dist = l.dot(l).sqrt();
+                    if dist >= *range {
+                        continue;
+                    }
+                    // Косинус угла падения, умноженный на затухание 1 - dist / range
+                    let cos = if dist > Fixed::ZERO { n.dot(l) / dist } else { Fixed::ONE };
+                    (cos * (Fixed::ONE - dist / *range), color)
+                }
+            };
+            if k > Fixed::ZERO {
+                for (s, c) in sum.iter_mut().zip(color) {
+                    *s = s.saturating_add(k * *c);
+                }
+            }
+        }
+        sum
+    }
+
+    /// Цвет точки поверхности с цветом `base`
+    fn color(&self, position: Vec3, normal: Vec3, base: u32, material: &Material) -> u32 {
+        let light = self.diffuse(FixedVec3::new(position), FixedVec3::new(normal));
+        let ambient = material.ambient.unwrap_or(base);
+        let diffuse = material.diffuse.unwrap_or(base);
+        let mut out = 0xFF00_0000;
+        for (i, shift) in [16u32, 8, 0].into_iter().enumerate() {
+            let byte = |c: u32| ((c >> shift) & 0xFF) as i64;
+            let value = (byte(ambient) * self.ambient[i].raw() as i64 + byte(diffuse) * light[i].raw() as i64 + 0x8000) >> 16;
+            out |= ((value + byte(material.emissive)).clamp(0, 255) as u32) << shift;
+        }
+        out
+    }
+}
+
+/// Умножить RGB-составляющие цвета на яркость 0.0..=1.0
+pub fn shade(color: u32, intensity: f32) -> u32 {
+    let k = (intensity.clamp(0.0, 1.0) * 255.0) as u32;
+    let r = ((color >> 16) & 0xFF) * k / 255;
+    let g = ((color >> 8) & 0xFF) * k / 255;
+    let b = (color & 0xFF) * k / 255;
+    (color & 0xFF00_0000) | (r << 16) | (g << 8) | b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::models;
+
+    #[test]
+    fn lights_add_up_per_channel() {
+        let sun = Light::Directional { direction: Vec3::new(0.0, 1.0, 0.0), color: 0xFF8000 };
+        let lamp = Light::Point { position: Vec3::new(0.0, -2.0, 0.0), color: 0x0000FF, range: 4.0 };
+        let lights = [sun, lamp];
+        let rig = LightRig::new(&Lighting { ambient: 0x202020, lights: &lights });
+        let up = Vec3::new(0.0, -1.0, 0.0);
+        // Поверхность смотрит вверх (y вниз): солнце светит прямо, лампа в 2 единицах — вполсилы
+        let c = rig.color(Vec3::ZERO, up, 0xFFFFFFFF, &Material::MESH);
+        assert_eq!(c, 0xFFFFA09F);
+        // Нижняя сторона не освещена ничем, кроме рассеянного света
+        assert_eq!(rig.color(Vec3::ZERO, -up, 0xFFFFFFFF, &Material::MESH), 0xFF202020);
+        // Материал задаёт свои цвета и свечение; лампа дальше `range` не светит
+        let red = Material { ambient: Some(0), diffuse: Some(0xFF0000), emissive: 0x000010 };
+        assert_eq!(rig.color(Vec3::new(0.0, 5.0, 0.0), up, 0xFFFFFFFF, &red), 0xFFFF0010);
+    }
+
+    #[test]
+    fn lit_cube_is_brighter_on_the_lit_side() {
+        const S: usize = 32;
+        let mut px = [0u32; S * S];
+        let mut depth = [f32::INFINITY; S * S];
+        let camera = Camera::new(Vec3::new(0.0, 0.0, -5.0), core::f32::consts::FRAC_PI_3, 1.0, 0.1, 100.0);
+        // Куб повёрнут на 45°: видны две грани, свет падает слева
+        let model = Mat4::rotation_y(core::f32::consts::FRAC_PI_4);
+        let sun = [Light::Directional { direction: Vec3::X, color: 0xFFFFFF }];
+        let lighting = Lighting { ambient: 0x101010, lights: &sun };
+        for shading in [Shading::Flat, Shading::Gouraud] {
+            let mut fb = Framebuffer::from_slice(&mut px, S, S, &mut depth);
+            fb.clear(0);
+            fb.clear_depth();
+            draw_mesh_lit(&mut fb, &camera, &models::CUBE, &model, &lighting, &Material::MESH, shading);
+            let brightness = |x: usize| px[S / 2 * S + x].to_le_bytes()[..3].iter().map(|&b| b as u32).sum::<u32>();
+            assert!(brightness(S / 2 - 4) > 2 * brightness(S / 2 + 4), "{:?}", shading);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Стек сцен: меню, игра, пауза, конец игры.
+//!
+//! Обновляется только верхняя сцена; она же решает, что делать со стеком
+//! дальше ([`Transition`]). Рисуется верхняя сцена и те, что видны под ней
+//! (см. [`Scene::is_overlay`]). Сцены хранятся в стеке без кучи, поэтому
+//! удобнее всего сделать их вариантами одного перечисления.
+
+use crate::collections::FixedVec;
+use crate::framebuffer::Framebuffer;
+use crate::input::InputState;
+
+/// Сколько длится затемнение с проявлением по умолчанию, секунды
+pub const FADE_TIME: f32 = 0.4;
+
+/// Что сделать со стеком после шага верхней сцены
+pub enum Transition<S> {
+    /// Остаться в текущей сцене
+    Stay,
+    /// Положить сцену поверх текущей (пауза поверх игры); на полном стеке пропускается
+    Push(S),
+    /// Убрать текущую сцену и вернуться к предыдущей
+    Pop,
+    /// Заменить текущую сцену
+    Replace(S),
+    /// Затемнить экран, заменить весь стек одной сценой и проявить её
+    FadeTo(S),
+}
+
+pub trait Scene: Sized {
+    /// Данные, общие для всех сцен: настройки, рекорды, загруженные ресурсы
+    type Context;
+
+    /// Сцену положили в стек (но не вернулись к ней после `Pop`): здесь она
+    /// готовит общие данные, например начинает уровень заново
+    fn enter(&mut self, _ctx: &mut Self::Context) {}
+    /// Шаг логики; вызывается только у верхней сцены, ввод достаётся только ей
+    fn update(&mut self, ctx: &mut Self::Context, input: &InputState, delta: f32) -> Transition<Self>;
+    /// Нарисовать сцену. Сцены под верхней не обновляются и получают `alpha` = 1.0.
+    fn render(&mut self, ctx: &Self::Context, fb: &mut Framebuffer<'_>, alpha: f32);
+    /// Сцена прозрачна и рисуется поверх предыдущей
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// Идущее затемнение: сцена `next` заменит стек на середине
+struct Fade<S> {
+    next: Option<S>,
+    time: f32,
+}
+
+/// Стек не больше `N` сцен
+pub struct SceneStack<S, const N: usize> {
+    scenes: FixedVec<S, N>,
+    fade: Option<Fade<S>>,
+    fade_time: f32,
+}
+
+impl<S: Scene, const N: usize> SceneStack<S, N> {
+    /// Стек из одной сцены
+    pub fn new(first: S, ctx: &mut S::Context) -> Self {
+        let mut stack = SceneStack { scenes: FixedVec::new(), fade: None, fade_time: FADE_TIME };
+        stack.push(first, ctx);
+        stack
+    }
+
+    /// Другая длительность затемнения
+    pub fn with_fade_time(mut self, seconds: f32) -> Self {
+        self.fade_time = seconds.max(0.0);
+        self
+    }
+
+    /// Сцены снизу вверх
+    pub fn scenes(&self) -> &[S] {
+        &self.scenes
+    }
+
+    pub fn top(&self) -> Option<&S> {
+        self.scenes.last()
+    }
+
+    pub fn top_mut(&mut self) -> Option<&mut S> {
+        self.scenes.last_mut()
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Шаг верхней сцены и переход, который она вернула. Пока идёт
+    /// затемнение, сцены стоят и ввод не получают.
+    pub fn update(&mut self, ctx: &mut S::Context, input: &InputState, delta: f32) {
+        if let Some(fade) = &mut self.fade {
+            fade.time += delta;
+            let time = fade.time;
+            let next = if time >= self.fade_time * 0.5 { fade.next.take() } else { None };
+            if let Some(next) = next {
+                self.scenes.clear();
+                self.push(next, ctx);
+            }
+            if time >= self.fade_time {
+                self.fade = None;
+            }
+            return;
+        }
+        let Some(top) = self.scenes.last_mut() else {
+            return;
+        };
+        match top.update(ctx, input, delta) {
+            Transition::Stay => {}
+            Transition::Push(scene) => self.push(scene, ctx),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.push(scene, ctx);
+            }
+            Transition::FadeTo(scene) => self.fade = Some(Fade { next: Some(scene), time: 0.0 }),
+        }
+    }
+
+    /// Нарисовать видимые сцены снизу вверх и затемнение поверх них
+    pub fn render(&mut self, ctx: &S::Context, fb: &mut Framebuffer<'_>, alpha: f32) {
+        let count = self.scenes.len();
+        // Спускаемся, пока сцены сверху прозрачные
+        let mut first = count.saturating_sub(1);
+        while first > 0 && self.scenes[first].is_overlay() {
+            first -= 1;
+        }
+        let fading = self.fade.is_some();
+        for i in first..count {
+            let alpha = if i + 1 == count && !fading { alpha } else { 1.0 };
+            self.scenes[i].render(ctx, fb, alpha);
+        }
+        if let Some(fade) = &self.fade {
+            // Темнота растёт до середины затемнения и спадает после неё
+            let half = self.fade_time * 0.5;
+            let darkness = if half > 0.0 { 1.0 - ((fade.time - half) / half).abs() } else { 1.0 };
+            let a = (darkness.clamp(0.0, 1.0) * 255.0) as u32;
+            fb.blend_rect(fb.bounds(), a << 24);
+        }
+    }
+
+    /// Положить сцену; если стек полон, переход пропускается и верхней остаётся текущая
+    fn push(&mut self, mut scene: S, ctx: &mut S::Context) {
+        if self.scenes.len() == N {
+            return;
+        }
+        scene.enter(ctx);
+        let _ = self.scenes.push(scene);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Rect;
+    use crate::input::{Event, Key};
+
+    /// Сцена заливает свой прямоугольник; Enter кладёт поверх прозрачную,
+    /// Escape убирает верхнюю, пробел затемняет экран до новой
+    enum Test {
+        Solid(u32),
+        Overlay(u32),
+    }
+
+    impl Scene for Test {
+        /// Шаги сцен и, по сотне, входы в стек
+        type Context = u32;
+
+        fn enter(&mut self, steps: &mut u32) {
+            *steps += 100;
+        }
+
+        fn update(&mut self, steps: &mut u32, input: &InputState, _: f32) -> Transition<Self> {
+            *steps += 1;
+            if input.pressed(Key::ENTER) {
+                Transition::Push(Test::Overlay(0xFF00FF00))
+            } else if input.pressed(Key::ESCAPE) {
+                Transition::Pop
+            } else if input.pressed(Key::SPACE) {
+                Transition::FadeTo(Test::Solid(0xFF0000FF))
+            } else {
+                Transition::Stay
+            }
+        }
+
+        fn render(&mut self, _: &u32, fb: &mut Framebuffer<'_>, _: f32) {
+            match *self {
+                Test::Solid(color) => fb.fill_rect(fb.bounds(), color),
+                Test::Overlay(color) => fb.fill_rect(Rect::new(0, 0, 2, 2), color),
+            }
+        }
+
+        fn is_overlay(&self) -> bool {
+            matches!(self, Test::Overlay(_))
+        }
+    }
+
+    fn tap(key: Key) -> InputState {
+        let mut input = InputState::new();
+        input.push(Event::KeyDown(key));
+        input
+    }
+
+    #[test]
+    fn overlay_draws_over_paused_scene() {
+        let (mut px, mut depth) = ([0u32; 16], [0f32; 16]);
+        let mut steps = 0;
+        let mut stack = SceneStack::<Test, 4>::new(Test::Solid(0xFFFF0000), &mut steps);
+        stack.update(&mut steps, &tap(Key::ENTER), 0.1);
+        assert_eq!(stack.scenes().len(), 2);
+        stack.render(&steps, &mut Framebuffer::from_slice(&mut px, 4, 4, &mut depth), 0.5);
+        // Сцена снизу видна вокруг прозрачной
+        assert_eq!((px[0], px[15]), (0xFF00FF00, 0xFFFF0000));
+        stack.update(&mut steps, &tap(Key::ESCAPE), 0.1);
+        assert!(matches!(stack.top(), Some(Test::Solid(_))));
+        // Две сцены вошли в стек и сделали два шага; возврат после Pop — не вход
+        assert_eq!(steps, 202);
+    }
+
+    #[test]
+    fn push_on_full_stack_keeps_top_scene() {
+        let mut steps = 0;
+        let mut stack = SceneStack::<Test, 2>::new(Test::Solid(0xFFFF0000), &mut steps);
+        stack.update(&mut steps, &tap(Key::ENTER), 0.1);
+        stack.update(&mut steps, &tap(Key::ENTER), 0.1);
+        // Вторая прозрачная сцена не поместилась и в стек не входила
+        assert_eq!((steps, stack.scenes().len()), (202, 2));
+        assert!(matches!(stack.top(), Some(Test::Overlay(_))));
+    }
+
+    #[test]
+    fn fade_swaps_scenes_in_the_dark() {
+        let (mut px, mut depth) = ([0u32; 16], [0f32; 16]);
+        let mut steps = 0;
+        let mut stack = SceneStack::<Test, 4>::new(Test::Solid(0xFFFF0000), &mut steps).with_fade_time(1.0);
+        stack.update(&mut steps, &tap(Key::ENTER), 0.1);
+        stack.update(&mut steps, &tap(Key::SPACE), 0.1);
+        // Во время затемнения сцены стоят и ввод не получают
+        stack.update(&mut steps, &tap(Key::ESCAPE), 0.25);
+        assert_eq!((steps, stack.scenes().len()), (202, 2));
+        stack.render(&steps, &mut Framebuffer::from_slice(&mut px, 4, 4, &mut depth), 0.0);
+        assert_eq!(px[15], 0xFF800000);
+        // На середине стек заменился одной новой сценой, экран чёрный
+        stack.update(&mut steps, &InputState::new(), 0.25);
+        assert_eq!((steps, stack.scenes().len()), (302, 1));
+        stack.render(&steps, &mut Framebuffer::from_slice(&mut px, 4, 4, &mut depth), 0.0);
+        assert_eq!(px[0], 0xFF000000);
+        stack.update(&mut steps, &InputState::new(), 0.5);
+        assert!(!stack.is_fading());
+        stack.render(&steps, &mut Framebuffer::from_slice(&mut px, 4, 4, &mut depth), 0.0);
+        assert_eq!(px[0], 0xFF0000FF);
+    }
+}
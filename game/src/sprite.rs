@@ -0,0 +1,230 @@
+//! Вывод растровых спрайтов: отсечение, цветовой ключ, альфа-смешивание, анимация по листу кадров
+
+use crate::framebuffer::{Framebuffer, Rect};
+use crate::image::Image;
+
+/// Как пиксели спрайта ложатся на экран
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlitMode {
+    /// Копировать как есть
+    Opaque,
+    /// Пропускать пиксели этого цвета (сравниваются только RGB)
+    ColorKey(u32),
+    /// Смешивать по альфа-каналу спрайта
+    Alpha,
+    /// Прибавлять цвет спрайта, умноженный на его альфу: свечение, искры, огонь
+    Additive,
+}
+
+/// Смешать `src` поверх `dst` по альфе `src`; результат непрозрачный
+#[inline]
+pub fn blend(dst: u32, src: u32) -> u32 {
+    let a = src >> 24;
+    match a {
+        0 => dst | 0xFF00_0000,
+        255 => src,
+        _ => {
+            // Красный и синий смешиваются одним умножением, зелёный отдельно
+            let rb = ((src & 0xFF00FF) * a + (dst & 0xFF00FF) * (255 - a) + 0x800080) >> 8;
+            let g = ((src & 0x00FF00) * a + (dst & 0x00FF00) * (255 - a) + 0x008000) >> 8;
+            0xFF00_0000 | (rb & 0xFF00FF) | (g & 0x00FF00)
+        }
+    }
+}
+
+/// Прибавить к `dst` цвет `src`, умноженный на альфу `src`; каналы насыщаются на 255
+#[inline]
+pub fn add(dst: u32, src: u32) -> u32 {
+    let a = src >> 24;
+    let channel = |shift: u32| {
+        let sum = ((dst >> shift) & 0xFF) + ((((src >> shift) & 0xFF) * a + 127) / 255);
+        sum.min(255) << shift
+    };
+    0xFF00_0000 | channel(16) | channel(8) | channel(0)
+}
+
+/// Нарисовать область `src` изображения так, чтобы её левый верхний угол попал в (x, y).
+///
+/// Часть, выходящая за экран или за пределы изображения, отбрасывается.
+pub fn blit(fb: &mut Framebuffer<'_>, x: i32, y: i32, image: &Image<'_>, src: Rect, mode: BlitMode) {
+    let src = src.intersect(&Rect::new(0, 0, image.width as i32, image.height as i32));
+    // Область на экране, куда попадает спрайт, и смещение от неё к источнику
+    let dst = Rect::new(x, y, src.w, src.h).intersect(&fb.bounds());
+    if dst.is_empty() {
+        return;
+    }
+    let (ox, oy) = (src.x - x, src.y - y);
+    for py in dst.y..dst.y + dst.h {
+        for px in dst.x..dst.x + dst.w {
+            let color = image.pixel((px + ox) as usize, (py + oy) as usize);
+            match mode {
+                BlitMode::Opaque => fb.put_pixel(px, py, color),
+                BlitMode::ColorKey(key) => {
+                    if (color ^ key) & 0xFF_FFFF != 0 {
+                        fb.put_pixel(px, py, color);
+                    }
+                }
+                BlitMode::Alpha => match color >> 24 {
+                    0 => {}
+                    255 => fb.put_pixel(px, py, color),
+                    _ => {
+                        let under = fb.get_pixel(px, py);
+                        fb.put_pixel(px, py, blend(under, color));
+                    }
+                },
+                BlitMode::Additive => {
+                    if color >> 24 != 0 {
+                        let under = fb.get_pixel(px, py);
+                        fb.put_pixel(px, py, add(under, color));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Изображение, нарезанное на кадры одинакового размера (слева направо, сверху вниз)
+#[derive(Clone, Copy)]
+pub struct SpriteSheet<'a> {
+    pub image: Image<'a>,
+    pub frame_width: usize,
+    pub frame_height: usize,
+}
+
+impl<'a> SpriteSheet<'a> {
+    pub fn new(image: Image<'a>, frame_width: usize, frame_height: usize) -> Self {
+        assert!(frame_width > 0 && frame_height > 0, "пустой кадр");
+        SpriteSheet { image, frame_width, frame_height }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.image.width / self.frame_width
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.columns() * (self.image.height / self.frame_height)
+    }
+
+    /// Прямоугольник кадра в изображении
+    pub fn frame(&self, index: usize) -> Rect {
+        let columns = self.columns().max(1);
+        Rect::new(
+            ((index % columns) * self.frame_width) as i32,
+            ((index / columns) * self.frame_height) as i32,
+            self.frame_width as i32,
+            self.frame_height as i32,
+        )
+    }
+
+    pub fn draw(&self, fb: &mut Framebuffer<'_>, x: i32, y: i32, index: usize, mode: BlitMode) {
+        blit(fb, x, y, &self.image, self.frame(index), mode);
+    }
+}
+
+/// Проигрывание подряд идущих кадров листа
+#[derive(Clone, Copy, Debug)]
+pub struct Animation {
+    pub first: usize,
+    pub count: usize,
+    /// Длительность одного кадра, секунды
+    pub frame_time: f32,
+    /// Начинать заново после последнего кадра или остановиться на нём
+    pub looped: bool,
+    time: f32,
+}
+
+impl Animation {
+    pub fn new(first: usize, count: usize, frame_time: f32, looped: bool) -> Self {
+        Animation { first, count: count.max(1), frame_time, looped, time: 0.0 }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta;
+        let length = self.frame_time * self.count as f32;
+        if self.looped && length > 0.0 {
+            while self.time >= length {
+                self.time -= length;
+            }
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+    }
+
+    /// Неповторяющаяся анимация дошла до последнего кадра
+    pub fn finished(&self) -> bool {
+        !self.looped && self.time >= self.frame_time * self.count as f32
+    }
+
+    /// Номер текущего кадра в листе
+    pub fn frame(&self) -> usize {
+        let step = if self.frame_time > 0.0 { (self.time / self.frame_time) as usize } else { 0 };
+        self.first + step.min(self.count - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: usize = 4;
+    const H: usize = 4;
+
+    #[test]
+    fn blend_weights() {
+        assert_eq!(blend(0xFF0000FF, 0x00FF0000), 0xFF0000FF);
+        assert_eq!(blend(0xFF0000FF, 0xFFFF0000), 0xFFFF0000);
+        assert_eq!(blend(0xFF000000, 0x80FFFFFF), 0xFF808080);
+        assert_eq!(blend(0xFFFFFFFF, 0x80000000), 0xFF7F7F7F);
+        assert_eq!(add(0xFF102030, 0xFF010203), 0xFF112233);
+        assert_eq!(add(0xFFF08000, 0x80FFFFFF), 0xFFFFFF80);
+    }
+
+    #[test]
+    fn blit_clips_and_keys() {
+        // Спрайт 2x2: красный, ключ (пурпурный), полупрозрачный белый, прозрачный
+        let pixels = [0xFFFF0000, 0xFFFF00FF, 0x80FFFFFF, 0x00123456];
+        let image = Image::new(2, 2, &pixels);
+        let all = Rect::new(0, 0, 2, 2);
+        let mut px = [0xFF000000u32; W * H];
+        let mut depth = [0.0f32; W * H];
+
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        blit(&mut fb, -1, -1, &image, all, BlitMode::Opaque);
+        blit(&mut fb, 3, 3, &image, all, BlitMode::Opaque);
+        assert_eq!(px[0], 0x00123456);
+        assert_eq!(px[W * H - 1], 0xFFFF0000);
+        assert_eq!(px.iter().filter(|&&p| p != 0xFF000000).count(), 2);
+
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.clear(0xFF000000);
+        blit(&mut fb, 0, 0, &image, all, BlitMode::ColorKey(0x00FF00FF));
+        assert_eq!(&px[..2], &[0xFFFF0000, 0xFF000000]);
+
+        let mut fb = Framebuffer::from_slice(&mut px, W, H, &mut depth);
+        fb.clear(0xFF000000);
+        blit(&mut fb, 0, 0, &image, all, BlitMode::Alpha);
+        assert_eq!(&px[W..W + 2], &[0xFF808080, 0xFF000000]);
+    }
+
+    #[test]
+    fn sheet_frames_and_animation() {
+        let pixels = [0u32; 8 * 4];
+        let sheet = SpriteSheet::new(Image::new(8, 4, &pixels), 2, 2);
+        assert_eq!(sheet.frame_count(), 8);
+        assert_eq!(sheet.frame(5), Rect::new(2, 2, 2, 2));
+
+        let mut anim = Animation::new(2, 3, 0.1, true);
+        assert_eq!(anim.frame(), 2);
+        anim.update(0.25);
+        assert_eq!(anim.frame(), 4);
+        anim.update(0.1);
+        assert_eq!(anim.frame(), 2);
+
+        let mut once = Animation::new(0, 2, 0.1, false);
+        once.update(1.0);
+        assert_eq!(once.frame(), 1);
+        assert!(once.finished());
+    }
+}
@@ -0,0 +1,159 @@
+//! Строка фиксированной длины на стеке и форматирование чисел без кучи
+//!
+//! Годится для счётчиков, очков и сообщений: `write!(buf, "{}", info)` тоже работает.
+
+use core::fmt;
+
+/// Строка не длиннее `N` байт. Не поместившееся отбрасывается.
+pub struct TextBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> TextBuf<N> {
+    pub const fn new() -> Self {
+        TextBuf { buf: [0; N], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Пишутся только целые символы UTF-8, см. push_str
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Дописать строку; хвост, который не влез, обрезается по границе символа
+    pub fn push_str(&mut self, s: &str) {
+        let mut take = s.len().min(N - self.len);
+        while !s.is_char_boundary(take) {
+            take -= 1;
+        }
+        self.buf[self.len..self.len + take].copy_from_slice(&s.as_bytes()[..take]);
+        self.len += take;
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn push_int(&mut self, value: i64) {
+        if value < 0 {
+            self.push_char('-');
+        }
+        self.push_uint(value.unsigned_abs(), 0, b' ');
+    }
+
+    /// Беззнаковое число, дополненное слева символом `pad` до `width` знаков
+    /// (очки "000120" — `push_uint(120, 6, b'0')`)
+    pub fn push_uint(&mut self, mut value: u64, width: usize, pad: u8) {
+        let mut digits = [0u8; 20];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        for _ in digits.len() - start..width {
+            self.push_char(pad as char);
+        }
+        // Цифры — ASCII, строка корректна
+        self.push_str(unsafe { core::str::from_utf8_unchecked(&digits[start..]) });
+    }
+
+    /// Число с `decimals` знаками после точки (округление до ближайшего)
+    pub fn push_float(&mut self, value: f32, decimals: u32) {
+        if value.is_nan() {
+            return self.push_str("NaN");
+        }
+        if value < 0.0 {
+            self.push_char('-');
+        }
+        let scale = 10u64.pow(decimals.min(9));
+        let scaled = value.abs() as f64 * scale as f64 + 0.5;
+        if scaled >= u64::MAX as f64 {
+            return self.push_str("inf");
+        }
+        let scaled = scaled as u64;
+        self.push_uint(scaled / scale, 0, b' ');
+        if decimals > 0 {
+            self.push_char('.');
+            self.push_uint(scaled % scale, decimals.min(9) as usize, b'0');
+        }
+    }
+}
+
+impl<const N: usize> Default for TextBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for TextBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn formats_numbers() {
+        let mut t = TextBuf::<64>::new();
+        t.push_int(0);
+        t.push_char(' ');
+        t.push_int(-42);
+        t.push_char(' ');
+        t.push_int(i64::MIN);
+        assert_eq!(t.as_str(), "0 -42 -9223372036854775808");
+
+        t.clear();
+        t.push_uint(120, 6, b'0');
+        t.push_char('|');
+        t.push_uint(1234567, 3, b' ');
+        t.push_char('|');
+        t.push_uint(7, 3, b' ');
+        assert_eq!(t.as_str(), "000120|1234567|  7");
+
+        t.clear();
+        t.push_float(12.345, 2);
+        t.push_char(' ');
+        t.push_float(-0.05, 1);
+        t.push_char(' ');
+        t.push_float(59.96, 0);
+        t.push_char(' ');
+        t.push_float(1.005, 3);
+        assert_eq!(t.as_str(), "12.35 -0.1 60 1.005");
+    }
+
+    #[test]
+    fn truncates_on_char_boundary() {
+        let mut t = TextBuf::<7>::new();
+        t.push_str("Очки");
+        // 'О', 'ч', 'к' по 2 байта, на 'и' места нет
+        assert_eq!(t.as_str(), "Очк");
+        t.push_str("!!");
+        assert_eq!(t.as_str(), "Очк!");
+        assert_eq!(t.len(), 7);
+
+        let mut t = TextBuf::<16>::new();
+        write!(t, "FPS {}", 60).unwrap();
+        assert_eq!(t.as_str(), "FPS 60");
+    }
+}
@@ -0,0 +1,160 @@
+//! Текстуры: выборка цвета по координатам (u, v) с фильтрацией и адресацией.
+//!
+//! Пиксели берутся из [`Image`]; встроенные текстуры лежат статическими
+//! массивами в [`textures`].
+
+use crate::image::Image;
+
+/// Как цвет выбирается между текселями
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Ближайший тексель: чёткие квадраты при увеличении
+    Nearest,
+    /// Смешивание четырёх соседних текселей
+    Bilinear,
+}
+
+/// Что происходит с координатами за пределами 0.0..1.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    /// Текстура повторяется
+    Repeat,
+    /// Берётся крайний тексель
+    Clamp,
+}
+
+impl Wrap {
+    #[inline]
+    fn apply(self, i: i32, size: i32) -> usize {
+        match self {
+            Wrap::Repeat => i.rem_euclid(size) as usize,
+            Wrap::Clamp => i.clamp(0, size - 1) as usize,
+        }
+    }
+}
+
+/// Изображение с настройками выборки. (0, 0) — левый верхний угол, (1, 1) — правый нижний.
+#[derive(Clone, Copy)]
+pub struct Texture<'a> {
+    pub image: Image<'a>,
+    pub filter: Filter,
+    pub wrap: Wrap,
+}
+
+impl<'a> Texture<'a> {
+    /// Текстура с выборкой ближайшего текселя и повтором
+    pub fn new(image: Image<'a>) -> Self {
+        assert!(image.width > 0 && image.height > 0, "пустая текстура");
+        Texture { image, filter: Filter::Nearest, wrap: Wrap::Repeat }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Цвет 0xAARRGGBB в точке (u, v)
+    pub fn sample(&self, u: f32, v: f32) -> u32 {
+        let (w, h) = (self.image.width as f32, self.image.height as f32);
+        match self.filter {
+            Filter::Nearest => self.texel(floor(u * w), floor(v * h)),
+            Filter::Bilinear => {
+                // Центры текселей лежат на полуцелых координатах
+                let (x, y) = (u * w - 0.5, v * h - 0.5);
+                let (x0, y0) = (floor(x), floor(y));
+                let fx = ((x - x0 as f32) * 256.0) as u32;
+                let fy = ((y - y0 as f32) * 256.0) as u32;
+                let top = lerp(self.texel(x0, y0), self.texel(x0 + 1, y0), fx);
+                let bottom = lerp(self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1), fx);
+                lerp(top, bottom, fy)
+            }
+        }
+    }
+
+    #[inline]
+    fn texel(&self, x: i32, y: i32) -> u32 {
+        let x = self.wrap.apply(x, self.image.width as i32);
+        let y = self.wrap.apply(y, self.image.height as i32);
+        self.image.pixel(x, y)
+    }
+}
+
+/// Целая часть с округлением вниз (в `core` нет `f32::floor`).
+/// За пределами i32 результат насыщается, как у `as`.
+#[inline]
+pub(crate) fn floor(x: f32) -> i32 {
+    let i = x as i32;
+    if (i as f32) > x { i.saturating_sub(1) } else { i }
+}
+
+/// Смешать два цвета по всем четырём каналам, `t` от 0 до 256
+#[inline]
+pub(crate) fn lerp(a: u32, b: u32, t: u32) -> u32 {
+    let mut out = 0;
+    for shift in [0u32, 8, 16, 24] {
+        let (ca, cb) = (((a >> shift) & 0xFF) as i32, ((b >> shift) & 0xFF) as i32);
+        out |= ((ca + (((cb - ca) * t as i32) >> 8)) as u32) << shift;
+    }
+    out
+}
+
+/// Встроенные текстуры
+pub mod textures {
+    use crate::image::Image;
+
+    /// Шахматная доска 8x8 из светлых и серых клеток
+    pub static CHECKER: Image<'static> = Image { width: 8, height: 8, pixels: &CHECKER_PIXELS };
+
+    static CHECKER_PIXELS: [u32; 64] = checker(0xFFFFFFFF, 0xFFA0A0A0);
+
+    const fn checker(light: u32, dark: u32) -> [u32; 64] {
+        let mut pixels = [0; 64];
+        let mut i = 0;
+        while i < 64 {
+            pixels[i] = if (i % 8 + i / 8) % 2 == 0 { light } else { dark };
+            i += 1;
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIXELS: [u32; 4] = [0xFF000000, 0xFFFF0000, 0xFF00FF00, 0xFF0000FF];
+
+    #[test]
+    fn nearest_repeats_or_clamps() {
+        let texture = Texture::new(Image::new(2, 2, &PIXELS));
+        assert_eq!(texture.sample(0.25, 0.25), 0xFF000000);
+        assert_eq!(texture.sample(0.75, 0.75), 0xFF0000FF);
+        // Повтор: 1.25 — то же, что 0.25; -0.25 — то же, что 0.75
+        assert_eq!(texture.sample(1.25, 0.25), 0xFF000000);
+        assert_eq!(texture.sample(-0.25, 0.25), 0xFFFF0000);
+        let clamped = texture.with_wrap(Wrap::Clamp);
+        assert_eq!(clamped.sample(-0.25, 0.25), 0xFF000000);
+        assert_eq!(clamped.sample(3.0, 3.0), 0xFF0000FF);
+    }
+
+    #[test]
+    fn floor_rounds_down_and_saturates() {
+        assert_eq!((floor(1.5), floor(-1.5), floor(-2.0)), (1, -2, -2));
+        assert_eq!((floor(-1e10), floor(1e10), floor(f32::NAN)), (i32::MIN, i32::MAX, 0));
+    }
+
+    #[test]
+    fn bilinear_blends_neighbours() {
+        let texture = Texture::new(Image::new(2, 2, &PIXELS)).with_filter(Filter::Bilinear).with_wrap(Wrap::Clamp);
+        // В центре текселя — сам тексель, посередине между четырьмя — среднее
+        assert_eq!(texture.sample(0.25, 0.25), 0xFF000000);
+        assert_eq!(texture.sample(0.5, 0.25), 0xFF7F0000);
+        assert_eq!(texture.sample(0.5, 0.5), 0xFF3F3F3F);
+        assert_eq!(textures::CHECKER.pixel(1, 0), 0xFFA0A0A0);
+    }
+}
@@ -0,0 +1,477 @@
+//! Тайловые карты для 2D-игр: слои тайлов, флаги столкновений и прокрутка.
+//!
+//! Карты хранятся в компактном двоичном формате NGTM; уровни из `assets/*.map`
+//! собирает в него build.rs, они лежат в [`levels`]. Тайлы рисуются кадрами
+//! [`SpriteSheet`]: номер тайла — номер кадра, тайл 0 пустой и не рисуется.
+//!
+//! Формат (little-endian): `"NGTM"`, версия u8, ширина и высота тайла u8,
+//! число слоёв u8, ширина и высота карты в тайлах u16, число флагов u8 и
+//! сами флаги (по байту на тайл, начиная с 0), затем слои один за другим:
+//! пары (длина серии u8, тайл u8), пока слой не заполнится.
+
+use crate::arena::Arena;
+use crate::framebuffer::{Framebuffer, Rect};
+use crate::math::Vec2;
+use crate::sprite::{BlitMode, SpriteSheet};
+use crate::texture::floor;
+
+/// Больше слоёв формат не допускает
+pub const MAX_LAYERS: usize = 4;
+
+const MAGIC: &[u8; 4] = b"NGTM";
+const VERSION: u8 = 1;
+/// Длина заголовка до таблицы флагов
+const HEADER_LEN: usize = 13;
+/// Насколько край коробки отступает внутрь: коробка, стоящая вплотную к тайлу, его не задевает
+const EDGE: f32 = 1.0 / 256.0;
+
+/// Свойства тайла для столкновений
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileFlags(pub u8);
+
+impl TileFlags {
+    pub const NONE: TileFlags = TileFlags(0);
+    /// Сквозь тайл нельзя пройти
+    pub const SOLID: TileFlags = TileFlags(1);
+    /// На тайл можно встать сверху, снизу и сбоку он проходим
+    pub const PLATFORM: TileFlags = TileFlags(2);
+    /// Касание ранит (шипы, лава)
+    pub const HAZARD: TileFlags = TileFlags(4);
+
+    /// Есть ли хотя бы один из флагов `other`
+    pub fn intersects(self, other: TileFlags) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl core::ops::BitOr for TileFlags {
+    type Output = TileFlags;
+
+    fn bitor(self, o: TileFlags) -> TileFlags {
+        TileFlags(self.0 | o.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapError {
+    /// Не карта NGTM или неподдерживаемая версия
+    Format,
+    /// Данные кончились раньше карты
+    Truncated,
+    /// Буфер для тайлов меньше карты
+    BufferTooSmall,
+}
+
+/// Размеры карты из заголовка
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapHeader {
+    /// В тайлах
+    pub width: usize,
+    pub height: usize,
+    pub layers: usize,
+    /// Размер тайла в пикселях
+    pub tile_width: usize,
+    pub tile_height: usize,
+}
+
+impl MapHeader {
+    /// Сколько байт нужно под тайлы всех слоёв
+    pub fn tile_count(&self) -> usize {
+        self.width * self.height * self.layers
+    }
+}
+
+/// Прочитать заголовок карты
+pub fn header(data: &[u8]) -> Result<MapHeader, MapError> {
+    if data.len() < HEADER_LEN {
+        return Err(if data.starts_with(&MAGIC[..data.len().min(4)]) { MapError::Truncated } else { MapError::Format });
+    }
+    if &data[..4] != MAGIC || data[4] != VERSION {
+        return Err(MapError::Format);
+    }
+    let word = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let header = MapHeader {
+        width: word(8),
+        height: word(10),
+        layers: data[7] as usize,
+        tile_width: data[5] as usize,
+        tile_height: data[6] as usize,
+    };
+    if header.layers == 0 || header.layers > MAX_LAYERS || header.tile_width == 0 || header.tile_height == 0 {
+        return Err(MapError::Format);
+    }
+    Ok(header)
+}
+
+/// Распаковать карту, складывая тайлы в `tiles`. Флаги остаются в `data`.
+pub fn decode_into<'a>(data: &'a [u8], tiles: &'a mut [u8]) -> Result<TileMap<'a>, MapError> {
+    let header = header(data)?;
+    if tiles.len() < header.tile_count() {
+        return Err(MapError::BufferTooSmall);
+    }
+    let tiles = &mut tiles[..header.tile_count()];
+    let flag_count = data[HEADER_LEN - 1] as usize;
+    let flags = data.get(HEADER_LEN..HEADER_LEN + flag_count).ok_or(MapError::Truncated)?;
+    let mut pos = HEADER_LEN + flag_count;
+    let mut filled = 0;
+    while filled < tiles.len() {
+        let run = data.get(pos..pos + 2).ok_or(MapError::Truncated)?;
+        let (count, tile) = (run[0] as usize, run[1]);
+        // Серия не переходит через конец последнего слоя
+        if count == 0 || filled + count > tiles.len() {
+            return Err(MapError::Format);
+        }
+        tiles[filled..filled + count].fill(tile);
+        filled += count;
+        pos += 2;
+    }
+    Ok(TileMap {
+        width: header.width,
+        height: header.height,
+        layers: header.layers,
+        tile_width: header.tile_width,
+        tile_height: header.tile_height,
+        tiles,
+        flags,
+    })
+}
+
+/// Распаковать карту в память арены. `None`, если арене не хватило места.
+pub fn load(data: &'static [u8], arena: &mut Arena) -> Option<Result<TileMap<'static>, MapError>> {
+    let header = match header(data) {
+        Ok(header) => header,
+        Err(e) => return Some(Err(e)),
+    };
+    let tiles = arena.alloc_slice(header.tile_count(), 0u8)?;
+    Some(decode_into(data, tiles))
+}
+
+/// Куда сдвинулась коробка в [`TileMap::move_box`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Movement {
+    pub position: Vec2,
+    /// Упёрлась в стену по горизонтали
+    pub blocked_x: bool,
+    /// Встала на пол или платформу (при движении вниз) или ударилась головой
+    pub blocked_y: bool,
+}
+
+/// Карта из слоёв тайлов одного размера; слой 0 рисуется первым
+pub struct TileMap<'a> {
+    /// В тайлах
+    pub width: usize,
+    pub height: usize,
+    pub layers: usize,
+    /// Размер тайла в пикселях
+    pub tile_width: usize,
+    pub tile_height: usize,
+    /// Слои подряд, в каждом ряды сверху вниз
+    tiles: &'a mut [u8],
+    /// Флаги по номеру тайла; тайлы за концом таблицы флагов не имеют
+    flags: &'a [u8],
+}
+
+impl<'a> TileMap<'a> {
+    /// Размер карты в пикселях
+    pub fn pixel_size(&self) -> (usize, usize) {
+        (self.width * self.tile_width, self.height * self.tile_height)
+    }
+
+    /// Тайл слоя `layer` в клетке (x, y); за пределами карты — 0
+    pub fn tile(&self, layer: usize, x: i32, y: i32) -> u8 {
+        self.index(layer, x, y).map_or(0, |i| self.tiles[i])
+    }
+
+    /// Заменить тайл (подобранная монета, сломанный блок); за пределами карты ничего не делает
+    pub fn set_tile(&mut self, layer: usize, x: i32, y: i32, tile: u8) {
+        if let Some(i) = self.index(layer, x, y) {
+            self.tiles[i] = tile;
+        }
+    }
+
+    /// Флаги тайла с номером `tile`
+    pub fn flags(&self, tile: u8) -> TileFlags {
+        TileFlags(self.flags.get(tile as usize).copied().unwrap_or(0))
+    }
+
+    /// Флаги клетки по всем слоям
+    pub fn flags_at(&self, x: i32, y: i32) -> TileFlags {
+        (0..self.layers).fold(TileFlags::NONE, |acc, layer| acc | self.flags(self.tile(layer, x, y)))
+    }
+
+    /// Клетка, в которую попадает точка карты в пикселях
+    pub fn cell_at(&self, point: Vec2) -> (i32, i32) {
+        (floor(point.x / self.tile_width as f32), floor(point.y / self.tile_height as f32))
+    }
+
+    /// Касается ли прямоугольник (в пикселях карты) клеток с любым из флагов `mask`
+    pub fn touches(&self, rect: Rect, mask: TileFlags) -> bool {
+        if rect.is_empty() {
+            return false;
+        }
+        let position = Vec2::new(rect.x as f32, rect.y as f32);
+        self.box_touches(position, Vec2::new(rect.w as f32, rect.h as f32), mask)
+    }
+
+    /// Сдвинуть коробку (`position` — левый верхний угол, `size` — размер, в пикселях)
+    /// на `delta`, не пуская её в сплошные тайлы и давая встать на платформы.
+    /// Сначала ось x, потом y; длинный сдвиг делится на шаги не больше тайла,
+    /// поэтому быстрая коробка не проскакивает сквозь стены.
+    pub fn move_box(&self, mut position: Vec2, size: Vec2, delta: Vec2) -> Movement {
+        let (tw, th) = (self.tile_width as f32, self.tile_height as f32);
+        let steps = ((delta.x.abs() / tw).max(delta.y.abs() / th) as u32 + 1).min(1024);
+        let step = delta * (1.0 / steps as f32);
+        let (mut blocked_x, mut blocked_y) = (false, false);
+        for _ in 0..steps {
+            if step.x != 0.0 && !blocked_x {
+                let x = position.x + step.x;
+                if self.box_touches(Vec2::new(x, position.y), size, TileFlags::SOLID) {
+                    blocked_x = true;
+                    position.x = if step.x > 0.0 {
+                        floor((x + size.x - EDGE) / tw) as f32 * tw - size.x
+                    } else {
+                        (floor(x / tw) + 1) as f32 * tw
+                    };
+                } else {
+                    position.x = x;
+                }
+            }
+            if step.y != 0.0 && !blocked_y {
+                let y = position.y + step.y;
+                let row = floor((y + size.y - EDGE) / th);
+                // На платформу можно встать, только если ноги были над её верхом
+                let lands = step.y > 0.0
+                    && position.y + size.y <= row as f32 * th + EDGE
+                    && self.row_touches(position.x, size.x, row, TileFlags::PLATFORM);
+                if lands || self.box_touches(Vec2::new(position.x, y), size, TileFlags::SOLID) {
+                    blocked_y = true;
+                    position.y = if step.y > 0.0 { row as f32 * th - size.y } else { (floor(y / th) + 1) as f32 * th };
+                } else {
+                    position.y = y;
+                }
+            }
+        }
+        Movement { position, blocked_x, blocked_y }
+    }
+
+    /// Нарисовать слой так, как его видит `view`, от левого верхнего угла экрана.
+    /// Нижний слой обычно рисуют `BlitMode::Opaque`, верхние — `ColorKey` или `Alpha`.
+    pub fn draw_layer(
+        &self,
+        fb: &mut Framebuffer<'_>,
+        sheet: &SpriteSheet<'_>,
+        layer: usize,
+        view: &Viewport,
+        alpha: f32,
+        mode: BlitMode,
+    ) {
+        let (tw, th) = (self.tile_width as i32, self.tile_height as i32);
+        let (ox, oy) = view.origin(alpha);
+        let (cx, cy) = (ox.div_euclid(tw), oy.div_euclid(th));
+        let columns = (view.width.min(fb.width()) as i32 + tw - 1) / tw + 1;
+        let rows = (view.height.min(fb.height()) as i32 + th - 1) / th + 1;
+        for y in cy..cy + rows {
+            for x in cx..cx + columns {
+                let tile = self.tile(layer, x, y);
+                if tile != 0 {
+                    sheet.draw(fb, x * tw - ox, y * th - oy, tile as usize, mode);
+                }
+            }
+        }
+    }
+
+    fn index(&self, layer: usize, x: i32, y: i32) -> Option<usize> {
+        if layer >= self.layers || x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some((layer * self.height + y as usize) * self.width + x as usize)
+    }
+
+    fn box_touches(&self, position: Vec2, size: Vec2, mask: TileFlags) -> bool {
+        let (x0, y0) = self.cell_at(position);
+        let (x1, y1) = self.cell_at(position + size - Vec2::new(EDGE, EDGE));
+        (y0..=y1).any(|y| (x0..=x1).any(|x| self.flags_at(x, y).intersects(mask)))
+    }
+
+    fn row_touches(&self, x: f32, width: f32, row: i32, mask: TileFlags) -> bool {
+        let tw = self.tile_width as f32;
+        (floor(x / tw)..=floor((x + width - EDGE) / tw)).any(|column| self.flags_at(column, row).intersects(mask))
+    }
+}
+
+/// Окно на карту размером с экран (или его часть).
+///
+/// Положение — левый верхний угол в пикселях карты — хранится с дробной частью:
+/// медленная прокрутка копится и движется ровно, а рисуется окно со сдвигом,
+/// округлённым до пикселя. Как и у объектов игры, между шагами положение
+/// интерполируется по `alpha`, для этого перед шагом вызывается [`Viewport::remember`].
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub position: Vec2,
+    prev: Vec2,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Viewport {
+    pub fn new(width: usize, height: usize) -> Self {
+        Viewport { position: Vec2::ZERO, prev: Vec2::ZERO, width, height }
+    }
+
+    /// Запомнить положение перед шагом логики
+    pub fn remember(&mut self) {
+        self.prev = self.position;
+    }
+
+    /// Сдвинуть окно, не выпуская за края карты
+    pub fn scroll(&mut self, delta: Vec2, map: &TileMap) {
+        self.position += delta;
+        self.clamp(map);
+    }
+
+    /// Поставить точку `target` в центр окна, насколько позволяют края карты
+    pub fn follow(&mut self, target: Vec2, map: &TileMap) {
+        self.position = target - Vec2::new(self.width as f32, self.height as f32) * 0.5;
+        self.clamp(map);
+    }
+
+    /// Сдвиг карты на экране в целых пикселях с учётом доли шага `alpha`
+    pub fn origin(&self, alpha: f32) -> (i32, i32) {
+        let p = self.prev.lerp(self.position, alpha);
+        (floor(p.x), floor(p.y))
+    }
+
+    /// Где на экране окажется точка карты
+    pub fn to_screen(&self, point: Vec2, alpha: f32) -> (i32, i32) {
+        let (ox, oy) = self.origin(alpha);
+        (floor(point.x) - ox, floor(point.y) - oy)
+    }
+
+    fn clamp(&mut self, map: &TileMap) {
+        let (w, h) = map.pixel_size();
+        let max = Vec2::new(w.saturating_sub(self.width) as f32, h.saturating_sub(self.height) as f32);
+        self.position = Vec2::new(self.position.x.clamp(0.0, max.x), self.position.y.clamp(0.0, max.y));
+    }
+}
+
+/// Уровни, собранные из `assets/*.map`: `assets/demo.map` — [`levels::DEMO`]
+pub mod levels {
+    include!(concat!(env!("OUT_DIR"), "/levels.rs"));
+}
+
+/// Встроенные наборы тайлов
+pub mod tiles {
+    use crate::image::Image;
+
+    /// Тайлы 8x8 для [`super::levels::DEMO`]: пустой, кирпич, платформа, шипы,
+    /// звезда и стена фона. Прозрачные пиксели — 0x00000000.
+    pub static DEMO: Image<'static> = Image { width: 48, height: 8, pixels: &DEMO_PIXELS };
+
+    static DEMO_PIXELS: [u32; 48 * 8] = demo();
+
+    const fn demo() -> [u32; 48 * 8] {
+        let mut pixels = [0; 48 * 8];
+        let mut i = 0;
+        while i < pixels.len() {
+            let (x, y) = ((i % 48) as i32, (i / 48) as i32);
+            let (tile, tx) = (x / 8, x % 8);
+            pixels[i] = match tile {
+                // Кирпичи со швами, ряды сдвинуты на полкирпича
+                1 if y % 4 == 3 || tx == (y / 4 % 2) * 4 => 0xFF606060,
+                1 => 0xFFB04030,
+                2 if y < 2 => 0xFFC08040,
+                3 if (2 * tx - 7).abs() <= y => 0xFFE0E0E0,
+                4 if (tx - 3).abs() + (y - 3).abs() <= 2 => 0xFFFFE040,
+                5 if tx % 4 == 1 && y % 4 == 1 => 0xFF303A50,
+                5 => 0xFF202838,
+                _ => 0,
+            };
+            i += 1;
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demo(tiles: &mut [u8]) -> TileMap<'_> {
+        decode_into(levels::DEMO, tiles).unwrap()
+    }
+
+    #[test]
+    fn level_loads_from_compact_binary() {
+        let size = header(levels::DEMO).unwrap();
+        assert_eq!((size.width, size.height, size.layers, size.tile_width), (40, 15, 2, 8));
+        // Карта 40x15x2 сжата сериями
+        assert!(levels::DEMO.len() < size.tile_count() / 2);
+        let mut tiles = [0u8; 40 * 15 * 2];
+        let map = demo(&mut tiles);
+        assert_eq!((map.tile(0, 0, 0), map.tile(1, 0, 14), map.tile(1, 1, 1)), (5, 1, 0));
+        assert_eq!(map.flags_at(0, 14), TileFlags::SOLID);
+        assert_eq!(map.flags_at(16, 7), TileFlags::PLATFORM);
+        assert_eq!(map.flags_at(11, 13), TileFlags::HAZARD);
+        // Фон без флагов, за картой пусто
+        assert_eq!((map.flags_at(1, 1), map.tile(0, -1, 3)), (TileFlags::NONE, 0));
+        assert!(map.touches(Rect::new(86, 100, 4, 8), TileFlags::HAZARD));
+        assert!(!map.touches(Rect::new(8, 8, 8, 8), TileFlags::SOLID | TileFlags::HAZARD));
+
+        assert_eq!(header(b"NGTM\x02"), Err(MapError::Truncated));
+        assert_eq!(header(b"P6 2 1 255\n......"), Err(MapError::Format));
+        let cut = &levels::DEMO[..levels::DEMO.len() - 2];
+        assert_eq!(decode_into(cut, &mut tiles).err(), Some(MapError::Truncated));
+        assert_eq!(decode_into(levels::DEMO, &mut tiles[..100]).err(), Some(MapError::BufferTooSmall));
+    }
+
+    #[test]
+    fn boxes_stop_at_walls_and_land_on_platforms() {
+        let mut tiles = [0u8; 40 * 15 * 2];
+        let map = demo(&mut tiles);
+        let size = Vec2::new(6.0, 8.0);
+        // Падение на пол: пол на ряду 14, ноги встают на y = 112
+        let fall = map.move_box(Vec2::new(20.0, 40.0), size, Vec2::new(0.0, 200.0));
+        assert_eq!(fall.position, Vec2::new(20.0, 104.0));
+        assert!(fall.blocked_y && !fall.blocked_x);
+        // Быстрый разбег влево упирается в стену, а не проскакивает её
+        let run = map.move_box(Vec2::new(20.0, 104.0), size, Vec2::new(-100.0, 0.0));
+        assert_eq!(run.position, Vec2::new(8.0, 104.0));
+        assert!(run.blocked_x);
+        // Платформа в ряду 9 (x 48..80): сверху на неё встают, снизу проходят насквозь
+        let land = map.move_box(Vec2::new(50.0, 60.0), size, Vec2::new(0.0, 30.0));
+        assert_eq!(land.position, Vec2::new(50.0, 64.0));
+        let jump = map.move_box(Vec2::new(50.0, 80.0), size, Vec2::new(0.0, -20.0));
+        assert!((jump.position.y - 60.0).abs() < 1e-3);
+        assert!(!jump.blocked_y);
+    }
+
+    #[test]
+    fn viewport_scrolls_by_sub_pixels() {
+        let mut tiles = [0u8; 40 * 15 * 2];
+        let map = demo(&mut tiles);
+        let sheet = SpriteSheet::new(tiles::DEMO, 8, 8);
+        let mut view = Viewport::new(16, 16);
+        // Четверть пикселя за шаг: окно сдвигается на пиксель за четыре шага
+        for step in 1..=4 {
+            view.remember();
+            view.scroll(Vec2::new(0.25, 0.0), &map);
+            assert_eq!(view.origin(1.0).0, step / 4);
+        }
+        assert_eq!(view.origin(0.0), (0, 0));
+        // Край карты не даёт уйти дальше
+        view.follow(Vec2::new(1000.0, 1000.0), &map);
+        assert_eq!(view.position, Vec2::new(304.0, 104.0));
+        view.remember();
+        assert_eq!(view.to_screen(Vec2::new(310.5, 110.0), 1.0), (6, 6));
+
+        let (mut px, mut depth) = ([0u32; 16 * 16], [0f32; 16 * 16]);
+        let mut fb = Framebuffer::from_slice(&mut px, 16, 16, &mut depth);
+        view.position = Vec2::new(3.5, 100.0);
+        view.remember();
+        map.draw_layer(&mut fb, &sheet, 1, &view, 1.0, BlitMode::ColorKey(0));
+        // Левая стена — кирпичный столбец x 0..8 карты, на экране 0..5; правее пусто
+        assert_eq!(px[0], 0xFFB04030);
+        assert_eq!(px[15 * 16 + 15], 0xFF606060);
+        assert_eq!(px[16 + 8], 0);
+    }
+}
@@ -0,0 +1,101 @@
+//! Фиксированный шаг симуляции.
+//!
+//! Реальное время кадра копится в аккумуляторе и расходуется шагами
+//! одинаковой длины, поэтому скорость игры не зависит от частоты кадров.
+//! Остаток меньше шага отдаётся рисованию как доля для интерполяции.
+
+use crate::abi::Game;
+use crate::input::InputState;
+
+pub struct FixedTimestep {
+    /// Длина шага, секунды
+    tick: f32,
+    /// Сколько шагов можно сделать за кадр, догоняя отставание
+    max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(tick: f32, max_steps: u32) -> Self {
+        assert!(tick > 0.0, "шаг должен быть положительным");
+        FixedTimestep { tick, max_steps: max_steps.max(1), accumulator: 0.0 }
+    }
+
+    pub fn tick(&self) -> f32 {
+        self.tick
+    }
+
+    /// Добавить прошедшее время и вернуть, сколько шагов сделать.
+    ///
+    /// Если отставание больше `max_steps` шагов (пауза отладчика, медленный хост),
+    /// лишнее время отбрасывается: игра замедляется, но не застревает в догонялках.
+    pub fn advance(&mut self, elapsed: f32) -> u32 {
+        // NaN и отрицательное время не двигают часы
+        if elapsed > 0.0 {
+            self.accumulator += elapsed;
+        }
+        let steps = (self.accumulator / self.tick) as u32;
+        if steps > self.max_steps {
+            self.accumulator = 0.0;
+            return self.max_steps;
+        }
+        self.accumulator -= steps as f32 * self.tick;
+        steps
+    }
+
+    /// Насколько текущий момент ушёл от последнего шага: 0.0 ..= 1.0
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.tick).clamp(0.0, 1.0)
+    }
+
+    /// Прогнать игру на `elapsed` секунд реального времени.
+    ///
+    /// Ввод кадра игра видит в первом шаге; если шагов не было, он
+    /// накапливается до следующего кадра, так что нажатия не теряются.
+    pub fn run<G: Game>(&mut self, game: &mut G, input: &mut InputState, elapsed: f32) {
+        for _ in 0..self.advance(elapsed) {
+            crate::profile_scope!("step");
+            game.update(input, self.tick);
+            input.begin_frame();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_do_not_depend_on_frame_rate() {
+        let mut slow = FixedTimestep::new(0.01, 10);
+        let mut fast = FixedTimestep::new(0.01, 10);
+        let (mut slow_steps, mut fast_steps) = (0, 0);
+        for _ in 0..10 {
+            slow_steps += slow.advance(0.05);
+        }
+        for _ in 0..100 {
+            fast_steps += fast.advance(0.005);
+        }
+        // Разница не больше одного шага, застрявшего в аккумуляторе из-за округления
+        assert!((49..=50).contains(&slow_steps), "{}", slow_steps);
+        assert!((49..=50).contains(&fast_steps), "{}", fast_steps);
+    }
+
+    #[test]
+    fn remainder_becomes_alpha() {
+        let mut t = FixedTimestep::new(0.1, 5);
+        assert_eq!(t.advance(0.25), 2);
+        assert!((t.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(t.advance(-1.0), 0);
+        assert_eq!(t.advance(f32::NAN), 0);
+        assert!((t.alpha() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn catch_up_is_capped() {
+        let mut t = FixedTimestep::new(0.1, 3);
+        assert_eq!(t.advance(10.0), 3);
+        assert_eq!(t.alpha(), 0.0);
+        assert_eq!(t.advance(0.1), 1);
+    }
+}
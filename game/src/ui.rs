@@ -0,0 +1,492 @@
+//! Интерфейс в непосредственном режиме: окна, надписи, кнопки, флажки,
+//! ползунки и списки для меню и отладочных панелей.
+//!
+//! Состояние виджетов живёт в данных игры, а интерфейс каждый кадр строится
+//! заново вызовами функций: `if frame.button("Заново") { ... }`. Ввод приходит
+//! в шагах логики, а рисование идёт в `render`, поэтому [`Ui::input`]
+//! вызывается из `Game::update` и копит нажатия до ближайшего кадра, а
+//! [`Ui::begin`] открывает кадр в `Game::render`.
+//!
+//! Мышь: наведение подсвечивает, щелчок нажимает, ползунок тянется.
+//! Клавиатура: Tab и Shift+Tab, а также стрелки вверх и вниз переводят фокус,
+//! Enter и пробел нажимают, стрелки влево и вправо двигают ползунок. Список в
+//! фокусе листает выбор стрелками вверх и вниз, уйти с него можно по Tab.
+
+use crate::font::{draw_text, text_width, ADVANCE, GLYPH_HEIGHT, LINE_HEIGHT};
+use crate::framebuffer::{Framebuffer, Rect};
+use crate::input::{InputState, Key, MouseButton};
+use crate::text::TextBuf;
+
+/// Высота строки виджета
+pub const ROW: i32 = LINE_HEIGHT as i32 + 4;
+/// Поля внутри окна и промежуток между виджетами
+const PADDING: i32 = 4;
+const SPACING: i32 = 2;
+/// Сторона квадрата флажка
+const CHECK: i32 = GLYPH_HEIGHT as i32;
+/// На какую долю диапазона ползунок сдвигается стрелкой
+const SLIDER_STEP: f32 = 0.05;
+
+/// Цвета интерфейса, 0xAARRGGBB; у фона окна альфа задаёт прозрачность
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    pub window: u32,
+    pub title: u32,
+    pub widget: u32,
+    pub hover: u32,
+    pub accent: u32,
+    pub text: u32,
+    pub border: u32,
+    /// Рамка вокруг виджета в фокусе
+    pub focus: u32,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        window: 0xD0182030,
+        title: 0xFF304870,
+        widget: 0xFF283448,
+        hover: 0xFF3A4A64,
+        accent: 0xFFE0A040,
+        text: 0xFFFFFFFF,
+        border: 0xFF5A6A84,
+        focus: 0xFFFFE080,
+    };
+}
+
+/// Нажатия, накопленные с прошлого кадра
+#[derive(Clone, Copy, Default)]
+struct Pending {
+    /// Где нажали левую кнопку мыши (первое нажатие)
+    click: Option<(i32, i32)>,
+    /// Перевод фокуса: +1 вперёд, −1 назад
+    step: i32,
+    /// Стрелки вверх и вниз: перевод фокуса или выбор в списке
+    vertical: i32,
+    /// Стрелки влево и вправо
+    horizontal: i32,
+    activate: bool,
+}
+
+/// Состояние интерфейса между кадрами: фокус, перетаскивание, накопленный ввод
+pub struct Ui {
+    pub theme: Theme,
+    mouse: (i32, i32),
+    pending: Pending,
+    /// Номер виджета в фокусе, по порядку вызова в кадре
+    focus: Option<u32>,
+    /// Ползунок, который тянут мышью
+    dragging: Option<u32>,
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Ui { theme: Theme::DARK, mouse: (0, 0), pending: Pending::default(), focus: None, dragging: None }
+    }
+
+    /// Учесть ввод шага логики; вызывается из `Game::update`.
+    ///
+    /// Возвращает `true`, если клавиатура досталась интерфейсу (у виджета фокус
+    /// или Tab его переводит): тогда игре не стоит видеть эти нажатия.
+    pub fn input(&mut self, input: &InputState) -> bool {
+        self.mouse = input.mouse();
+        if !input.mouse_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+        let p = &mut self.pending;
+        if p.click.is_none() && input.mouse_pressed(MouseButton::Left) {
+            p.click = Some(input.mouse());
+        }
+        if input.pressed(Key::TAB) {
+            let back = input.is_down(Key::LEFT_SHIFT) || input.is_down(Key::RIGHT_SHIFT);
+            p.step += if back { -1 } else { 1 };
+        }
+        p.vertical += input.pressed(Key::DOWN) as i32 - input.pressed(Key::UP) as i32;
+        p.horizontal += input.pressed(Key::RIGHT) as i32 - input.pressed(Key::LEFT) as i32;
+        p.activate |= input.pressed(Key::ENTER) || input.pressed(Key::SPACE);
+        self.focus.is_some() || input.pressed(Key::TAB)
+    }
+
+    /// Сбросить фокус, например при закрытии меню
+    pub fn unfocus(&mut self) {
+        self.focus = None;
+    }
+
+    /// Начать кадр интерфейса; виджеты раскладываются сверху вниз от левого
+    /// верхнего угла экрана, пока не открыто окно
+    pub fn begin<'a, 'b>(&'a mut self, fb: &'a mut Framebuffer<'b>) -> Frame<'a, 'b> {
+        let area = Rect::new(PADDING, PADDING, fb.width() as i32 - 2 * PADDING, fb.height() as i32 - 2 * PADDING);
+        Frame { ui: self, fb, area, cursor: area.y, count: 0, hit: false, list_focused: false }
+    }
+
+    /// Нажатие мыши пришлось на прямоугольник
+    fn clicked_in(&self, rect: Rect) -> bool {
+        self.pending.click.is_some_and(|(x, y)| rect.contains(x, y))
+    }
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Что произошло с виджетом в этом кадре
+struct Response {
+    rect: Rect,
+    id: u32,
+    hovered: bool,
+    /// Щелчок мышью или Enter в фокусе
+    clicked: bool,
+    focused: bool,
+}
+
+/// Кадр интерфейса. По окончании кадра (при удалении) применяются переходы
+/// фокуса, а накопленные нажатия сбрасываются.
+pub struct Frame<'a, 'b> {
+    ui: &'a mut Ui,
+    fb: &'a mut Framebuffer<'b>,
+    /// Куда раскладываются виджеты
+    area: Rect,
+    cursor: i32,
+    /// Сколько виджетов, принимающих фокус, уже вызвано
+    count: u32,
+    /// Щелчок пришёлся на виджет
+    hit: bool,
+    /// В фокусе список: стрелки вверх и вниз достались ему
+    list_focused: bool,
+}
+
+impl Frame<'_, '_> {
+    /// Окно с заголовком; следующие виджеты раскладываются внутри него
+    pub fn window(&mut self, title: &str, rect: Rect) {
+        let theme = self.ui.theme;
+        self.fb.blend_rect(rect, theme.window);
+        self.fb.fill_rect(Rect::new(rect.x, rect.y, rect.w, ROW), theme.title);
+        outline(self.fb, rect, theme.border);
+        let text = fit(title, rect.w - 2 * PADDING);
+        draw_text(self.fb, rect.x + PADDING, rect.y + 3, text, theme.text);
+        self.area = Rect::new(rect.x + PADDING, rect.y + ROW + PADDING, rect.w - 2 * PADDING, rect.h - ROW - 2 * PADDING);
+        self.cursor = self.area.y;
+        // Щелчок по окну не снимает фокус, даже если мимо виджетов
+        self.hit |= self.ui.clicked_in(rect);
+    }
+
+    /// Окно без заголовка
+    pub fn panel(&mut self, rect: Rect) {
+        let theme = self.ui.theme;
+        self.fb.blend_rect(rect, theme.window);
+        outline(self.fb, rect, theme.border);
+        self.area = Rect::new(rect.x + PADDING, rect.y + PADDING, rect.w - 2 * PADDING, rect.h - 2 * PADDING);
+        self.cursor = self.area.y;
+        self.hit |= self.ui.clicked_in(rect);
+    }
+
+    /// Пропустить `height` пикселей по вертикали
+    pub fn space(&mut self, height: i32) {
+        self.cursor += height;
+    }
+
+    pub fn label(&mut self, text: &str) {
+        let rect = self.next_rect(ROW);
+        draw_text(self.fb, rect.x, rect.y + 3, fit(text, rect.w), self.ui.theme.text);
+    }
+
+    /// Кнопка; `true`, когда её нажали
+    pub fn button(&mut self, text: &str) -> bool {
+        let r = self.widget(ROW);
+        let theme = self.ui.theme;
+        self.fb.fill_rect(r.rect, if r.hovered { theme.hover } else { theme.widget });
+        outline(self.fb, r.rect, if r.focused { theme.focus } else { theme.border });
+        let text = fit(text, r.rect.w - 2 * PADDING);
+        let x = r.rect.x + (r.rect.w - text_width(text) as i32) / 2;
+        draw_text(self.fb, x, r.rect.y + 3, text, theme.text);
+        r.clicked
+    }
+
+    /// Флажок с подписью; `true`, когда значение изменилось
+    pub fn checkbox(&mut self, text: &str, value: &mut bool) -> bool {
+        let r = self.widget(ROW);
+        let theme = self.ui.theme;
+        if r.clicked {
+            *value = !*value;
+        }
+        if r.focused || r.hovered {
+            self.fb.fill_rect(r.rect, theme.hover);
+        }
+        if r.focused {
+            outline(self.fb, r.rect, theme.focus);
+        }
+        let check = Rect::new(r.rect.x + 2, r.rect.y + (ROW - CHECK) / 2, CHECK, CHECK);
+        self.fb.fill_rect(check, theme.widget);
+        outline(self.fb, check, theme.border);
+        if *value {
+            self.fb.fill_rect(Rect::new(check.x + 2, check.y + 2, CHECK - 4, CHECK - 4), theme.accent);
+        }
+        let x = check.x + CHECK + PADDING;
+        draw_text(self.fb, x, r.rect.y + 3, fit(text, r.rect.x + r.rect.w - x), theme.text);
+        r.clicked
+    }
+
+    /// Ползунок для значения от `min` до `max`, поверх — подпись и значение;
+    /// `true`, когда значение изменилось
+    pub fn slider(&mut self, text: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let r = self.widget(ROW);
+        let theme = self.ui.theme;
+        let before = *value;
+        if self.ui.clicked_in(r.rect) {
+            self.ui.dragging = Some(r.id);
+        }
+        if self.ui.dragging == Some(r.id) && r.rect.w > 1 {
+            let t = (self.ui.mouse.0 - r.rect.x) as f32 / (r.rect.w - 1) as f32;
+            *value = min + (max - min) * t.clamp(0.0, 1.0);
+        }
+        if r.focused {
+            *value += (max - min) * SLIDER_STEP * self.ui.pending.horizontal as f32;
+        }
+        *value = value.clamp(min.min(max), max.max(min));
+
+        self.fb.fill_rect(r.rect, if r.hovered { theme.hover } else { theme.widget });
+        let t = if max != min { (*value - min) / (max - min) } else { 0.0 };
+        let filled = (r.rect.w as f32 * t) as i32;
+        self.fb.fill_rect(Rect::new(r.rect.x, r.rect.y + ROW - 3, filled, 3), theme.accent);
+        outline(self.fb, r.rect, if r.focused { theme.focus } else { theme.border });
+        let mut line = TextBuf::<48>::new();
+        line.push_str(text);
+        line.push_str(" ");
+        line.push_float(*value, 2);
+        draw_text(self.fb, r.rect.x + PADDING, r.rect.y + 2, fit(line.as_str(), r.rect.w - 2 * PADDING), theme.text);
+        *value != before
+    }
+
+    /// Список из `rows` видимых строк с выбранным элементом `selected`;
+    /// `true`, когда выбор изменился
+    pub fn list(&mut self, items: &[&str], selected: &mut usize, rows: usize) -> bool {
+        let rows = rows.max(1);
+        let r = self.widget(rows as i32 * LINE_HEIGHT as i32 + 4);
+        let theme = self.ui.theme;
+        let before = *selected;
+        if r.focused {
+            self.list_focused = true;
+            let moved = *selected as i32 + self.ui.pending.vertical;
+            *selected = moved.clamp(0, items.len() as i32 - 1).max(0) as usize;
+        }
+        // Прокручиваем так, чтобы выбранный элемент был виден
+        let first = (*selected + 1).saturating_sub(rows);
+        let line = |i: usize| Rect::new(r.rect.x + 1, r.rect.y + 2 + (i * LINE_HEIGHT) as i32, r.rect.w - 2, LINE_HEIGHT as i32);
+        if let Some((mx, my)) = self.ui.pending.click {
+            if let Some(i) = (0..rows).find(|&i| line(i).contains(mx, my)) {
+                if first + i < items.len() {
+                    *selected = first + i;
+                }
+            }
+        }
+        self.fb.fill_rect(r.rect, theme.widget);
+        for (i, item) in items.iter().enumerate().skip(first).take(rows) {
+            let rect = line(i - first);
+            if i == *selected {
+                self.fb.fill_rect(rect, theme.hover);
+            }
+            let color = if i == *selected { theme.accent } else { theme.text };
+            draw_text(self.fb, rect.x + PADDING - 1, rect.y + 1, fit(item, rect.w - 2 * PADDING), color);
+        }
+        outline(self.fb, r.rect, if r.focused { theme.focus } else { theme.border });
+        *selected != before
+    }
+
+    /// Место под следующий виджет
+    fn next_rect(&mut self, height: i32) -> Rect {
+        let rect = Rect::new(self.area.x, self.cursor, self.area.w, height);
+        self.cursor += height + SPACING;
+        rect
+    }
+
+    /// Место под виджет, принимающий фокус, и что с ним сделали
+    fn widget(&mut self, height: i32) -> Response {
+        let rect = self.next_rect(height);
+        let id = self.count;
+        self.count += 1;
+        let ui = &mut *self.ui;
+        let hovered = rect.contains(ui.mouse.0, ui.mouse.1);
+        let mut clicked = false;
+        if ui.clicked_in(rect) {
+            ui.focus = Some(id);
+            self.hit = true;
+            clicked = true;
+        }
+        let focused = ui.focus == Some(id);
+        if focused && ui.pending.activate {
+            clicked = true;
+        }
+        Response { rect, id, hovered, clicked, focused }
+    }
+}
+
+impl Drop for Frame<'_, '_> {
+    fn drop(&mut self) {
+        let ui = &mut *self.ui;
+        let mut step = ui.pending.step;
+        if !self.list_focused {
+            step += ui.pending.vertical;
+        }
+        if ui.pending.click.is_some() && !self.hit {
+            ui.focus = None;
+        }
+        if self.count > 0 && step != 0 {
+            let count = self.count as i32;
+            let from = match ui.focus {
+                Some(id) => id as i32,
+                // Без фокуса первый шаг вперёд попадает на первый виджет, назад — на последний
+                None if step > 0 => -1,
+                None => count,
+            };
+            ui.focus = Some((from + step).rem_euclid(count) as u32);
+        } else if ui.focus.is_some_and(|id| id >= self.count) {
+            // Виджет в фокусе исчез из интерфейса
+            ui.focus = None;
+        }
+        ui.pending = Pending::default();
+    }
+}
+
+/// Рамка толщиной в пиксель по краю прямоугольника
+fn outline(fb: &mut Framebuffer<'_>, r: Rect, color: u32) {
+    if r.is_empty() {
+        return;
+    }
+    fb.fill_rect(Rect::new(r.x, r.y, r.w, 1), color);
+    fb.fill_rect(Rect::new(r.x, r.y + r.h - 1, r.w, 1), color);
+    fb.fill_rect(Rect::new(r.x, r.y, 1, r.h), color);
+    fb.fill_rect(Rect::new(r.x + r.w - 1, r.y, 1, r.h), color);
+}
+
+/// Начало строки, которое помещается в `width` пикселей
+fn fit(text: &str, width: i32) -> &str {
+    let chars = ((width + 1).max(0) as usize) / ADVANCE;
+    match text.char_indices().nth(chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Event;
+
+    const W: usize = 120;
+    const H: usize = 100;
+
+    /// Панель отладки: кнопка, флажок, ползунок, список
+    struct Panel {
+        clicks: u32,
+        enabled: bool,
+        speed: f32,
+        mode: usize,
+    }
+
+    fn draw(ui: &mut Ui, panel: &mut Panel, px: &mut [u32]) {
+        let mut depth = [0f32; W * H];
+        let mut fb = Framebuffer::from_slice(px, W, H, &mut depth);
+        fb.clear(0xFF000000);
+        let mut frame = ui.begin(&mut fb);
+        frame.window("Отладка", Rect::new(0, 0, W as i32, H as i32));
+        frame.label("FPS 60");
+        if frame.button("Сброс") {
+            panel.clicks += 1;
+        }
+        frame.checkbox("Тени", &mut panel.enabled);
+        frame.slider("Скорость", &mut panel.speed, 0.0, 2.0);
+        frame.list(&["Каркас", "Плоско", "Гуро"], &mut panel.mode, 2);
+    }
+
+    /// Шаг логики с событиями; ввод живёт между шагами, как в `FixedTimestep`
+    fn step(ui: &mut Ui, input: &mut InputState, events: &[Event]) {
+        for &event in events {
+            input.push(event);
+        }
+        ui.input(input);
+        input.begin_frame();
+    }
+
+    #[test]
+    fn mouse_drives_widgets() {
+        let (mut ui, mut input) = (Ui::new(), InputState::new());
+        let mut panel = Panel { clicks: 0, enabled: false, speed: 1.0, mode: 0 };
+        let mut px = [0u32; W * H];
+        // Окно: заголовок 14, затем строки по 14 с промежутком 2 от y = 18
+        let row = |i: i32| 18 + i * (ROW + SPACING) + ROW / 2;
+        let click = |x: i32, y: i32| [Event::MouseMove { x, y }, Event::MouseDown(MouseButton::Left), Event::MouseUp(MouseButton::Left)];
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(px[W * 5 + 60], Theme::DARK.title);
+
+        // Щелчок, пришедший в одном из нескольких шагов, засчитывается один раз
+        step(&mut ui, &mut input, &click(60, row(1)));
+        step(&mut ui, &mut input, &[]);
+        draw(&mut ui, &mut panel, &mut px);
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(panel.clicks, 1);
+
+        step(&mut ui, &mut input, &click(60, row(2)));
+        draw(&mut ui, &mut panel, &mut px);
+        assert!(panel.enabled);
+        // Галочка закрашена цветом акцента
+        assert_eq!(px[W * row(2) as usize + 4 + 4], Theme::DARK.accent);
+
+        // Ползунок тянется за мышью, пока кнопка нажата
+        step(&mut ui, &mut input, &[Event::MouseMove { x: 4, y: row(3) }, Event::MouseDown(MouseButton::Left)]);
+        step(&mut ui, &mut input, &[Event::MouseMove { x: 200, y: 0 }]);
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(panel.speed, 2.0);
+        step(&mut ui, &mut input, &[Event::MouseUp(MouseButton::Left), Event::MouseMove { x: 4, y: row(3) }]);
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(panel.speed, 2.0);
+
+        // Вторая видимая строка списка
+        step(&mut ui, &mut input, &click(60, row(4) - ROW / 2 + 2 + LINE_HEIGHT as i32 + 3));
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(panel.mode, 1);
+    }
+
+    #[test]
+    fn keyboard_moves_focus_and_changes_values() {
+        let (mut ui, mut input) = (Ui::new(), InputState::new());
+        let mut panel = Panel { clicks: 0, enabled: false, speed: 1.0, mode: 0 };
+        let mut px = [0u32; W * H];
+        let tap = |key: Key| [Event::KeyDown(key), Event::KeyUp(key)];
+        draw(&mut ui, &mut panel, &mut px);
+        // Tab — на кнопку, Enter её нажимает
+        step(&mut ui, &mut input, &tap(Key::TAB));
+        draw(&mut ui, &mut panel, &mut px);
+        step(&mut ui, &mut input, &tap(Key::ENTER));
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(panel.clicks, 1);
+        // Вниз на флажок, пробел его ставит; ещё вниз — ползунок, вправо его двигает
+        step(&mut ui, &mut input, &tap(Key::DOWN));
+        draw(&mut ui, &mut panel, &mut px);
+        step(&mut ui, &mut input, &tap(Key::SPACE));
+        draw(&mut ui, &mut panel, &mut px);
+        step(&mut ui, &mut input, &tap(Key::DOWN));
+        draw(&mut ui, &mut panel, &mut px);
+        step(&mut ui, &mut input, &tap(Key::RIGHT));
+        step(&mut ui, &mut input, &tap(Key::RIGHT));
+        draw(&mut ui, &mut panel, &mut px);
+        assert!(panel.enabled);
+        assert!((panel.speed - 1.2).abs() < 1e-5);
+        // В списке стрелки листают выбор, а не фокус
+        step(&mut ui, &mut input, &tap(Key::DOWN));
+        draw(&mut ui, &mut panel, &mut px);
+        for _ in 0..3 {
+            step(&mut ui, &mut input, &tap(Key::DOWN));
+            draw(&mut ui, &mut panel, &mut px);
+        }
+        assert_eq!(panel.mode, 2);
+        // Tab с последнего виджета возвращает на первый
+        step(&mut ui, &mut input, &tap(Key::TAB));
+        draw(&mut ui, &mut panel, &mut px);
+        step(&mut ui, &mut input, &tap(Key::ENTER));
+        draw(&mut ui, &mut panel, &mut px);
+        assert_eq!(panel.clicks, 2);
+        assert_eq!(fit("Скорость", 20), "Ско");
+    }
+}
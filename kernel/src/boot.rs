@@ -0,0 +1,73 @@
+//! Параметры загрузки, которые VMM кладёт в память гостя
+
+use game::audio::AudioInfo;
+use game::framebuffer::{FramebufferInfo, PixelFormat};
+use game::profile::ProfileInfo;
+
+/// Физический адрес структуры `BootInfo`
+pub const BOOT_INFO_ADDR: usize = 0x1FF0_0000;
+
+/// "NGBI" в little-endian
+pub const BOOT_MAGIC: u32 = 0x4942_474E;
+pub const BOOT_VERSION: u32 = 5;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BootInfo {
+    pub magic: u32,
+    pub version: u32,
+    pub framebuffer: FramebufferInfo,
+    /// Свободная память, которую ядро отдаёт игре
+    pub scratch_base: u64,
+    pub scratch_size: u64,
+    /// Номер игры в списке `games::list()`
+    pub game: u32,
+    pub _reserved: u32,
+    /// Частота счётчика тактов (RDTSC), Гц; 0 — хост не знает, см. `clock`
+    pub tsc_hz: u64,
+    /// Кольцевой буфер звука; `base` = 0 — звука нет
+    pub audio: AudioInfo,
+    /// Кольцевой буфер профилировщика; `base` = 0 — профилировщика нет
+    pub profile: ProfileInfo,
+}
+
+/// Параметры по умолчанию, если хост ничего не передал (640x480 по адресу 0x2000_0000)
+const DEFAULT_BOOT_INFO: BootInfo = BootInfo {
+    magic: BOOT_MAGIC,
+    version: BOOT_VERSION,
+    framebuffer: FramebufferInfo {
+        base: 0x2000_0000,
+        width: 640,
+        height: 480,
+        stride: 640 * 4,
+        format: PixelFormat::Xrgb8888,
+    },
+    scratch_base: 0x0200_0000,
+    scratch_size: 0x0400_0000,
+    game: 0,
+    _reserved: 0,
+    tsc_hz: 0,
+    audio: AudioInfo::NONE,
+    profile: ProfileInfo::NONE,
+};
+
+/// Прочитать параметры загрузки от хоста
+pub fn boot_info() -> BootInfo {
+    let ptr = BOOT_INFO_ADDR as *const BootInfo;
+    // Сначала проверяем заголовок: формат пикселя нельзя читать как enum, пока не ясно,
+    // что структура действительно заполнена хостом
+    let (magic, version) = unsafe {
+        (
+            core::ptr::addr_of!((*ptr).magic).read_volatile(),
+            core::ptr::addr_of!((*ptr).version).read_volatile(),
+        )
+    };
+    if magic != BOOT_MAGIC || version != BOOT_VERSION {
+        return DEFAULT_BOOT_INFO;
+    }
+    let format = unsafe { (core::ptr::addr_of!((*ptr).framebuffer.format) as *const u32).read_volatile() };
+    if format > PixelFormat::Xbgr8888 as u32 {
+        return DEFAULT_BOOT_INFO;
+    }
+    unsafe { ptr.read_volatile() }
+}
@@ -0,0 +1,46 @@
+//! Реальное время гостя по счётчику тактов процессора (RDTSC)
+
+use core::arch::x86_64::{__cpuid, _rdtsc};
+
+/// Частота, если её не знают ни хост, ни CPUID; грубая оценка для современных CPU
+const FALLBACK_TSC_HZ: u64 = 2_000_000_000;
+
+/// Частота TSC по CPUID: лист 0x15 (отношение к частоте кварца) или 0x16 (базовая частота)
+fn cpuid_tsc_hz() -> Option<u64> {
+    let max_leaf = __cpuid(0).eax;
+    if max_leaf >= 0x15 {
+        let r = __cpuid(0x15);
+        if r.eax != 0 && r.ebx != 0 && r.ecx != 0 {
+            return Some(r.ecx as u64 * r.ebx as u64 / r.eax as u64);
+        }
+    }
+    if max_leaf >= 0x16 {
+        let mhz = __cpuid(0x16).eax & 0xFFFF;
+        if mhz != 0 {
+            return Some(mhz as u64 * 1_000_000);
+        }
+    }
+    None
+}
+
+/// Меряет время между вызовами `elapsed`
+pub struct Clock {
+    hz: u64,
+    last: u64,
+}
+
+impl Clock {
+    /// `tsc_hz` — частота от хоста; 0 — определить самому
+    pub fn new(tsc_hz: u64) -> Self {
+        let hz = if tsc_hz != 0 { tsc_hz } else { cpuid_tsc_hz().unwrap_or(FALLBACK_TSC_HZ) };
+        Clock { hz, last: unsafe { _rdtsc() } }
+    }
+
+    /// Секунды с прошлого вызова (или с создания)
+    pub fn elapsed(&mut self) -> f32 {
+        let now = unsafe { _rdtsc() };
+        let ticks = now.wrapping_sub(self.last);
+        self.last = now;
+        (ticks as f64 / self.hz as f64) as f32
+    }
+}
@@ -0,0 +1,39 @@
+//! Список игр, слинкованных в ядро (секция `.game_modules`, см. linker.ld)
+
+use game::abi::GameModule;
+
+extern "C" {
+    static __game_modules_start: GameModule;
+    static __game_modules_end: GameModule;
+}
+
+/// Все игровые модули в порядке линковки, включая несовместимые
+pub fn list() -> &'static [GameModule] {
+    unsafe {
+        let start = core::ptr::addr_of!(__game_modules_start);
+        let end = core::ptr::addr_of!(__game_modules_end);
+        let len = (end as usize - start as usize) / core::mem::size_of::<GameModule>();
+        core::slice::from_raw_parts(start, len)
+    }
+}
+
+pub enum SelectError {
+    /// Модуля с таким номером нет
+    NotFound,
+    /// Модуль собран под другую версию ABI
+    AbiMismatch,
+}
+
+/// Выбрать игру по номеру в списке, отвергая модули с чужой версией ABI
+pub fn select(index: usize) -> Result<&'static GameModule, SelectError> {
+    let module = list().get(index).ok_or(SelectError::NotFound)?;
+    if !module.is_compatible() {
+        return Err(SelectError::AbiMismatch);
+    }
+    Ok(module)
+}
+
+/// Первая совместимая игра
+pub fn first_compatible() -> Option<&'static GameModule> {
+    list().iter().find(|m| m.is_compatible())
+}
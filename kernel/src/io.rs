@@ -0,0 +1,41 @@
+//! Порты ввода-вывода устройств VMM.
+//! Номера портов должны совпадать с vmm/src/main.rs.
+
+use core::arch::asm;
+
+/// Запись сюда сообщает VMM, что в видимом буфере готов новый кадр.
+/// Значение — номер кадра.
+pub const PRESENT_PORT: u16 = 0x0E00;
+
+/// Чтение отсюда возвращает следующее событие ввода (`game::input::Event::decode`)
+/// или 0, если очередь устройства пуста
+pub const INPUT_PORT: u16 = 0x0E04;
+
+/// Запись сюда сообщает VMM, что в кольце звука появились новые отсчёты.
+/// Значение — сколько отсчётов записано с начала работы.
+pub const AUDIO_PORT: u16 = 0x0E08;
+
+/// Запись сюда сообщает VMM, что в кольце профилировщика появились новые записи.
+/// Значение — сколько записей сделано с начала работы.
+pub const PROFILE_PORT: u16 = 0x0E0C;
+
+/// Записать 32-битное слово в порт; вызывает выход из гостя в VMM.
+///
+/// Без `nomem`: запись в порт сообщает VMM, что память (кадр, кольца) готова,
+/// поэтому компилятор не должен переносить через неё обращения к памяти.
+#[inline]
+pub fn outl(port: u16, value: u32) {
+    unsafe {
+        asm!("out dx, eax", in("dx") port, in("eax") value, options(nostack, preserves_flags));
+    }
+}
+
+/// Прочитать 32-битное слово из порта; вызывает выход из гостя в VMM
+#[inline]
+pub fn inl(port: u16) -> u32 {
+    let value: u32;
+    unsafe {
+        asm!("in eax, dx", in("dx") port, out("eax") value, options(nomem, nostack, preserves_flags));
+    }
+    value
+}
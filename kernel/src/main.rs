@@ -3,8 +3,17 @@
 
 extern crate game;
 
+mod boot;
+mod clock;
+mod games;
+mod io;
+
 use core::panic::PanicInfo;
 use core::arch::asm;
+use game::{profile, profile_scope};
+
+/// Сколько событий ввода ядро забирает у VMM за кадр
+const MAX_EVENTS_PER_FRAME: usize = 64;
 
 #[panic_handler]
 fn panic(_info: &PanicInfo) -> ! {
@@ -13,10 +22,61 @@ fn panic(_info: &PanicInfo) -> ! {
 
 #[no_mangle]
 pub extern "C" fn _start() -> ! {
-    game::init();
+    let boot = boot::boot_info();
+    unsafe { profile::install(&boot.profile) };
+    // Игру выбирает хост; если модуль не найден или собран под другое ABI,
+    // запускаем первую совместимую
+    let module = match games::select(boot.game as usize) {
+        Ok(module) => module,
+        Err(_) => match games::first_compatible() {
+            Some(module) => module,
+            None => panic!("нет игр с ABI версии {}", game::abi::GAME_ABI_VERSION),
+        },
+    };
+    let state = unsafe {
+        (module.init)(&boot.framebuffer, &boot.audio, boot.scratch_base as *mut u8, boot.scratch_size as usize)
+    };
+    if state.is_null() {
+        panic!("игре «{}» не хватило памяти", module.name());
+    }
+    let mut clock = clock::Clock::new(boot.tsc_hz);
+    let mut frame = 0u32;
+    let mut samples = 0u32;
+    let mut records = 0u32;
     loop {
-        game::update(0.016);
-        game::render();
+        {
+            profile_scope!("frame");
+            // Забрать накопившиеся события ввода; не больше MAX_EVENTS_PER_FRAME,
+            // чтобы поток событий не задерживал кадр
+            for _ in 0..MAX_EVENTS_PER_FRAME {
+                let event = io::inl(io::INPUT_PORT);
+                if event == 0 {
+                    break;
+                }
+                unsafe { (module.input)(state, event) };
+            }
+            // Игра сама разбивает реальное время на фиксированные шаги
+            unsafe {
+                (module.update)(state, clock.elapsed());
+                (module.render)(state);
+                // Кадр целиком скопирован в видимый буфер — только теперь VMM его забирает
+                (module.present)(state);
+            }
+        }
+        frame = frame.wrapping_add(1);
+        io::outl(io::PRESENT_PORT, frame);
+        // Звук за кадр уже в кольце; VMM забирает его по звонку
+        let written = unsafe { (module.audio)(state) };
+        if written != samples {
+            samples = written;
+            io::outl(io::AUDIO_PORT, samples);
+        }
+        // Участки кадра уже в кольце профилировщика
+        let written = profile::written();
+        if written != records {
+            records = written;
+            io::outl(io::PROFILE_PORT, records);
+        }
         unsafe { asm!("hlt"); }
     }
 }
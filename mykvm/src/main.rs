@@ -6,6 +6,7 @@ use std::fs;
 use std::thread;
 use std::os::unix::prelude::FromRawFd;
 use std::os::raw::c_ulong;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use std::io::Write as _;
@@ -67,24 +68,33 @@ fn main() {
                                     log!("[mykvm] framebuffer header: {:?} ascii: {}", header, header_ascii);
                                     if &header == b"FRAMEBUFFER" {
                                         log!("[mykvm] FRAMEBUFFER command received (header)");
-                                        let mut fb = vec![0u8; 640*480*4];
+                                        // Следом идут ширина, высота и формат пикселя (u32 LE)
+                                        let mut dims = [0u8; 12];
+                                        if let Err(e) = stream.read_exact(&mut dims) {
+                                            log!("[mykvm] framebuffer dims read error: {}", e);
+                                            break;
+                                        }
+                                        let width = u32::from_le_bytes(dims[0..4].try_into().unwrap()) as usize;
+                                        let height = u32::from_le_bytes(dims[4..8].try_into().unwrap()) as usize;
+                                        let format = u32::from_le_bytes(dims[8..12].try_into().unwrap());
+                                        log!("[mykvm] framebuffer {}x{} format {}", width, height, format);
+                                        if width == 0 || height == 0 || width > MAX_FB_SIDE || height > MAX_FB_SIDE {
+                                            log!("[mykvm] framebuffer size rejected");
+                                            break;
+                                        }
+                                        let mut fb = vec![0u8; width*height*4];
                                         if let Err(e) = stream.read_exact(&mut fb) {
                                             log!("[mykvm] framebuffer read error: {}", e);
                                             break;
                                         }
                                         let path = "framebuffer_dump.ppm";
-                                        if let Ok(mut file) = std::fs::File::create(&path) {
-                                            // Корректный PPM: P6\n640 480\n255\n
-                                            let _ = file.write_all(b"P6\n640 480\n255\n");
-                                            for px in fb.chunks(4) {
-                                                let _ = file.write_all(&px[0..3]);
+                                        match save_ppm(path, &fb, width, height, format) {
+                                            Ok(()) => {
+                                                log!("[mykvm] framebuffer_dump.ppm saved (on FRAMEBUFFER)");
+                                                let _ = std::process::Command::new("/home/rutasan/NeuroGame/mykvm/viewer/target/debug/viewer")
+                                                    .spawn();
                                             }
-                                            file.flush().ok();
-                                            log!("[mykvm] framebuffer_dump.ppm saved (on FRAMEBUFFER)");
-                                            let _ = std::process::Command::new("/home/rutasan/NeuroGame/mykvm/viewer/target/debug/viewer")
-                                                .spawn();
-                                        } else {
-                                            log!("[mykvm] failed to save framebuffer_dump.ppm");
+                                            Err(e) => log!("[mykvm] failed to save framebuffer_dump.ppm: {}", e),
                                         }
                                         continue;
                                     }
@@ -107,6 +117,26 @@ fn main() {
     }
 }
 
+/// Наибольшая допустимая сторона кадра, защищает от мусора в заголовке
+const MAX_FB_SIDE: usize = 8192;
+
+/// Сохранить кадр гостя в PPM (P6). `format` — game::framebuffer::PixelFormat:
+/// 0 — XRGB8888 (в памяти B, G, R, X), 1 — XBGR8888 (в памяти R, G, B, X).
+fn save_ppm(path: &str, fb: &[u8], width: usize, height: usize, format: u32) -> std::io::Result<()> {
+    let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    data.reserve(width * height * 3);
+    for px in fb.chunks(4) {
+        match format {
+            0 => data.extend_from_slice(&[px[2], px[1], px[0]]),
+            _ => data.extend_from_slice(&px[0..3]),
+        }
+    }
+    // Пишем во временный файл и переименовываем, чтобы viewer не прочитал половину кадра
+    let tmp = format!("{}.tmp", path);
+    std::fs::write(&tmp, &data)?;
+    std::fs::rename(&tmp, path)
+}
+
 // Минимальные константы ioctls
 const KVM_CREATE_VM: c_ulong = 0xAE01;
 const KVM_CREATE_VCPU: c_ulong = 0xAE41;
@@ -115,6 +145,10 @@ const KVM_GET_REGS: c_ulong = 0x8090AE81;
 const KVM_SET_USER_MEMORY_REGION: c_ulong = 0xAE02;
 const KVM_SET_REGS: c_ulong = 0xAE40;
 const KVM_RUN: c_ulong = 0xAE44;
+const KVM_EXIT_IO: u32 = 2;
+const KVM_EXIT_IO_OUT: u8 = 1;
+/// Порт, записью в который гость сообщает о готовом кадре (vmm/src/main.rs)
+const PRESENT_PORT: u16 = 0x0E00;
 
 #[repr(C, align(8))]
 struct KvmSregs {
@@ -152,6 +186,8 @@ struct State {
     vms: Mutex<HashMap<u64, Arc<Mutex<Vm>>>>,
     next_vm_id: Mutex<u64>,
     next_vcpu_id: Mutex<u64>,
+    /// Сколько кадров «показал» гость
+    frames: AtomicU32,
 }
 
 impl State {
@@ -160,6 +196,7 @@ impl State {
             vms: Mutex::new(HashMap::new()),
             next_vm_id: Mutex::new(1),
             next_vcpu_id: Mutex::new(1),
+            frames: AtomicU32::new(0),
         }
     }
 }
@@ -249,29 +286,20 @@ fn handle_ioctl(req: c_ulong, stream: &mut std::os::unix::net::UnixStream, state
             let _ = stream.flush();
         }
         KVM_RUN => {
-            // Примитивная эмуляция: копируем framebuffer из памяти гостя и сохраняем в файл
-            let vms = state.vms.lock().unwrap();
-            if let Some(vm) = vms.values().next() {
-                let vm = vm.lock().unwrap();
-                // Параметры framebuffer
-                let fb_offset = 0x2000_0000 - 0x100000; // guest_phys_addr - base
-                let fb_size = 640 * 480 * 4;
-                if vm.memory.len() > fb_offset + fb_size {
-                    let fb = &vm.memory[fb_offset..fb_offset+fb_size];
-                    let path = format!("framebuffer_dump.ppm");
-                    let mut file = std::fs::File::create(&path).unwrap();
-                    // Корректный PPM: P6\n640 480\n255\n
-                    let _ = file.write_all(b"P6\n640 480\n255\n");
-                    for px in fb.chunks(4) {
-                        let _ = file.write_all(&px[0..3]);
-                    }
-                    file.flush().ok();
-                    log!("[mykvm] framebuffer_dump.ppm saved");
-                }
-            }
-            let exit_reason = 5u32; // KVM_EXIT_HLT
-            let mut resp = [0u8; 4];
-            resp[..4].copy_from_slice(&exit_reason.to_le_bytes());
+            // Примитивная эмуляция: гостя не исполняем, кадры приходят командой FRAMEBUFFER
+            // вместе с размером, поэтому здесь ничего не дампим.
+            // Аргумент: vcpu_id u64 и io_in u32 (ответ на прошлое чтение порта, здесь не нужен).
+            // Ответ: exit_reason u32, port u16, direction u8, size u8, data u32.
+            // Считаем, что гость каждый раз заканчивает кадр записью в порт present.
+            let mut arg = [0u8; 12];
+            let _ = stream.read_exact(&mut arg);
+            let frame = state.frames.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut resp = [0u8; 12];
+            resp[..4].copy_from_slice(&KVM_EXIT_IO.to_le_bytes());
+            resp[4..6].copy_from_slice(&PRESENT_PORT.to_le_bytes());
+            resp[6] = KVM_EXIT_IO_OUT;
+            resp[7] = 4;
+            resp[8..12].copy_from_slice(&frame.to_le_bytes());
             let _ = stream.write_all(&resp);
             let _ = stream.flush();
         }
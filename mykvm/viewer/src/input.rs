@@ -0,0 +1,145 @@
+//! Пересылка ввода из окна в VMM.
+//!
+//! Каждое событие — 32-битное слово little-endian в формате `game::input::Event`
+//! (game/src/input.rs): старшие 4 бита — вид события, остальные — данные.
+//! Клавиши кодируются номерами USB HID.
+
+use minifb::{InputCallback, Key, KeyRepeat, MouseButton, MouseMode, Window};
+use std::os::unix::net::UnixDatagram;
+use std::sync::{Arc, Mutex};
+
+/// Сокет устройства ввода VMM (vmm/src/input.rs)
+const INPUT_SOCKET: &str = "/tmp/vmm_input.sock";
+
+const KIND_KEY_DOWN: u32 = 1;
+const KIND_KEY_UP: u32 = 2;
+const KIND_TEXT: u32 = 3;
+const KIND_MOUSE_MOVE: u32 = 4;
+const KIND_MOUSE_DOWN: u32 = 5;
+const KIND_MOUSE_UP: u32 = 6;
+const KIND_WHEEL: u32 = 7;
+
+fn event(kind: u32, payload: u32) -> u32 {
+    kind << 28 | (payload & 0x0FFF_FFFF)
+}
+
+/// Код USB HID для клавиши minifb; `None` для клавиш, которые игре не передаём
+fn hid_code(key: Key) -> Option<u8> {
+    use Key::*;
+    const LETTERS: [Key; 26] = [A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z];
+    const DIGITS: [Key; 10] = [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0];
+    const FUNCTION: [Key; 12] = [F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12];
+    if let Some(i) = LETTERS.iter().position(|&k| k == key) {
+        return Some(0x04 + i as u8);
+    }
+    if let Some(i) = DIGITS.iter().position(|&k| k == key) {
+        return Some(0x1E + i as u8);
+    }
+    if let Some(i) = FUNCTION.iter().position(|&k| k == key) {
+        return Some(0x3A + i as u8);
+    }
+    Some(match key {
+        Enter => 0x28,
+        Escape => 0x29,
+        Backspace => 0x2A,
+        Tab => 0x2B,
+        Space => 0x2C,
+        Minus => 0x2D,
+        Equal => 0x2E,
+        LeftBracket => 0x2F,
+        RightBracket => 0x30,
+        Backslash => 0x31,
+        Semicolon => 0x33,
+        Apostrophe => 0x34,
+        Backquote => 0x35,
+        Comma => 0x36,
+        Period => 0x37,
+        Slash => 0x38,
+        Insert => 0x49,
+        Home => 0x4A,
+        PageUp => 0x4B,
+        Delete => 0x4C,
+        End => 0x4D,
+        PageDown => 0x4E,
+        Right => 0x4F,
+        Left => 0x50,
+        Down => 0x51,
+        Up => 0x52,
+        LeftCtrl => 0xE0,
+        LeftShift => 0xE1,
+        LeftAlt => 0xE2,
+        RightCtrl => 0xE4,
+        RightShift => 0xE5,
+        RightAlt => 0xE6,
+        _ => return None,
+    })
+}
+
+/// Собирает введённые символы из окна
+struct TextInput(Arc<Mutex<Vec<u32>>>);
+
+impl InputCallback for TextInput {
+    fn add_char(&mut self, uni_char: u32) {
+        self.0.lock().unwrap().push(event(KIND_TEXT, uni_char));
+    }
+}
+
+/// Следит за окном и отправляет изменения ввода в VMM
+pub struct InputForwarder {
+    socket: UnixDatagram,
+    text: Arc<Mutex<Vec<u32>>>,
+    mouse: Option<(u32, u32)>,
+    buttons: [bool; 3],
+}
+
+impl InputForwarder {
+    pub fn new(window: &mut Window) -> std::io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        let text = Arc::new(Mutex::new(Vec::new()));
+        window.set_input_callback(Box::new(TextInput(text.clone())));
+        Ok(InputForwarder { socket, text, mouse: None, buttons: [false; 3] })
+    }
+
+    /// Собрать события за кадр окна и отправить одной датаграммой.
+    /// Если VMM не запущен, события просто теряются.
+    pub fn forward(&mut self, window: &Window) {
+        let mut events = Vec::new();
+        for key in window.get_keys_pressed(KeyRepeat::No) {
+            if let Some(code) = hid_code(key) {
+                events.push(event(KIND_KEY_DOWN, code as u32));
+            }
+        }
+        for key in window.get_keys_released() {
+            if let Some(code) = hid_code(key) {
+                events.push(event(KIND_KEY_UP, code as u32));
+            }
+        }
+        events.append(&mut self.text.lock().unwrap());
+        if let Some((x, y)) = window.get_mouse_pos(MouseMode::Clamp) {
+            let pos = (x.max(0.0) as u32 & 0x3FFF, y.max(0.0) as u32 & 0x3FFF);
+            if self.mouse != Some(pos) {
+                self.mouse = Some(pos);
+                events.push(event(KIND_MOUSE_MOVE, pos.0 | pos.1 << 14));
+            }
+        }
+        let buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+        for (i, &button) in buttons.iter().enumerate() {
+            let down = window.get_mouse_down(button);
+            if down != self.buttons[i] {
+                self.buttons[i] = down;
+                events.push(event(if down { KIND_MOUSE_DOWN } else { KIND_MOUSE_UP }, i as u32));
+            }
+        }
+        if let Some((_, dy)) = window.get_scroll_wheel() {
+            let steps = dy.round().clamp(-128.0, 127.0) as i8;
+            if steps != 0 {
+                events.push(event(KIND_WHEEL, steps as u8 as u32));
+            }
+        }
+        if events.is_empty() {
+            return;
+        }
+        let bytes: Vec<u8> = events.iter().flat_map(|e| e.to_le_bytes()).collect();
+        let _ = self.socket.send_to(&bytes, INPUT_SOCKET);
+    }
+}
@@ -1,16 +1,24 @@
+mod input;
+
+use input::InputForwarder;
 use minifb::{Key, Window, WindowOptions};
 use std::fs;
 use std::thread;
 use std::time::Duration;
 use std::io::{self, Write};
 
-const WIDTH: usize = 640;
-const HEIGHT: usize = 480;
 const FILE: &str = "../framebuffer_dump.ppm";
 
-fn load_ppm(path: &str) -> Result<Vec<u32>, String> {
+/// Кадр из PPM: ширина, высота и пиксели 0x00RRGGBB
+struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+fn load_ppm(path: &str) -> Result<Frame, String> {
     let data = fs::read(path).map_err(|e| format!("Read error: {}", e))?;
-    let header_end = data.windows(3).position(|w| w == b"\n255").map(|i| i + 4).ok_or("Header end not found")?;
+    let header_end = data.windows(4).position(|w| w == b"\n255").map(|i| i + 5).ok_or("Header end not found")?;
     let header = String::from_utf8_lossy(&data[..header_end]);
     let mut lines = header.lines();
     if lines.next() != Some("P6") { return Err("Not P6".into()); }
@@ -19,35 +27,52 @@ fn load_ppm(path: &str) -> Result<Vec<u32>, String> {
     let w: usize = dims.next().ok_or("No width")?.parse().map_err(|_| "Bad width")?;
     let h: usize = dims.next().ok_or("No height")?.parse().map_err(|_| "Bad height")?;
     let maxval: usize = lines.next().ok_or("No maxval")?.parse().map_err(|_| "Bad maxval")?;
-    if w != WIDTH || h != HEIGHT || maxval != 255 { return Err(format!("Bad dims: {}x{}x{}", w, h, maxval)); }
+    if w == 0 || h == 0 || maxval != 255 { return Err(format!("Bad dims: {}x{}x{}", w, h, maxval)); }
     let pixel_data = &data[header_end..];
-    if pixel_data.len() < WIDTH * HEIGHT * 3 { return Err("Not enough pixel data".into()); }
-    let mut buf = Vec::with_capacity(WIDTH * HEIGHT);
-    for i in 0..(WIDTH * HEIGHT) {
+    if pixel_data.len() < w * h * 3 { return Err("Not enough pixel data".into()); }
+    let mut buf = Vec::with_capacity(w * h);
+    for i in 0..(w * h) {
         let r = pixel_data[i * 3] as u32;
         let g = pixel_data[i * 3 + 1] as u32;
         let b = pixel_data[i * 3 + 2] as u32;
         buf.push((r << 16) | (g << 8) | b);
     }
-    Ok(buf)
+    Ok(Frame { width: w, height: h, pixels: buf })
 }
 
-fn main() {
-    let mut window = Window::new(
+fn open_window(width: usize, height: usize) -> Window {
+    Window::new(
         "Framebuffer Viewer",
-        WIDTH,
-        HEIGHT,
+        width,
+        height,
         WindowOptions::default(),
     ).unwrap_or_else(|e| {
         eprintln!("Window error: {}", e);
         std::process::exit(1);
-    });
+    })
+}
+
+fn main() {
+    // Размер окна берётся из первого кадра и меняется вместе с разрешением гостя
+    let (mut width, mut height) = load_ppm(FILE).map(|f| (f.width, f.height)).unwrap_or((640, 480));
+    let mut window = open_window(width, height);
+    // Клавиатура и мышь окна уходят в устройство ввода VMM
+    let mut input = InputForwarder::new(&mut window).ok();
     let mut error_count = 0;
     let max_errors = 10;
     while window.is_open() && !window.is_key_down(Key::Escape) {
         match load_ppm(FILE) {
-            Ok(buf) => {
-                window.update_with_buffer(&buf, WIDTH, HEIGHT).unwrap();
+            Ok(frame) => {
+                if (frame.width, frame.height) != (width, height) {
+                    width = frame.width;
+                    height = frame.height;
+                    window = open_window(width, height);
+                    input = InputForwarder::new(&mut window).ok();
+                }
+                window.update_with_buffer(&frame.pixels, width, height).unwrap();
+                if let Some(input) = &mut input {
+                    input.forward(&window);
+                }
                 error_count = 0; // сброс при успехе
             }
             Err(e) => {
@@ -0,0 +1,95 @@
+//! Звуковое устройство гостя.
+//!
+//! Гость дописывает отсчёты (моно, i16, `SAMPLE_RATE` Гц) в кольцевой буфер по
+//! адресу `AUDIO_ADDR` и сообщает об этом записью в `AUDIO_PORT`. VMM забирает
+//! новые отсчёты и дописывает их в WAV-файл, так что звук можно проверить
+//! без звуковой карты. Раскладка кольца (game::audio::AudioInfo): счётчик
+//! записанных гостем отсчётов u32, счётчик прочитанных хостом u32, затем
+//! `AUDIO_FRAMES` отсчётов i16 little-endian.
+
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::bootinfo::{AUDIO_ADDR, AUDIO_FRAMES, GUEST_BASE};
+
+/// Порт устройства (kernel/src/io.rs); значение — счётчик записанных отсчётов
+pub const AUDIO_PORT: u16 = 0x0E08;
+/// Частота дискретизации (game::audio::SAMPLE_RATE)
+pub const SAMPLE_RATE: u32 = 22050;
+/// Файл, в который пишется звук гостя
+pub const AUDIO_DUMP: &str = "audio_dump.wav";
+
+pub struct AudioDevice {
+    file: Option<File>,
+    /// Сколько байт отсчётов уже в файле
+    data_len: u32,
+}
+
+impl AudioDevice {
+    /// Создать WAV-файл. Если не вышло, устройство работает, но звук никуда не пишется.
+    pub fn open() -> Self {
+        let file = match File::create(AUDIO_DUMP).and_then(|mut f| f.write_all(&wav_header(0)).map(|_| f)) {
+            Ok(f) => {
+                println!("[vmm] audio: пишем {} ({} Гц, моно)", AUDIO_DUMP, SAMPLE_RATE);
+                Some(f)
+            }
+            Err(e) => {
+                eprintln!("[vmm] audio: не удалось создать {}: {}", AUDIO_DUMP, e);
+                None
+            }
+        };
+        AudioDevice { file, data_len: 0 }
+    }
+
+    /// Ответ на запись гостем `AUDIO_PORT`: забрать отсчёты до счётчика `written`
+    /// и сдвинуть счётчик прочитанного в памяти гостя
+    pub fn drain(&mut self, guest_mem: &mut [u8], written: u32) {
+        let offset = AUDIO_ADDR - GUEST_BASE;
+        let Some(ring) = guest_mem.get_mut(offset..offset + 8 + AUDIO_FRAMES as usize * 2) else {
+            eprintln!("[vmm] audio: кольцо выходит за пределы памяти гостя");
+            return;
+        };
+        let read = u32::from_le_bytes(ring[4..8].try_into().unwrap());
+        // Гость не пишет больше ёмкости кольца; если счётчик испорчен, берём последнее кольцо
+        let count = written.wrapping_sub(read).min(AUDIO_FRAMES);
+        let mut data = Vec::with_capacity(count as usize * 2);
+        for i in 0..count {
+            let index = 8 + (read.wrapping_add(i) % AUDIO_FRAMES) as usize * 2;
+            data.extend_from_slice(&ring[index..index + 2]);
+        }
+        ring[4..8].copy_from_slice(&read.wrapping_add(count).to_le_bytes());
+        if let Err(e) = self.write(&data) {
+            eprintln!("[vmm] audio: ошибка записи {}: {}", AUDIO_DUMP, e);
+            self.file = None;
+        }
+    }
+
+    /// Дописать отсчёты и обновить размеры в заголовке, чтобы файл был целым в любой момент
+    fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let Some(file) = &mut self.file else { return Ok(()) };
+        file.write_all(data)?;
+        self.data_len += data.len() as u32;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&wav_header(self.data_len))?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+}
+
+/// Заголовок WAV (PCM, моно, 16 бит) для `data_len` байт отсчётов
+fn wav_header(data_len: u32) -> [u8; 44] {
+    let mut h = [0u8; 44];
+    h[0..4].copy_from_slice(b"RIFF");
+    h[4..8].copy_from_slice(&(36 + data_len).to_le_bytes());
+    h[8..16].copy_from_slice(b"WAVEfmt ");
+    h[16..20].copy_from_slice(&16u32.to_le_bytes());
+    h[20..22].copy_from_slice(&1u16.to_le_bytes()); // PCM
+    h[22..24].copy_from_slice(&1u16.to_le_bytes()); // каналов
+    h[24..28].copy_from_slice(&SAMPLE_RATE.to_le_bytes());
+    h[28..32].copy_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // байт в секунду
+    h[32..34].copy_from_slice(&2u16.to_le_bytes()); // байт на отсчёт
+    h[34..36].copy_from_slice(&16u16.to_le_bytes());
+    h[36..40].copy_from_slice(b"data");
+    h[40..44].copy_from_slice(&data_len.to_le_bytes());
+    h
+}
@@ -0,0 +1,156 @@
+//! Параметры загрузки для гостя: кадровый буфер, свободная память, кольца звука
+//! и профилировщика.
+//! Раскладка `BootInfo` должна совпадать с kernel/src/boot.rs.
+
+use crate::audio::SAMPLE_RATE;
+
+/// Физический адрес, с которого начинается память гостя
+pub const GUEST_BASE: usize = 0x100000;
+/// Физический адрес структуры BootInfo (kernel::boot::BOOT_INFO_ADDR)
+pub const BOOT_INFO_ADDR: usize = 0x1FF0_0000;
+/// Физический адрес кадрового буфера
+pub const FRAMEBUFFER_ADDR: usize = 0x2000_0000;
+/// Физический адрес кольцевого буфера звука (см. audio.rs)
+pub const AUDIO_ADDR: usize = 0x1FE0_0000;
+/// Ёмкость кольца в отсчётах
+pub const AUDIO_FRAMES: u32 = 8192;
+/// Физический адрес кольцевого буфера профилировщика (см. profile.rs)
+pub const PROFILE_ADDR: usize = 0x1FD0_0000;
+/// Ёмкость кольца в записях
+pub const PROFILE_CAPACITY: u32 = 4096;
+/// Свободная память, которую ядро отдаёт игре
+pub const SCRATCH_ADDR: usize = 0x0200_0000;
+pub const SCRATCH_SIZE: usize = PROFILE_ADDR - SCRATCH_ADDR;
+
+const BOOT_MAGIC: u32 = 0x4942_474E; // "NGBI"
+const BOOT_VERSION: u32 = 5;
+
+/// Формат пикселя (game::framebuffer::PixelFormat)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// В памяти байты B, G, R, X
+    Xrgb8888 = 0,
+    /// В памяти байты R, G, B, X
+    Xbgr8888 = 1,
+}
+
+impl PixelFormat {
+    /// Переставить байты пикселя из памяти гостя в порядок R, G, B
+    pub fn to_rgb(self, px: &[u8]) -> [u8; 3] {
+        match self {
+            PixelFormat::Xrgb8888 => [px[2], px[1], px[0]],
+            PixelFormat::Xbgr8888 => [px[0], px[1], px[2]],
+        }
+    }
+}
+
+/// Кадровый буфер, который VMM выделяет гостю
+#[derive(Clone, Copy, Debug)]
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Framebuffer { width, height, format: PixelFormat::Xrgb8888 }
+    }
+
+    /// Длина строки в байтах
+    pub fn stride(&self) -> usize {
+        self.width as usize * 4
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        self.stride() * self.height as usize
+    }
+
+    /// Смещение буфера в guest_mem
+    pub fn offset(&self) -> usize {
+        FRAMEBUFFER_ADDR - GUEST_BASE
+    }
+
+    /// Сколько памяти нужно гостю, чтобы в неё поместился кадровый буфер
+    pub fn guest_memory_size(&self) -> usize {
+        FRAMEBUFFER_ADDR + self.size_bytes() - GUEST_BASE
+    }
+
+    /// Разобрать размер вида "800x600"
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (w, h) = s.split_once('x').ok_or_else(|| format!("ожидается ШИРИНАxВЫСОТА, получено {}", s))?;
+        let w: u32 = w.parse().map_err(|_| format!("неверная ширина: {}", w))?;
+        let h: u32 = h.parse().map_err(|_| format!("неверная высота: {}", h))?;
+        if w == 0 || h == 0 {
+            return Err(format!("пустой кадр: {}", s));
+        }
+        Ok(Framebuffer::new(w, h))
+    }
+}
+
+/// Записать BootInfo в память гостя.
+///
+/// Раскладка (repr(C), little-endian):
+/// magic u32, version u32, fb.base u64, fb.width u32, fb.height u32, fb.stride u32,
+/// fb.format u32, scratch_base u64, scratch_size u64, game u32, reserved u32, tsc_hz u64,
+/// audio.base u64, audio.frames u32, audio.rate u32,
+/// profile.base u64, profile.capacity u32, reserved u32.
+///
+/// `game` — номер игрового модуля, который ядро запустит,
+/// `tsc_hz` — частота RDTSC (см. `measure_tsc_hz`), по ней гость меряет время.
+/// Счётчики колец звука и профилировщика обнуляются.
+pub fn write_boot_info(guest_mem: &mut [u8], fb: &Framebuffer, game: u32, tsc_hz: u64) -> Result<(), String> {
+    let mut buf = Vec::with_capacity(96);
+    buf.extend_from_slice(&BOOT_MAGIC.to_le_bytes());
+    buf.extend_from_slice(&BOOT_VERSION.to_le_bytes());
+    buf.extend_from_slice(&(FRAMEBUFFER_ADDR as u64).to_le_bytes());
+    buf.extend_from_slice(&fb.width.to_le_bytes());
+    buf.extend_from_slice(&fb.height.to_le_bytes());
+    buf.extend_from_slice(&(fb.stride() as u32).to_le_bytes());
+    buf.extend_from_slice(&(fb.format as u32).to_le_bytes());
+    buf.extend_from_slice(&(SCRATCH_ADDR as u64).to_le_bytes());
+    buf.extend_from_slice(&(SCRATCH_SIZE as u64).to_le_bytes());
+    buf.extend_from_slice(&game.to_le_bytes());
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    buf.extend_from_slice(&tsc_hz.to_le_bytes());
+    buf.extend_from_slice(&(AUDIO_ADDR as u64).to_le_bytes());
+    buf.extend_from_slice(&AUDIO_FRAMES.to_le_bytes());
+    buf.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    buf.extend_from_slice(&(PROFILE_ADDR as u64).to_le_bytes());
+    buf.extend_from_slice(&PROFILE_CAPACITY.to_le_bytes());
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    let audio = AUDIO_ADDR - GUEST_BASE;
+    guest_mem
+        .get_mut(audio..audio + 8)
+        .ok_or("кольцо звука выходит за пределы памяти гостя")?
+        .fill(0);
+    let profile = PROFILE_ADDR - GUEST_BASE;
+    guest_mem
+        .get_mut(profile..profile + 8)
+        .ok_or("кольцо профилировщика выходит за пределы памяти гостя")?
+        .fill(0);
+    let offset = BOOT_INFO_ADDR - GUEST_BASE;
+    let dst = guest_mem
+        .get_mut(offset..offset + buf.len())
+        .ok_or("BootInfo выходит за пределы памяти гостя")?;
+    dst.copy_from_slice(&buf);
+    Ok(())
+}
+
+/// Измерить частоту счётчика тактов хоста. Гость под KVM видит тот же счётчик.
+#[cfg(target_arch = "x86_64")]
+pub fn measure_tsc_hz() -> u64 {
+    use std::arch::x86_64::_rdtsc;
+    use std::time::{Duration, Instant};
+    let start = Instant::now();
+    let t0 = unsafe { _rdtsc() };
+    std::thread::sleep(Duration::from_millis(50));
+    let t1 = unsafe { _rdtsc() };
+    let secs = start.elapsed().as_secs_f64();
+    (t1.wrapping_sub(t0) as f64 / secs) as u64
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub fn measure_tsc_hz() -> u64 {
+    0
+}
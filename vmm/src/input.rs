@@ -0,0 +1,76 @@
+//! Устройство ввода гостя.
+//!
+//! Источник событий (например, viewer) шлёт датаграммы в `INPUT_SOCKET`:
+//! каждое событие — 32-битное слово little-endian в формате `game::input::Event`.
+//! Гость читает слова по одному из `INPUT_PORT`; пустая очередь отдаёт 0.
+
+use std::collections::VecDeque;
+use std::os::unix::net::UnixDatagram;
+
+/// Порт устройства (kernel/src/io.rs)
+pub const INPUT_PORT: u16 = 0x0E04;
+/// Сокет, в который источник ввода шлёт события
+pub const INPUT_SOCKET: &str = "/tmp/vmm_input.sock";
+/// Сколько событий хранится, пока гость их не забрал; старые отбрасываются
+const QUEUE_LIMIT: usize = 1024;
+
+pub struct InputDevice {
+    socket: Option<UnixDatagram>,
+    queue: VecDeque<u32>,
+}
+
+impl InputDevice {
+    /// Открыть сокет. Если не вышло, устройство работает, но событий не будет.
+    pub fn open() -> Self {
+        let _ = std::fs::remove_file(INPUT_SOCKET);
+        let socket = match UnixDatagram::bind(INPUT_SOCKET).and_then(|s| s.set_nonblocking(true).map(|_| s)) {
+            Ok(s) => {
+                println!("[vmm] input: слушаем {}", INPUT_SOCKET);
+                Some(s)
+            }
+            Err(e) => {
+                eprintln!("[vmm] input: не удалось открыть {}: {}", INPUT_SOCKET, e);
+                None
+            }
+        };
+        InputDevice { socket, queue: VecDeque::new() }
+    }
+
+    /// Добавить событие в очередь (для источников внутри VMM)
+    pub fn push(&mut self, event: u32) {
+        if event == 0 {
+            return;
+        }
+        if self.queue.len() == QUEUE_LIMIT {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(event);
+    }
+
+    /// Забрать всё, что пришло в сокет
+    fn poll(&mut self) {
+        let Some(socket) = &self.socket else { return };
+        let mut buf = [0u8; 4096];
+        let mut received = Vec::new();
+        while let Ok(n) = socket.recv(&mut buf) {
+            received.extend(buf[..n].chunks_exact(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())));
+        }
+        for event in received {
+            self.push(event);
+        }
+    }
+
+    /// Ответ на чтение гостем `INPUT_PORT`
+    pub fn read(&mut self) -> u32 {
+        self.poll();
+        self.queue.pop_front().unwrap_or(0)
+    }
+}
+
+impl Drop for InputDevice {
+    fn drop(&mut self) {
+        if self.socket.is_some() {
+            let _ = std::fs::remove_file(INPUT_SOCKET);
+        }
+    }
+}
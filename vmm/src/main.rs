@@ -1,7 +1,15 @@
 mod syscall;
 mod kvmproxy;
-
+mod audio;
+mod bootinfo;
+mod input;
+mod profile;
+
+use crate::audio::{AudioDevice, AUDIO_PORT};
+use crate::bootinfo::{measure_tsc_hz, write_boot_info, Framebuffer};
+use crate::input::{InputDevice, INPUT_PORT};
 use crate::kvmproxy::KvmProxy;
+use crate::profile::{Profiler, PROFILE_PORT};
 use crate::syscall::{sys_ioctl, sys_mmap, sys_open, KVM_CREATE_VM, KVM_CREATE_VCPU, MAP_SHARED, O_RDWR, PROT_READ, PROT_WRITE};
 use core::ptr;
 use std::io::Write;
@@ -104,24 +112,53 @@ pub struct Vmm {
     pub vm_id: u64,
     pub vcpu_id: u64,
     pub guest_mem: Vec<u8>,
+    pub fb: Framebuffer,
 }
 
 const KVM_CREATE_IRQCHIP: usize = 0xae60;
 
-pub fn create_vm(memory_size: usize) -> Result<Vmm, String> {
+pub fn create_vm(memory_size: usize, fb: Framebuffer) -> Result<Vmm, String> {
     let mut proxy = open_kvm()?;
     let vm_id = create_vm_fd(&mut proxy)?;
     let vcpu_id = create_vcpu_fd(&mut proxy)?;
     let guest_mem = vec![0u8; memory_size];
-    Ok(Vmm { proxy, vm_id, vcpu_id, guest_mem })
+    Ok(Vmm { proxy, vm_id, vcpu_id, guest_mem, fb })
 }
 
-pub fn run_vcpu(proxy: &mut KvmProxy, vcpu_id: u64) -> Result<(), String> {
-    // KVM_RUN: req, vcpu_id (8 байт), ответ — 4 байта exit_reason
-    let arg = vcpu_id.to_le_bytes();
-    let resp = proxy.ioctl(0xAE44, Some(&arg), 4)?;
-    let exit_reason = u32::from_le_bytes(resp.try_into().unwrap());
-    if exit_reason == 5 { Ok(()) } else { Err(format!("KVM_RUN exit_reason: {}", exit_reason)) }
+/// Почему гость остановился
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcpuExit {
+    Hlt,
+    /// Гость записал `data` (`size` байт) в порт `port`
+    IoOut { port: u16, size: u8, data: u32 },
+    /// Гость читает `size` байт из порта `port`
+    IoIn { port: u16, size: u8 },
+}
+
+/// Запустить VCPU до следующего выхода.
+///
+/// `io_in` — значение для предыдущего выхода `IoIn`: гость получит его как результат `in`.
+pub fn run_vcpu(proxy: &mut KvmProxy, vcpu_id: u64, io_in: u32) -> Result<VcpuExit, String> {
+    // KVM_RUN: req, vcpu_id (8 байт), io_in (4 байта), ответ — 12 байт:
+    // exit_reason u32, для KVM_EXIT_IO ещё port u16, direction u8, size u8, data u32
+    let mut arg = vcpu_id.to_le_bytes().to_vec();
+    arg.extend_from_slice(&io_in.to_le_bytes());
+    let resp = proxy.ioctl(0xAE44, Some(&arg), 12)?;
+    let exit_reason = u32::from_le_bytes(resp[0..4].try_into().unwrap());
+    match exit_reason {
+        KVM_EXIT_HLT => Ok(VcpuExit::Hlt),
+        KVM_EXIT_IO => {
+            let port = u16::from_le_bytes(resp[4..6].try_into().unwrap());
+            let (direction, size) = (resp[6], resp[7]);
+            let data = u32::from_le_bytes(resp[8..12].try_into().unwrap());
+            if direction == KVM_EXIT_IO_OUT {
+                Ok(VcpuExit::IoOut { port, size, data })
+            } else {
+                Ok(VcpuExit::IoIn { port, size })
+            }
+        }
+        _ => Err(format!("KVM_RUN exit_reason: {}", exit_reason)),
+    }
 }
 
 const KVM_GET_SREGS: usize = 0x8138AE80;
@@ -245,6 +282,11 @@ use crate::syscall::*;
 
 const KVM_EXIT_IO: u32  = 2;
 const KVM_EXIT_HLT: u32 = 5;
+const KVM_EXIT_IO_OUT: u8 = 1;
+
+/// Запись в этот порт — гость закончил кадр и скопировал его в видимый буфер
+/// (kernel/src/io.rs). Значение — номер кадра.
+const PRESENT_PORT: u16 = 0x0E00;
 
 #[repr(C)]
 struct kvm_run_io {
@@ -256,43 +298,52 @@ struct kvm_run_io {
     _pad: [u8; 40],
 }
 
-const FRAMEBUFFER_ADDR: usize = 0x2000_0000;
-const WIDTH: usize = 640;
-const HEIGHT: usize = 480;
+/// Срез памяти гостя с кадровым буфером
+fn framebuffer_slice(vm: &Vmm) -> Result<&[u8], String> {
+    let fb_offset = vm.fb.offset();
+    let fb_size = vm.fb.size_bytes();
+    vm.guest_mem
+        .get(fb_offset..fb_offset + fb_size)
+        .ok_or_else(|| "Framebuffer выходит за пределы выделенной памяти VM".to_string())
+}
 
 /// Дампит содержимое guest framebuffer в файл "frame_<num>.ppm"
 fn dump_frame(vm: &Vmm, frame_num: usize) -> Result<(), String> {
     use std::fs::File;
     use std::io::Write;
-    let fb_offset = 0x2000_0000 - 0x100000;
-    let fb_size = 640 * 480 * 4;
-    if vm.guest_mem.len() < fb_offset + fb_size {
-        return Err("Framebuffer выходит за пределы выделенной памяти VM".to_string());
-    }
-    let fb_slice = &vm.guest_mem[fb_offset..fb_offset+fb_size];
+    let fb_slice = framebuffer_slice(vm)?;
     let path = format!("frames/frame_{}.ppm", frame_num);
     let mut file = File::create(&path).map_err(|e| e.to_string())?;
-    file.write_all(format!("P6\n{} {}\n255\n", 640, 480).as_bytes())
+    file.write_all(format!("P6\n{} {}\n255\n", vm.fb.width, vm.fb.height).as_bytes())
         .map_err(|e| e.to_string())?;
     for px in fb_slice.chunks(4) {
-        file.write_all(&px[0..3]).map_err(|e| e.to_string())?;
+        file.write_all(&vm.fb.format.to_rgb(px)).map_err(|e| e.to_string())?;
     }
     Ok(())
 }
 
-// После KVM_RUN отправляем framebuffer в эмулятор
+// После present гостя отправляем framebuffer в эмулятор
 pub fn send_framebuffer(vm: &Vmm) {
     println!("[vmm] send_framebuffer: start");
-    let fb_offset = 0x2000_0000 - 0x100000;
-    let fb_size = 640 * 480 * 4;
-    let fb_slice = &vm.guest_mem[fb_offset..fb_offset+fb_size];
+    let fb_slice = match framebuffer_slice(vm) {
+        Ok(fb) => fb,
+        Err(e) => {
+            println!("[vmm] send_framebuffer: {}", e);
+            return;
+        }
+    };
     // Диагностика: дамп первых 64 байт framebuffer
     let dump = fb_slice.iter().take(64).map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
     println!("[vmm] framebuffer head (64): {} ({} bytes)", dump, fb_slice.len());
     match std::os::unix::net::UnixStream::connect("/tmp/mykvm.sock") {
         Ok(mut sock) => {
             println!("[vmm] send_framebuffer: connected to mykvm.sock");
-            if let Err(e) = sock.write_all(b"FRAMEBUFFER") {
+            // Заголовок: "FRAMEBUFFER", затем ширина, высота и формат (u32 LE)
+            let mut header = b"FRAMEBUFFER".to_vec();
+            header.extend_from_slice(&vm.fb.width.to_le_bytes());
+            header.extend_from_slice(&vm.fb.height.to_le_bytes());
+            header.extend_from_slice(&(vm.fb.format as u32).to_le_bytes());
+            if let Err(e) = sock.write_all(&header) {
                 println!("[vmm] send_framebuffer: failed to send header: {}", e);
                 return;
             }
@@ -316,9 +367,30 @@ fn main() {
     println!("[vmm] kvm_segment size: {} align: {}", size_of::<kvm_segment>(), align_of::<kvm_segment>());
 
     let kernel_path = "../kernel/target/x86_64-unknown-none/debug/kernel";
-    let memory_size = 0x20200000;
+    // Разрешение можно задать первым аргументом: `vmm 800x600`
+    let fb = match std::env::args().nth(1) {
+        Some(arg) => match Framebuffer::parse(&arg) {
+            Ok(fb) => fb,
+            Err(e) => {
+                eprintln!("[vmm] {}", e);
+                return;
+            }
+        },
+        None => Framebuffer::new(640, 480),
+    };
+    // Номер игры в ядре — вторым аргументом: `vmm 800x600 1`
+    let game = match std::env::args().nth(2).map(|s| s.parse::<u32>()) {
+        Some(Ok(game)) => game,
+        Some(Err(e)) => {
+            eprintln!("[vmm] неверный номер игры: {}", e);
+            return;
+        }
+        None => 0,
+    };
+    println!("[vmm] framebuffer {}x{} {:?}, game {}", fb.width, fb.height, fb.format, game);
+    let memory_size = fb.guest_memory_size();
     println!("[vmm] before create_vm");
-    let mut vmm = match create_vm(memory_size) {
+    let mut vmm = match create_vm(memory_size, fb) {
         Ok(vmm) => vmm,
         Err(e) => {
             eprintln!("[vmm] create_vm error: {}", e);
@@ -330,10 +402,15 @@ fn main() {
         eprintln!("[vmm] load_guest_kernel error: {}", e);
     }
     println!("[vmm] after load_guest_kernel");
+    let tsc_hz = measure_tsc_hz();
+    println!("[vmm] частота TSC: {} Гц", tsc_hz);
+    if let Err(e) = write_boot_info(&mut vmm.guest_mem, &fb, game, tsc_hz) {
+        eprintln!("[vmm] write_boot_info error: {}", e);
+    }
     use std::time::Instant;
     let start_time = Instant::now();
     let timeout = std::time::Duration::from_secs(10); // 10 секунд
-    let mut sregs = match get_sregs(&mut vmm.proxy, vmm.vcpu_id) {
+    let sregs = match get_sregs(&mut vmm.proxy, vmm.vcpu_id) {
         Ok(s) => {
             println!("[vmm] get_sregs: успешно получено {} байт", s.len());
             s
@@ -343,7 +420,7 @@ fn main() {
             vec![0u8; 312]
         }
     };
-    let mut regs = match get_regs(&mut vmm.proxy, vmm.vcpu_id) {
+    let regs = match get_regs(&mut vmm.proxy, vmm.vcpu_id) {
         Ok(r) => {
             println!("[vmm] get_regs: успешно получено {} байт", r.len());
             r
@@ -354,39 +431,45 @@ fn main() {
         }
     };
 
-    for frame in 0..300 {
+    println!("[vmm] before setup_sregs");
+    match setup_sregs(&mut vmm.proxy, vmm.vcpu_id, &sregs) {
+        Ok(()) => println!("[vmm] setup_sregs: ok"),
+        Err(e) => eprintln!("[vmm] setup_sregs error: {}", e),
+    }
+    println!("[vmm] before setup_regs");
+    match setup_regs(&mut vmm.proxy, vmm.vcpu_id, &regs) {
+        Ok(()) => println!("[vmm] setup_regs: ok"),
+        Err(e) => eprintln!("[vmm] setup_regs error: {}", e),
+    }
+
+    // Гость продолжает с места остановки; кадр отправляем только после его present,
+    // поэтому наполовину нарисованные кадры наружу не попадают
+    let mut input = InputDevice::open();
+    let mut audio = AudioDevice::open();
+    let mut profiler = Profiler::open(tsc_hz);
+    let mut io_in = 0;
+    let mut presented = 0;
+    while presented < 300 {
         if start_time.elapsed() > timeout {
             println!("[vmm] Таймаут: выполнение завершено через 10 секунд");
             break;
         }
-        println!("[vmm] === FRAME {} ===", frame);
-        println!("[vmm] before setup_sregs");
-        let sregs_result = setup_sregs(&mut vmm.proxy, vmm.vcpu_id, &sregs);
-        if let Err(e) = &sregs_result {
-            eprintln!("[vmm] setup_sregs error: {}", e);
-        } else {
-            println!("[vmm] setup_sregs: ok");
-        }
-        println!("[vmm] after setup_sregs");
-        println!("[vmm] before setup_regs");
-        let regs_result = setup_regs(&mut vmm.proxy, vmm.vcpu_id, &regs);
-        if let Err(e) = &regs_result {
-            eprintln!("[vmm] setup_regs error: {}", e);
-        } else {
-            println!("[vmm] setup_regs: ok");
-        }
-        println!("[vmm] after setup_regs");
-        println!("[vmm] before run_vcpu");
-        let run_result = run_vcpu(&mut vmm.proxy, vmm.vcpu_id);
-        if let Err(e) = &run_result {
-            eprintln!("[vmm] run_vcpu error: {}", e);
-        } else {
-            println!("[vmm] run_vcpu: ok");
+        match run_vcpu(&mut vmm.proxy, vmm.vcpu_id, io_in) {
+            Ok(VcpuExit::IoOut { port: PRESENT_PORT, data, .. }) => {
+                println!("[vmm] === FRAME {} (guest frame {}) ===", presented, data);
+                send_framebuffer(&vmm);
+                presented += 1;
+                std::thread::sleep(std::time::Duration::from_millis(40));
+            }
+            Ok(VcpuExit::IoOut { port: AUDIO_PORT, data, .. }) => audio.drain(&mut vmm.guest_mem, data),
+            Ok(VcpuExit::IoOut { port: PROFILE_PORT, data, .. }) => profiler.drain(&mut vmm.guest_mem, data),
+            Ok(VcpuExit::IoIn { port: INPUT_PORT, .. }) => io_in = input.read(),
+            Ok(VcpuExit::Hlt) => {}
+            Ok(exit) => println!("[vmm] необработанный выход: {:?}", exit),
+            Err(e) => {
+                eprintln!("[vmm] run_vcpu error: {}", e);
+                break;
+            }
         }
-        println!("[vmm] after run_vcpu");
-        println!("[vmm] before send_framebuffer");
-        send_framebuffer(&vmm);
-        println!("[vmm] after send_framebuffer");
-        std::thread::sleep(std::time::Duration::from_millis(40));
     }
 }
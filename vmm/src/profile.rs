@@ -0,0 +1,136 @@
+//! Профилировщик гостя.
+//!
+//! Гость замеряет участки кадра по RDTSC (game::profile), складывает записи в
+//! кольцевой буфер по адресу `PROFILE_ADDR` и после кадра сообщает их число
+//! записью в `PROFILE_PORT`. VMM переводит такты в микросекунды и дописывает
+//! участки в файл Chrome trace (JSON Array Format), который открывается в
+//! chrome://tracing или Perfetto. Раскладка кольца (game::profile::ProfileInfo):
+//! счётчик записей гостя u32, счётчик прочитанных хостом u32, затем
+//! `PROFILE_CAPACITY` записей по 24 байта: start u64, name u64 (адрес UTF-8
+//! строки в памяти гостя), duration u32, name_len u32.
+
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::bootinfo::{GUEST_BASE, PROFILE_ADDR, PROFILE_CAPACITY};
+
+/// Порт устройства (kernel/src/io.rs); значение — счётчик записей гостя
+pub const PROFILE_PORT: u16 = 0x0E0C;
+/// Файл, в который пишется трасса
+pub const PROFILE_DUMP: &str = "profile_trace.json";
+/// Размер одной записи в кольце
+const RECORD_SIZE: usize = 24;
+
+pub struct Profiler {
+    file: Option<File>,
+    /// Тактов в микросекунде
+    ticks_per_us: f64,
+    /// Такт, от которого отсчитывается время трассы; берётся из первых записей
+    origin: Option<u64>,
+    events: u64,
+}
+
+impl Profiler {
+    /// Создать файл трассы. `tsc_hz` — частота RDTSC гостя; если она неизвестна (0),
+    /// время в трассе идёт в тактах. Если файл не создался, записи просто забираются.
+    pub fn open(tsc_hz: u64) -> Self {
+        let file = match File::create(PROFILE_DUMP).and_then(|mut f| f.write_all(b"[\n]").map(|_| f)) {
+            Ok(f) => {
+                println!("[vmm] profile: пишем {}", PROFILE_DUMP);
+                Some(f)
+            }
+            Err(e) => {
+                eprintln!("[vmm] profile: не удалось создать {}: {}", PROFILE_DUMP, e);
+                None
+            }
+        };
+        let ticks_per_us = if tsc_hz == 0 { 1.0 } else { tsc_hz as f64 / 1e6 };
+        Profiler { file, ticks_per_us, origin: None, events: 0 }
+    }
+
+    /// Ответ на запись гостем `PROFILE_PORT`: забрать записи до счётчика `written`
+    /// и сдвинуть счётчик прочитанного в памяти гостя
+    pub fn drain(&mut self, guest_mem: &mut [u8], written: u32) {
+        let offset = PROFILE_ADDR - GUEST_BASE;
+        let Some(ring) = guest_mem.get_mut(offset..offset + 8 + PROFILE_CAPACITY as usize * RECORD_SIZE) else {
+            eprintln!("[vmm] profile: кольцо выходит за пределы памяти гостя");
+            return;
+        };
+        let read = u32::from_le_bytes(ring[4..8].try_into().unwrap());
+        // Гость затирает старые записи, если мы не успели: берём последнее кольцо
+        let count = written.wrapping_sub(read).min(PROFILE_CAPACITY);
+        let first = written.wrapping_sub(count);
+        let mut records = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let index = 8 + (first.wrapping_add(i) % PROFILE_CAPACITY) as usize * RECORD_SIZE;
+            let r = &ring[index..index + RECORD_SIZE];
+            let start = u64::from_le_bytes(r[0..8].try_into().unwrap());
+            let name = u64::from_le_bytes(r[8..16].try_into().unwrap());
+            let duration = u32::from_le_bytes(r[16..20].try_into().unwrap());
+            let name_len = u32::from_le_bytes(r[20..24].try_into().unwrap());
+            records.push((start, name, duration, name_len));
+        }
+        ring[4..8].copy_from_slice(&written.to_le_bytes());
+        // Участки записываются при выходе, поэтому внешний (кадр) идёт последним,
+        // а начался раньше всех
+        let origin = *self.origin.get_or_insert_with(|| records.iter().map(|r| r.0).min().unwrap_or(0));
+        let mut json = String::new();
+        for (start, name, duration, name_len) in records {
+            let name = guest_str(guest_mem, name, name_len);
+            let ts = start.saturating_sub(origin) as f64 / self.ticks_per_us;
+            let dur = duration as f64 / self.ticks_per_us;
+            if self.events > 0 {
+                json.push(',');
+            }
+            json.push_str(&format!(
+                "\n{{\"name\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":1}}",
+                escape(&name),
+                ts,
+                dur
+            ));
+            self.events += 1;
+        }
+        if let Err(e) = self.write(&json) {
+            eprintln!("[vmm] profile: ошибка записи {}: {}", PROFILE_DUMP, e);
+            self.file = None;
+        }
+    }
+
+    /// Дописать события перед закрывающей скобкой, чтобы файл был целым в любой момент
+    fn write(&mut self, json: &str) -> std::io::Result<()> {
+        let Some(file) = &mut self.file else { return Ok(()) };
+        if json.is_empty() {
+            return Ok(());
+        }
+        file.seek(SeekFrom::End(-2))?;
+        file.write_all(json.as_bytes())?;
+        file.write_all(b"\n]")?;
+        Ok(())
+    }
+}
+
+/// Имя участка из памяти гостя; ядро слинковано по физическим адресам, поэтому
+/// адрес строки — это смещение от `GUEST_BASE`
+fn guest_str(guest_mem: &[u8], addr: u64, len: u32) -> String {
+    let bytes = (addr as usize)
+        .checked_sub(GUEST_BASE)
+        .and_then(|start| guest_mem.get(start..start.checked_add(len as usize)?));
+    match bytes {
+        Some(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        None => format!("?{:#x}", addr),
+    }
+}
+
+/// Экранировать строку для JSON
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}